@@ -0,0 +1,178 @@
+//! Axis-aligned boxes of integer points in any number of dimensions.
+
+/// The points `p` with `min[i] <= p[i] < max[i]` in every dimension.  Empty if any `max[i] <= min[i]`.
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
+pub struct AABox<const N: usize> {
+  pub min: [i64; N],
+  pub max: [i64; N],
+}
+
+impl<const N: usize> AABox<N> {
+  pub fn new(min: [i64; N], max: [i64; N]) -> Self {
+    Self { min, max }
+  }
+
+  /// The smallest box holding both corners, which are themselves inside it.
+  pub fn from_corners(a: [i64; N], b: [i64; N]) -> Self {
+    Self {
+      min: std::array::from_fn(|i| a[i].min(b[i])),
+      max: std::array::from_fn(|i| a[i].max(b[i]) + 1),
+    }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    (0..N).any(|i| self.max[i] <= self.min[i])
+  }
+
+  pub fn volume(&self) -> u64 {
+    if self.is_empty() {
+      return 0;
+    }
+    (0..N).map(|i| (self.max[i] - self.min[i]) as u64).product()
+  }
+
+  pub fn contains(&self, p: [i64; N]) -> bool {
+    (0..N).all(|i| self.min[i] <= p[i] && p[i] < self.max[i])
+  }
+
+  pub fn contains_box(&self, other: &Self) -> bool {
+    other.is_empty() || (0..N).all(|i| self.min[i] <= other.min[i] && other.max[i] <= self.max[i])
+  }
+
+  /// `None` if they don't overlap.
+  pub fn intersection(self, other: Self) -> Option<Self> {
+    let b = Self {
+      min: std::array::from_fn(|i| self.min[i].max(other.min[i])),
+      max: std::array::from_fn(|i| self.max[i].min(other.max[i])),
+    };
+    (!b.is_empty()).then_some(b)
+  }
+
+  /// What is left of `self` once `other` is cut out, as at most `2 * N` disjoint boxes.  Each dimension in turn
+  /// has the slabs either side of `other` sliced off.
+  pub fn subtract(self, other: Self) -> Vec<Self> {
+    let cut = match self.intersection(other) {
+      Some(cut) => cut,
+      None if self.is_empty() => return vec![],
+      None => return vec![self],
+    };
+    let mut pieces = Vec::new();
+    let mut rest = self;
+    for i in 0..N {
+      if rest.min[i] < cut.min[i] {
+        let mut piece = rest;
+        piece.max[i] = cut.min[i];
+        pieces.push(piece);
+        rest.min[i] = cut.min[i];
+      }
+      if cut.max[i] < rest.max[i] {
+        let mut piece = rest;
+        piece.min[i] = cut.max[i];
+        pieces.push(piece);
+        rest.max[i] = cut.max[i];
+      }
+    }
+    pieces
+  }
+
+  /// Every point, varying the last coordinate fastest.
+  pub fn points(&self) -> impl Iterator<Item = [i64; N]> {
+    let b = *self;
+    let first = (!b.is_empty()).then_some(b.min);
+    std::iter::successors(first, move |&p| {
+      let mut p = p;
+      for i in (0..N).rev() {
+        p[i] += 1;
+        if p[i] < b.max[i] {
+          return Some(p);
+        }
+        p[i] = b.min[i];
+      }
+      None
+    })
+  }
+}
+
+/// How many points are in at least one of the boxes.
+pub fn union_volume<const N: usize>(boxes: impl IntoIterator<Item = AABox<N>>) -> u64 {
+  let mut disjoint: Vec<AABox<N>> = Vec::new();
+  for b in boxes {
+    let mut pieces = vec![b];
+    for d in &disjoint {
+      pieces = pieces.into_iter().flat_map(|p| p.subtract(*d)).collect();
+    }
+    disjoint.append(&mut pieces);
+  }
+  disjoint.iter().map(AABox::volume).sum()
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use std::collections::HashSet;
+
+  #[test]
+  fn test_box() {
+    let b = AABox::from_corners([3, 1], [0, 2]);
+    assert_eq!(b, AABox::new([0, 1], [4, 3]));
+    assert_eq!(b.volume(), 8);
+    assert!(b.contains([3, 2]));
+    assert!(!b.contains([4, 2]));
+    assert!(b.contains_box(&AABox::new([1, 1], [2, 3])));
+    assert!(!b.contains_box(&AABox::new([1, 1], [2, 4])));
+    assert_eq!(
+      b.intersection(AABox::new([2, 0], [9, 2])),
+      Some(AABox::new([2, 1], [4, 2]))
+    );
+    assert_eq!(b.intersection(AABox::new([4, 0], [9, 2])), None);
+    assert_eq!(
+      b.points().collect::<Vec<_>>()[..3],
+      [[0, 1], [0, 2], [1, 1]]
+    );
+    assert_eq!(AABox::new([0, 0, 0], [2, 0, 2]).points().count(), 0);
+    assert_eq!(AABox::new([0, 0, 0], [2, 0, 2]).volume(), 0);
+  }
+
+  #[test]
+  fn test_subtract() {
+    let a = AABox::new([0, 0, 0], [4, 4, 4]);
+    assert_eq!(a.subtract(a), vec![]);
+    assert_eq!(a.subtract(AABox::new([5, 5, 5], [6, 6, 6])), vec![a]);
+    assert_eq!(a.subtract(AABox::new([1, 1, 1], [3, 3, 3])).len(), 6);
+
+    // Every box with corners in a 3x3x3 cube, against every other.
+    let spans = [(0, 0), (0, 1), (0, 2), (1, 1), (1, 2), (2, 2)];
+    let mut boxes = Vec::new();
+    for (x0, x1) in spans {
+      for (y0, y1) in spans {
+        for (z0, z1) in spans {
+          boxes.push(AABox::from_corners([x0, y0, z0], [x1, y1, z1]));
+        }
+      }
+    }
+    for (a, b) in boxes
+      .iter()
+      .flat_map(|&a| boxes.iter().map(move |&b| (a, b)))
+    {
+      let pieces = a.subtract(b);
+      let points: Vec<_> = pieces.iter().flat_map(|p| p.points()).collect();
+      let expected: HashSet<_> = a.points().filter(|&p| !b.contains(p)).collect();
+      assert_eq!(points.len(), expected.len(), "pieces overlap");
+      assert_eq!(points.into_iter().collect::<HashSet<_>>(), expected);
+    }
+  }
+
+  #[test]
+  fn test_union_volume() {
+    let boxes = [
+      AABox::new([0, 0], [10, 10]),
+      AABox::new([5, 5], [15, 15]),
+      AABox::new([2, 2], [3, 3]),
+    ];
+    assert_eq!(union_volume(boxes), 175);
+    assert_eq!(union_volume::<2>([]), 0);
+
+    let points: HashSet<_> = boxes.iter().flat_map(|b| b.points()).collect();
+    assert_eq!(points.len(), 175);
+  }
+}
@@ -0,0 +1,106 @@
+//! The ways a day can fail, so that bad input is reported rather than panicked on.
+
+use std::fmt;
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum Error {
+  /// The parser couldn't make sense of the input at this point.
+  Parse {
+    line: usize,
+    column: usize,
+    snippet: String,
+  },
+  /// The parser finished, but left more than whitespace behind.
+  TrailingInput {
+    line: usize,
+    column: usize,
+    snippet: String,
+  },
+  /// The input is well-formed, but the puzzle has no answer for it.
+  NoSolution,
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// 1-based line and column of `remaining` within `input`, plus the rest of that line.
+fn locate(input: &str, remaining: &str) -> (usize, usize, String) {
+  let consumed = &input[..input.len() - remaining.len()];
+  let line = consumed.matches('\n').count() + 1;
+  let column = consumed
+    .rsplit('\n')
+    .next()
+    .map_or(0, |l| l.chars().count())
+    + 1;
+  let snippet = remaining.lines().next().unwrap_or("").to_owned();
+  (line, column, snippet)
+}
+
+impl Error {
+  /// A parse error pointing at `remaining`, which must be a suffix of `input`.
+  pub fn parse(input: &str, remaining: &str) -> Self {
+    let (line, column, snippet) = locate(input, remaining);
+    Error::Parse {
+      line,
+      column,
+      snippet,
+    }
+  }
+
+  pub fn trailing_input(input: &str, remaining: &str) -> Self {
+    let (line, column, snippet) = locate(input, remaining);
+    Error::TrailingInput {
+      line,
+      column,
+      snippet,
+    }
+  }
+
+  pub fn from_nom(input: &str, err: nom::Err<nom::error::Error<&str>>) -> Self {
+    match err {
+      nom::Err::Incomplete(_) => Error::parse(input, ""),
+      nom::Err::Error(e) | nom::Err::Failure(e) => Error::parse(input, e.input),
+    }
+  }
+}
+
+impl fmt::Display for Error {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Error::Parse {
+        line,
+        column,
+        snippet,
+      } => write!(
+        f,
+        "parse error at line {}, column {}: {:?}",
+        line, column, snippet
+      ),
+      Error::TrailingInput {
+        line,
+        column,
+        snippet,
+      } => write!(
+        f,
+        "unparsed input at line {}, column {}: {:?}",
+        line, column, snippet
+      ),
+      Error::NoSolution => write!(f, "no solution exists"),
+    }
+  }
+}
+
+impl std::error::Error for Error {}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_display() {
+    assert_eq!(
+      Error::parse("ab\ncd", "d").to_string(),
+      "parse error at line 2, column 2: \"d\""
+    );
+    assert_eq!(Error::NoSolution.to_string(), "no solution exists");
+  }
+}
@@ -0,0 +1,308 @@
+//! A rectangular grid, stored row by row.  Coordinates are `(x, y)`, with `(0, 0)` at the top left.
+
+use crate::nom_prelude::*;
+use std::fmt;
+use std::ops::{Index, IndexMut};
+
+pub type Coord = (usize, usize);
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Grid<T> {
+  width: usize,
+  height: usize,
+  cells: Vec<T>,
+}
+
+impl<T: Clone> Grid<T> {
+  pub fn new(width: usize, height: usize, fill: T) -> Self {
+    Self {
+      width,
+      height,
+      cells: vec![fill; width * height],
+    }
+  }
+}
+
+impl<T> Grid<T> {
+  pub fn from_vec(cells: Vec<T>, width: usize, height: usize) -> Option<Self> {
+    if cells.len() == width * height {
+      Some(Self {
+        width,
+        height,
+        cells,
+      })
+    } else {
+      None
+    }
+  }
+
+  /// `None` unless every row is the same length.
+  pub fn from_rows(rows: Vec<Vec<T>>) -> Option<Self> {
+    let height = rows.len();
+    let width = rows.first().map_or(0, Vec::len);
+    if rows.iter().any(|r| r.len() != width) {
+      return None;
+    }
+    Self::from_vec(rows.into_iter().flatten().collect(), width, height)
+  }
+
+  pub fn from_fn(width: usize, height: usize, mut f: impl FnMut(Coord) -> T) -> Self {
+    let cells = (0..height)
+      .flat_map(|y| (0..width).map(move |x| (x, y)))
+      .map(&mut f)
+      .collect();
+    Self {
+      width,
+      height,
+      cells,
+    }
+  }
+
+  pub fn width(&self) -> usize {
+    self.width
+  }
+
+  pub fn height(&self) -> usize {
+    self.height
+  }
+
+  pub fn len(&self) -> usize {
+    self.cells.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.cells.is_empty()
+  }
+
+  fn index_of(&self, (x, y): Coord) -> Option<usize> {
+    if x < self.width && y < self.height {
+      Some(y * self.width + x)
+    } else {
+      None
+    }
+  }
+
+  pub fn get(&self, c: Coord) -> Option<&T> {
+    self.index_of(c).map(|i| &self.cells[i])
+  }
+
+  pub fn get_mut(&mut self, c: Coord) -> Option<&mut T> {
+    self.index_of(c).map(move |i| &mut self.cells[i])
+  }
+
+  /// `None` for an empty grid, which has nothing to wrap onto.
+  fn wrap(&self, x: isize, y: isize) -> Option<Coord> {
+    if self.width == 0 || self.height == 0 {
+      return None;
+    }
+    Some((
+      x.rem_euclid(self.width as isize) as usize,
+      y.rem_euclid(self.height as isize) as usize,
+    ))
+  }
+
+  /// Indexes as though the grid repeated forever in every direction.
+  pub fn get_wrapping(&self, x: isize, y: isize) -> Option<&T> {
+    self.wrap(x, y).and_then(|c| self.get(c))
+  }
+
+  pub fn get_wrapping_mut(&mut self, x: isize, y: isize) -> Option<&mut T> {
+    self.wrap(x, y).and_then(move |c| self.get_mut(c))
+  }
+
+  /// Every coordinate, row by row.
+  pub fn coords(&self) -> impl Iterator<Item = Coord> {
+    let width = self.width;
+    (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+  }
+
+  /// Every cell, row by row.
+  pub fn iter(&self) -> impl Iterator<Item = &T> {
+    self.cells.iter()
+  }
+
+  pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+    self.cells.iter_mut()
+  }
+
+  pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+    // `chunks` panics on a zero width, which an empty grid can have.
+    self.cells.chunks(self.width.max(1))
+  }
+
+  pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+    (0..self.width).map(move |x| self.cells.iter().skip(x).step_by(self.width))
+  }
+
+  fn offsets<'a>(
+    &self,
+    (x, y): Coord,
+    deltas: &'a [(isize, isize)],
+  ) -> impl Iterator<Item = Coord> + 'a {
+    let (width, height) = (self.width, self.height);
+    deltas.iter().filter_map(move |(dx, dy)| {
+      let x = x.checked_add_signed(*dx)?;
+      let y = y.checked_add_signed(*dy)?;
+      (x < width && y < height).then_some((x, y))
+    })
+  }
+
+  /// The orthogonally adjacent coordinates that are on the grid.
+  pub fn neighbors4(&self, c: Coord) -> impl Iterator<Item = Coord> {
+    self.offsets(c, &[(0, -1), (1, 0), (0, 1), (-1, 0)])
+  }
+
+  /// The orthogonally and diagonally adjacent coordinates that are on the grid.
+  pub fn neighbors8(&self, c: Coord) -> impl Iterator<Item = Coord> {
+    self.offsets(
+      c,
+      &[
+        (-1, -1),
+        (0, -1),
+        (1, -1),
+        (1, 0),
+        (1, 1),
+        (0, 1),
+        (-1, 1),
+        (-1, 0),
+      ],
+    )
+  }
+
+  pub fn map<U>(&self, f: impl FnMut(&T) -> U) -> Grid<U> {
+    Grid {
+      width: self.width,
+      height: self.height,
+      cells: self.cells.iter().map(f).collect(),
+    }
+  }
+
+  /// Draws the grid a character per cell, for cells that don't have a `Display` of their own.
+  pub fn render(&self, mut f: impl FnMut(&T) -> char) -> String {
+    let mut s = String::with_capacity((self.width + 1) * self.height);
+    for row in self.rows() {
+      s.extend(row.iter().map(&mut f));
+      s.push('\n');
+    }
+    s
+  }
+}
+
+impl<T> Index<Coord> for Grid<T> {
+  type Output = T;
+
+  fn index(&self, c: Coord) -> &T {
+    self
+      .get(c)
+      .unwrap_or_else(|| panic!("{:?} is off a {}x{} grid", c, self.width, self.height))
+  }
+}
+
+impl<T> IndexMut<Coord> for Grid<T> {
+  fn index_mut(&mut self, c: Coord) -> &mut T {
+    let (width, height) = (self.width, self.height);
+    self
+      .get_mut(c)
+      .unwrap_or_else(|| panic!("{:?} is off a {}x{} grid", c, width, height))
+  }
+}
+
+impl<T: fmt::Display> fmt::Display for Grid<T> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    for row in self.rows() {
+      for cell in row {
+        write!(f, "{}", cell)?;
+      }
+      writeln!(f)?;
+    }
+    Ok(())
+  }
+}
+
+/// Parses a block of characters, one row per line, turning each character into a cell with `cell`.  A
+/// character `cell` rejects ends the row, and rows of differing lengths are an error.
+pub fn char_grid<'a, T, F: FnMut(char) -> Option<T>>(
+  cell: F,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Grid<T>> {
+  map_opt(lines_of(many1(map_opt(anychar, cell))), Grid::from_rows)
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn digits(input: &str) -> Grid<u32> {
+    parse_all(char_grid(|c| c.to_digit(10)))(input).unwrap()
+  }
+
+  #[test]
+  fn test_char_grid() {
+    let g = digits(
+      "123
+      456",
+    );
+    assert_eq!((g.width(), g.height()), (3, 2));
+    assert_eq!(g, Grid::from_vec(vec![1, 2, 3, 4, 5, 6], 3, 2).unwrap());
+    assert!(parse_all(char_grid(|c| c.to_digit(10)))("123\n45").is_err());
+    assert!(parse_all(char_grid(|c| c.to_digit(10)))("123\n4x6").is_err());
+  }
+
+  #[test]
+  fn test_indexing() {
+    let mut g = digits("123\n456");
+    assert_eq!(g[(2, 0)], 3);
+    assert_eq!(g.get((0, 1)), Some(&4));
+    assert_eq!(g.get((3, 0)), None);
+    assert_eq!(g.get((0, 2)), None);
+    assert_eq!(g.get_wrapping(-1, 0), Some(&3));
+    assert_eq!(g.get_wrapping(3, 3), Some(&4));
+    *g.get_wrapping_mut(-1, -1).unwrap() = 0;
+    g[(0, 0)] = 9;
+    assert_eq!(g.to_string(), "923\n450\n");
+
+    let mut empty = Grid::new(0, 0, 0);
+    assert_eq!(empty.get_wrapping(1, 1), None);
+    assert_eq!(empty.get_wrapping_mut(0, 0), None);
+  }
+
+  #[test]
+  fn test_neighbors() {
+    let g = digits("123\n456\n789");
+    assert_eq!(
+      g.neighbors4((0, 0)).map(|c| g[c]).collect::<Vec<_>>(),
+      vec![2, 4]
+    );
+    assert_eq!(g.neighbors4((1, 1)).count(), 4);
+    assert_eq!(
+      g.neighbors8((2, 2)).map(|c| g[c]).collect::<Vec<_>>(),
+      vec![5, 6, 8]
+    );
+    assert_eq!(g.neighbors8((1, 1)).count(), 8);
+  }
+
+  #[test]
+  fn test_rows_and_columns() {
+    let g = digits("123\n456");
+    assert_eq!(g.rows().collect::<Vec<_>>(), vec![&[1, 2, 3], &[4, 5, 6]]);
+    assert_eq!(
+      g.columns()
+        .map(|c| c.copied().collect::<Vec<_>>())
+        .collect::<Vec<_>>(),
+      vec![vec![1, 4], vec![2, 5], vec![3, 6]]
+    );
+    assert_eq!(
+      g.coords().collect::<Vec<_>>(),
+      vec![(0, 0), (1, 0), (2, 0), (0, 1), (1, 1), (2, 1)]
+    );
+  }
+
+  #[test]
+  fn test_from_fn_and_render() {
+    let g = Grid::from_fn(3, 2, |(x, y)| x + y);
+    assert_eq!(g, digits("012\n123").map(|d| *d as usize));
+    assert_eq!(
+      g.render(|d| if d % 2 == 0 { '#' } else { '.' }),
+      "#.#\n.#.\n"
+    );
+  }
+}
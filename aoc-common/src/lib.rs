@@ -0,0 +1,10 @@
+pub mod aabox;
+pub mod error;
+pub mod grid;
+pub mod nom_prelude;
+pub mod search;
+pub mod solution;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
+pub mod util;
+pub mod vector;
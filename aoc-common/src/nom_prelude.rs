@@ -1,3 +1,4 @@
+use crate::error::{Error, Result};
 pub use nom::branch::*;
 pub use nom::bytes::complete::*;
 pub use nom::character::complete::*;
@@ -12,6 +13,11 @@ pub fn parse_u32(input: &str) -> IResult<&str, u32> {
   map_res(digit1, |s: &str| s.parse::<u32>())(input)
 }
 
+// It's a little silly that I'm using this rather than the u16 parser provided by nom, but the types work out nicely.
+pub fn parse_u16(input: &str) -> IResult<&str, u16> {
+  map_res(digit1, |s: &str| s.parse::<u16>())(input)
+}
+
 // It's a little silly that I'm using this rather than the u32 parser provided by nom, but the types work out nicely.
 pub fn parse_u8(input: &str) -> IResult<&str, u8> {
   map_res(digit1, |s: &str| s.parse::<u8>())(input)
@@ -36,3 +42,48 @@ pub fn lines_of<'a, O, E: ParseError<&'a str>, F: Parser<&'a str, O, E>>(
 ) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<O>, E> {
   many1(delimited(multispace0, parser, multispace0))
 }
+
+/// Runs `parser` over the whole of the input, turning nom's result into a [`Result`].  Anything but whitespace
+/// left over is an error, so that a parser like [`lines_of`] which quietly stops at a malformed line can't
+/// hand back a partial answer.
+pub fn parse_all<'a, O, F: Parser<&'a str, O, nom::error::Error<&'a str>>>(
+  mut parser: F,
+) -> impl FnMut(&'a str) -> Result<O> {
+  move |input: &'a str| {
+    let (remaining, o) = parser.parse(input).map_err(|e| Error::from_nom(input, e))?;
+    let trimmed = remaining.trim_start();
+    if trimmed.is_empty() {
+      Ok(o)
+    } else {
+      Err(Error::trailing_input(input, trimmed))
+    }
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  #[test]
+  fn test_parse_all() {
+    let mut numbers = parse_all(lines_of(parse_u32));
+    assert_eq!(numbers("1\n2\n"), Ok(vec![1, 2]));
+    assert_eq!(
+      numbers("x"),
+      Err(Error::Parse {
+        line: 1,
+        column: 1,
+        snippet: "x".to_owned()
+      })
+    );
+    // `lines_of` stops at the malformed third line.
+    assert_eq!(
+      numbers("1\n2\nthree\n4\n"),
+      Err(Error::TrailingInput {
+        line: 3,
+        column: 1,
+        snippet: "three".to_owned()
+      })
+    );
+  }
+}
@@ -0,0 +1,209 @@
+//! Shortest paths over any state space, given a start, a way to list each state's neighbours, and a goal test.
+
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+use std::ops::Add;
+
+/// The cheapest way found from the start to a goal.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Path<S, C> {
+  pub cost: C,
+  /// From the start to the goal, inclusive.
+  pub states: Vec<S>,
+  /// `costs[i]` is the total cost of reaching `states[i]`, so `costs[0]` is zero and the last is `cost`.
+  pub costs: Vec<C>,
+}
+
+/// States seen so far, stored once each and referred to by index, so `S` only needs `Hash + Eq`.
+struct Visited<S, C> {
+  states: Vec<S>,
+  index: HashMap<S, usize>,
+  /// Best known cost to each state, and the state it was reached from.
+  best: Vec<(C, Option<usize>)>,
+}
+
+impl<S: Clone + Eq + Hash, C: Copy> Visited<S, C> {
+  fn new(start: S, zero: C) -> Self {
+    Self {
+      states: vec![start.clone()],
+      index: HashMap::from([(start, 0)]),
+      best: vec![(zero, None)],
+    }
+  }
+
+  /// The index of `state`, and whether it had been seen before.
+  fn insert(&mut self, state: S, cost: C, from: usize) -> (usize, bool) {
+    match self.index.entry(state) {
+      Entry::Occupied(e) => (*e.get(), true),
+      Entry::Vacant(e) => {
+        let i = self.states.len();
+        self.states.push(e.key().clone());
+        e.insert(i);
+        self.best.push((cost, Some(from)));
+        (i, false)
+      }
+    }
+  }
+
+  fn path(&self, goal: usize) -> Path<S, C> {
+    let mut states = Vec::new();
+    let mut costs = Vec::new();
+    let mut current = Some(goal);
+    while let Some(i) = current {
+      states.push(self.states[i].clone());
+      costs.push(self.best[i].0);
+      current = self.best[i].1;
+    }
+    states.reverse();
+    costs.reverse();
+    Path {
+      cost: self.best[goal].0,
+      states,
+      costs,
+    }
+  }
+}
+
+/// Dijkstra's algorithm.  `successors` gives each neighbour of a state along with the cost of moving there.
+pub fn dijkstra<S, C, I>(
+  start: S,
+  successors: impl FnMut(&S) -> I,
+  is_goal: impl FnMut(&S) -> bool,
+) -> Option<Path<S, C>>
+where
+  S: Clone + Eq + Hash,
+  C: Copy + Ord + Default + Add<Output = C>,
+  I: IntoIterator<Item = (S, C)>,
+{
+  astar(start, successors, |_| C::default(), is_goal)
+}
+
+/// A* search.  `heuristic` must never overestimate the remaining cost to a goal, or the path found may not be
+/// the cheapest.
+pub fn astar<S, C, I>(
+  start: S,
+  mut successors: impl FnMut(&S) -> I,
+  mut heuristic: impl FnMut(&S) -> C,
+  mut is_goal: impl FnMut(&S) -> bool,
+) -> Option<Path<S, C>>
+where
+  S: Clone + Eq + Hash,
+  C: Copy + Ord + Default + Add<Output = C>,
+  I: IntoIterator<Item = (S, C)>,
+{
+  let zero = C::default();
+  let mut frontier = BinaryHeap::from([Reverse((heuristic(&start), zero, 0))]);
+  let mut visited = Visited::new(start, zero);
+  while let Some(Reverse((_, cost, i))) = frontier.pop() {
+    // A stale entry, superseded by a cheaper route pushed after it.
+    if cost > visited.best[i].0 {
+      continue;
+    }
+    if is_goal(&visited.states[i]) {
+      return Some(visited.path(i));
+    }
+    for (next, delta) in successors(&visited.states[i]) {
+      let next_cost = cost + delta;
+      let estimate = next_cost + heuristic(&next);
+      let (j, seen) = visited.insert(next, next_cost, i);
+      if seen {
+        if next_cost >= visited.best[j].0 {
+          continue;
+        }
+        visited.best[j] = (next_cost, Some(i));
+      }
+      frontier.push(Reverse((estimate, next_cost, j)));
+    }
+  }
+  None
+}
+
+/// Breadth-first search, for when every move costs the same.  The cost is the number of moves.
+pub fn bfs<S, I>(
+  start: S,
+  mut successors: impl FnMut(&S) -> I,
+  mut is_goal: impl FnMut(&S) -> bool,
+) -> Option<Path<S, usize>>
+where
+  S: Clone + Eq + Hash,
+  I: IntoIterator<Item = S>,
+{
+  let mut frontier = VecDeque::from([0]);
+  let mut visited = Visited::new(start, 0);
+  while let Some(i) = frontier.pop_front() {
+    if is_goal(&visited.states[i]) {
+      return Some(visited.path(i));
+    }
+    let cost = visited.best[i].0 + 1;
+    for next in successors(&visited.states[i]) {
+      let (j, seen) = visited.insert(next, cost, i);
+      if !seen {
+        frontier.push_back(j);
+      }
+    }
+  }
+  None
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  // 0 -1-> 1 -1-> 2 -1-> 3, with a dearer shortcut 0 -5-> 3 and a dead end at 4.
+  fn edges(n: &u32) -> Vec<(u32, u32)> {
+    match n {
+      0 => vec![(3, 5), (1, 1), (4, 1)],
+      1 => vec![(2, 1)],
+      2 => vec![(3, 1)],
+      _ => vec![],
+    }
+  }
+
+  #[test]
+  fn test_dijkstra() {
+    assert_eq!(
+      dijkstra(0, edges, |&n| n == 3),
+      Some(Path {
+        cost: 3,
+        states: vec![0, 1, 2, 3],
+        costs: vec![0, 1, 2, 3],
+      })
+    );
+    assert_eq!(
+      dijkstra(0, edges, |&n| n == 0).map(|p| p.states),
+      Some(vec![0])
+    );
+    assert_eq!(dijkstra(4, edges, |&n| n == 3), None);
+  }
+
+  #[test]
+  fn test_astar() {
+    // Walking a number line towards 10 in steps of 1 or 3, where a step of 3 costs 2.
+    let successors = |&n: &i32| [(n + 1, 1), (n - 1, 1), (n + 3, 2), (n - 3, 2)];
+    let path = astar(0, successors, |&n| (10 - n).abs() / 3 * 2, |&n| n == 10).unwrap();
+    assert_eq!(path.cost, 7);
+    assert_eq!(path.states.len(), 5);
+    assert_eq!(path.costs.last(), Some(&7));
+    assert_eq!(
+      dijkstra(0, successors, |&n| n == 10).map(|p| p.cost),
+      Some(7)
+    );
+  }
+
+  #[test]
+  fn test_bfs() {
+    let successors = |&(x, y): &(u8, u8)| {
+      [(x + 1, y), (x, y + 1)]
+        .into_iter()
+        .filter(|&(x, y)| x < 3 && y < 3)
+    };
+    let path = bfs((0, 0), successors, |&p| p == (2, 2)).unwrap();
+    assert_eq!(path.cost, 4);
+    assert_eq!(path.states.first(), Some(&(0, 0)));
+    assert_eq!(path.states.last(), Some(&(2, 2)));
+    assert_eq!(path.costs, vec![0, 1, 2, 3, 4]);
+    assert_eq!(bfs((0, 0), successors, |&p| p == (3, 3)), None);
+  }
+}
@@ -0,0 +1,156 @@
+//! A uniform interface over every day's puzzle, so that tooling can drive any of them without knowing their types.
+
+use crate::error::Result;
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Part {
+  One,
+  Two,
+}
+
+pub trait Solution {
+  const YEAR: u32;
+  const DAY: u32;
+
+  /// The parsed puzzle input.  Some days borrow straight from the raw input, hence the lifetime.
+  type Input<'a>;
+  type Output1: Display;
+  type Output2: Display;
+
+  fn parse(input: &str) -> Result<Self::Input<'_>>;
+  fn part1(input: Self::Input<'_>) -> Result<Self::Output1>;
+  fn part2(input: Self::Input<'_>) -> Result<Self::Output2>;
+}
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Answer {
+  pub answer: String,
+  pub parse_time: Duration,
+  pub solve_time: Duration,
+}
+
+/// Parses `input` and solves the given part, rendering the answer with its `Display` impl.
+pub fn solve<S: Solution>(input: &str, part: Part) -> Result<Answer> {
+  let start = Instant::now();
+  let parsed = S::parse(input)?;
+  let parse_time = start.elapsed();
+
+  let start = Instant::now();
+  let answer = match part {
+    Part::One => S::part1(parsed)?.to_string(),
+    Part::Two => S::part2(parsed)?.to_string(),
+  };
+  let solve_time = start.elapsed();
+
+  Ok(Answer {
+    answer,
+    parse_time,
+    solve_time,
+  })
+}
+
+pub type Solver = fn(&str, Part) -> Result<Answer>;
+
+/// Every registered solution, keyed by `(year, day)`.
+#[derive(Default)]
+pub struct Registry(BTreeMap<(u32, u32), Solver>);
+
+impl Registry {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn register<S: Solution>(&mut self) -> &mut Self {
+    self.0.insert((S::YEAR, S::DAY), solve::<S>);
+    self
+  }
+
+  pub fn get(&self, year: u32, day: u32) -> Option<Solver> {
+    self.0.get(&(year, day)).copied()
+  }
+
+  /// Returns `None` if there is no solution registered for that day.
+  pub fn solve(&self, year: u32, day: u32, part: Part, input: &str) -> Option<Result<Answer>> {
+    let solver = self.get(year, day)?;
+    Some(solver(input, part))
+  }
+
+  pub fn days(&self) -> impl Iterator<Item = (u32, u32)> + '_ {
+    self.0.keys().copied()
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use crate::error::Error;
+  use crate::nom_prelude::parse_all;
+  use nom::character::complete::{digit1, multispace0};
+  use nom::combinator::map_res;
+  use nom::multi::many1;
+  use nom::sequence::terminated;
+
+  struct Sums;
+
+  impl Solution for Sums {
+    const YEAR: u32 = 2000;
+    const DAY: u32 = 1;
+    type Input<'a> = Vec<u32>;
+    type Output1 = u32;
+    type Output2 = String;
+
+    fn parse(input: &str) -> Result<Vec<u32>> {
+      parse_all(many1(terminated(
+        map_res(digit1, |s: &str| s.parse::<u32>()),
+        multispace0,
+      )))(input)
+    }
+
+    fn part1(input: Vec<u32>) -> Result<u32> {
+      Ok(input.into_iter().sum())
+    }
+
+    fn part2(input: Vec<u32>) -> Result<String> {
+      Ok(format!("{:?}", input))
+    }
+  }
+
+  fn answer(result: Option<Result<Answer>>) -> Option<Result<String>> {
+    result.map(|r| r.map(|a| a.answer))
+  }
+
+  #[test]
+  fn test_solve() {
+    assert_eq!(
+      answer(Some(solve::<Sums>("1 2 3", Part::One))),
+      Some(Ok("6".to_owned()))
+    );
+    assert_eq!(
+      answer(Some(solve::<Sums>("1 2 3", Part::Two))),
+      Some(Ok("[1, 2, 3]".to_owned()))
+    );
+    assert_eq!(
+      solve::<Sums>("x 1", Part::One),
+      Err(Error::Parse {
+        line: 1,
+        column: 1,
+        snippet: "x 1".to_owned()
+      })
+    );
+  }
+
+  #[test]
+  fn test_registry() {
+    let mut registry = Registry::new();
+    registry.register::<Sums>();
+    assert_eq!(registry.days().collect::<Vec<_>>(), vec![(2000, 1)]);
+    assert_eq!(
+      answer(registry.solve(2000, 1, Part::One, "4 5")),
+      Some(Ok("9".to_owned()))
+    );
+    assert_eq!(registry.solve(2000, 2, Part::One, "4 5"), None);
+  }
+}
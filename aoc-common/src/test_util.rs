@@ -0,0 +1,20 @@
+//! Helpers for tests, here so that every crate's tests can share them.  Other crates get them from the
+//! `test-util` feature, as a dev-dependency.
+
+/// Marsaglia's xorshift64: repeatable noise for generating test cases without pulling in a crate.  The seed must be
+/// nonzero.
+pub struct XorShift(pub u64);
+
+impl XorShift {
+  pub fn next_u64(&mut self) -> u64 {
+    self.0 ^= self.0 << 13;
+    self.0 ^= self.0 >> 7;
+    self.0 ^= self.0 << 17;
+    self.0
+  }
+
+  /// Somewhere from `lo` to `hi` inclusive.
+  pub fn range(&mut self, lo: i64, hi: i64) -> i64 {
+    lo + (self.next_u64() % (hi - lo + 1) as u64) as i64
+  }
+}
@@ -3,6 +3,12 @@ use std::hash::Hash;
 
 pub struct HashCounter<Key: Hash + Eq>(HashMap<Key, u32>);
 
+impl<Key: Hash + Eq> Default for HashCounter<Key> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
 impl<Key: Hash + Eq> HashCounter<Key> {
   pub fn new() -> Self {
     Self(HashMap::new())
@@ -10,7 +16,7 @@ impl<Key: Hash + Eq> HashCounter<Key> {
 
   pub fn inc(&mut self, key: Key) -> u32 {
     let e = self.0.entry(key).or_insert(0);
-    *e = *e + 1;
+    *e += 1;
     *e
   }
 
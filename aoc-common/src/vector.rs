@@ -0,0 +1,205 @@
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Copy, Clone)]
+pub struct V(pub i32, pub i32);
+
+impl std::ops::Add for V {
+  type Output = Self;
+  fn add(self, other: Self) -> Self {
+    Self(self.0 + other.0, self.1 + other.1)
+  }
+}
+
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Copy, Clone)]
+pub struct V3(pub i32, pub i32, pub i32);
+
+impl V3 {
+  /// # Panics
+  ///
+  /// Panics if index out of range
+  pub const fn get(self, i: usize) -> i32 {
+    match i {
+      0 => self.0,
+      1 => self.1,
+      2 => self.2,
+      _ => panic!("out of range"),
+    }
+  }
+
+  pub fn manhattan(self, other: V3) -> i32 {
+    (self.0 - other.0).abs() + (self.1 - other.1).abs() + (self.2 - other.2).abs()
+  }
+
+  pub fn dot(self, rhs: V3) -> i32 {
+    self.0 * rhs.0 + self.1 * rhs.1 + self.2 * rhs.2
+  }
+
+  pub fn cross(self, rhs: V3) -> V3 {
+    V3(
+      self.1 * rhs.2 - self.2 * rhs.1,
+      self.2 * rhs.0 - self.0 * rhs.2,
+      self.0 * rhs.1 - self.1 * rhs.0,
+    )
+  }
+}
+
+impl std::ops::Add for V3 {
+  type Output = Self;
+  fn add(self, rhs: Self) -> Self {
+    Self(self.0 + rhs.0, self.1 + rhs.1, self.2 + rhs.2)
+  }
+}
+
+impl std::ops::Sub for V3 {
+  type Output = Self;
+  fn sub(self, rhs: Self) -> Self {
+    Self(self.0 - rhs.0, self.1 - rhs.1, self.2 - rhs.2)
+  }
+}
+
+impl std::ops::Neg for V3 {
+  type Output = Self;
+  fn neg(self) -> Self {
+    Self(-self.0, -self.1, -self.2)
+  }
+}
+
+impl std::ops::Mul<i32> for V3 {
+  type Output = Self;
+  fn mul(self, rhs: i32) -> Self {
+    Self(self.0 * rhs, self.1 * rhs, self.2 * rhs)
+  }
+}
+
+/// A rotation by an integer matrix followed by a translation, mapping one frame's coordinates into another's.
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
+pub struct Transform {
+  /// The matrix, row by row.
+  pub rotation: [V3; 3],
+  pub translation: V3,
+}
+
+impl Transform {
+  pub const IDENTITY: Self = Self {
+    rotation: [V3(1, 0, 0), V3(0, 1, 0), V3(0, 0, 1)],
+    translation: V3(0, 0, 0),
+  };
+
+  pub fn translation(v: V3) -> Self {
+    Self {
+      translation: v,
+      ..Self::IDENTITY
+    }
+  }
+
+  /// The 24 rotations taking the axes to axes, i.e. the rotational symmetries of a cube.  The first row picks
+  /// where x goes from, the second y from the four directions left, and z follows to keep the handedness.
+  pub fn rotations() -> Vec<Self> {
+    let axes = [
+      V3(1, 0, 0),
+      V3(0, 1, 0),
+      V3(0, 0, 1),
+      V3(-1, 0, 0),
+      V3(0, -1, 0),
+      V3(0, 0, -1),
+    ];
+    let mut rotations = Vec::new();
+    for x in axes {
+      for y in axes.into_iter().filter(|y| y.dot(x) == 0) {
+        rotations.push(Self {
+          rotation: [x, y, x.cross(y)],
+          translation: V3(0, 0, 0),
+        });
+      }
+    }
+    rotations
+  }
+
+  /// Applies the rotation alone, as for a direction rather than a position.
+  pub fn rotate(&self, v: V3) -> V3 {
+    let [x, y, z] = self.rotation;
+    V3(x.dot(v), y.dot(v), z.dot(v))
+  }
+
+  pub fn apply(&self, v: V3) -> V3 {
+    self.rotate(v) + self.translation
+  }
+
+  /// Undoes `self`, assuming the matrix is a rotation, so that its transpose is its inverse.
+  pub fn inverse(&self) -> Self {
+    let [x, y, z] = self.rotation;
+    let rotation = [V3(x.0, y.0, z.0), V3(x.1, y.1, z.1), V3(x.2, y.2, z.2)];
+    let mut inverse = Self {
+      rotation,
+      translation: V3(0, 0, 0),
+    };
+    inverse.translation = -inverse.rotate(self.translation);
+    inverse
+  }
+}
+
+/// `a * b` applies `b` and then `a`.
+impl std::ops::Mul for Transform {
+  type Output = Self;
+  fn mul(self, rhs: Self) -> Self {
+    let columns = [V3(1, 0, 0), V3(0, 1, 0), V3(0, 0, 1)].map(|e| self.rotate(rhs.rotate(e)));
+    let [x, y, z] = columns;
+    Self {
+      rotation: [V3(x.0, y.0, z.0), V3(x.1, y.1, z.1), V3(x.2, y.2, z.2)],
+      translation: self.apply(rhs.translation),
+    }
+  }
+}
+
+impl std::ops::Mul<V3> for Transform {
+  type Output = V3;
+  fn mul(self, rhs: V3) -> V3 {
+    self.apply(rhs)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use std::collections::HashSet;
+
+  #[test]
+  fn test_v3() {
+    let (a, b) = (V3(1, 2, 3), V3(-4, 0, 5));
+    assert_eq!(-a, V3(-1, -2, -3));
+    assert_eq!(a * 3, V3(3, 6, 9));
+    assert_eq!(a.dot(b), 11);
+    assert_eq!(a.cross(b), V3(10, -17, 8));
+    assert_eq!(a.cross(b).dot(a), 0);
+    assert_eq!(V3(1, 0, 0).cross(V3(0, 1, 0)), V3(0, 0, 1));
+  }
+
+  #[test]
+  fn test_transform() {
+    let rotations = Transform::rotations();
+    assert_eq!(rotations.len(), 24);
+    let v = V3(1, 2, 3);
+    let images: HashSet<_> = rotations.iter().map(|r| r.apply(v)).collect();
+    assert_eq!(images.len(), 24);
+    assert!(rotations.contains(&Transform::IDENTITY));
+
+    let all: HashSet<_> = rotations.iter().copied().collect();
+    for &r in &rotations {
+      assert_eq!(r * r.inverse(), Transform::IDENTITY);
+      for &s in &rotations {
+        assert!(all.contains(&(r * s)));
+      }
+    }
+
+    let quarter = rotations
+      .iter()
+      .copied()
+      .find(|r| r.apply(V3(1, 0, 0)) == V3(0, 1, 0) && r.apply(V3(0, 0, 1)) == V3(0, 0, 1))
+      .unwrap();
+    let a = Transform::translation(V3(10, 0, 0)) * quarter;
+    let b = quarter * Transform::translation(V3(10, 0, 0));
+    assert_eq!(a * V3(1, 2, 3), V3(8, 1, 3));
+    assert_eq!(b * V3(1, 2, 3), V3(-2, 11, 3));
+    assert_eq!((a * b).apply(v), a.apply(b.apply(v)));
+    assert_eq!(a.inverse().apply(a.apply(v)), v);
+    assert_eq!((a * b).inverse(), b.inverse() * a.inverse());
+  }
+}
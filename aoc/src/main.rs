@@ -0,0 +1,183 @@
+//! Runs any registered day from the command line.
+//!
+//! ```text
+//! aoc run <year> <day> [--part 1|2] [--input <path>|-]
+//! ```
+//!
+//! Without `--part` both parts are run.  Without `--input` the puzzle input is read from
+//! `aoc<year>/inputs/dNN.txt`; `-` reads it from stdin.
+
+use aoc_common::solution::{Part, Registry};
+use std::io::Read;
+use std::process::ExitCode;
+
+const USAGE: &str = "usage: aoc run <year> <day> [--part 1|2] [--input <path>|-]";
+
+#[derive(Debug, Eq, PartialEq)]
+enum Input {
+  Default,
+  Stdin,
+  Path(String),
+}
+
+#[derive(Debug, Eq, PartialEq)]
+struct Run {
+  year: u32,
+  day: u32,
+  parts: Vec<Part>,
+  input: Input,
+}
+
+fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<Run, String> {
+  let mut args = args.into_iter();
+  match args.next().as_deref() {
+    Some("run") => {}
+    Some(cmd) => return Err(format!("unknown command {:?}", cmd)),
+    None => return Err("missing command".to_owned()),
+  }
+
+  let mut number = |name: &str| -> Result<u32, String> {
+    let arg = args.next().ok_or_else(|| format!("missing {}", name))?;
+    arg
+      .parse()
+      .map_err(|_| format!("{} must be a number, got {:?}", name, arg))
+  };
+  let year = number("year")?;
+  let day = number("day")?;
+
+  let mut parts = vec![Part::One, Part::Two];
+  let mut input = Input::Default;
+  while let Some(flag) = args.next() {
+    let value = args
+      .next()
+      .ok_or_else(|| format!("missing value for {}", flag))?;
+    match (flag.as_str(), value.as_str()) {
+      ("--part", "1") => parts = vec![Part::One],
+      ("--part", "2") => parts = vec![Part::Two],
+      ("--part", _) => return Err(format!("part must be 1 or 2, got {:?}", value)),
+      ("--input", "-") => input = Input::Stdin,
+      ("--input", _) => input = Input::Path(value),
+      _ => return Err(format!("unknown flag {:?}", flag)),
+    }
+  }
+
+  Ok(Run {
+    year,
+    day,
+    parts,
+    input,
+  })
+}
+
+fn read_input(run: &Run) -> Result<String, String> {
+  match &run.input {
+    Input::Stdin => {
+      let mut s = String::new();
+      std::io::stdin()
+        .read_to_string(&mut s)
+        .map_err(|e| format!("could not read stdin: {}", e))?;
+      Ok(s)
+    }
+    Input::Default => {
+      let path = format!("aoc{}/inputs/d{:02}.txt", run.year, run.day);
+      std::fs::read_to_string(&path).map_err(|e| format!("could not read {}: {}", path, e))
+    }
+    Input::Path(path) => {
+      std::fs::read_to_string(path).map_err(|e| format!("could not read {}: {}", path, e))
+    }
+  }
+}
+
+fn main() -> ExitCode {
+  let run = match parse_args(std::env::args().skip(1)) {
+    Ok(run) => run,
+    Err(e) => {
+      eprintln!("{}\n{}", e, USAGE);
+      return ExitCode::from(2);
+    }
+  };
+
+  let mut registry = Registry::new();
+  aoc2015::register(&mut registry);
+  aoc2021::register(&mut registry);
+  let solver = match registry.get(run.year, run.day) {
+    Some(solver) => solver,
+    None => {
+      eprintln!("no solution for {} day {}", run.year, run.day);
+      return ExitCode::FAILURE;
+    }
+  };
+
+  let input = match read_input(&run) {
+    Ok(input) => input,
+    Err(e) => {
+      eprintln!("{}", e);
+      return ExitCode::FAILURE;
+    }
+  };
+
+  for &part in &run.parts {
+    match solver(&input, part) {
+      Ok(answer) => println!(
+        "{} day {} part {:?}: {}  (parse {:?}, solve {:?})",
+        run.year, run.day, part, answer.answer, answer.parse_time, answer.solve_time
+      ),
+      Err(e) => {
+        eprintln!("{} day {} part {:?}: {}", run.year, run.day, part, e);
+        return ExitCode::FAILURE;
+      }
+    }
+  }
+  ExitCode::SUCCESS
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+
+  fn args(s: &str) -> Vec<String> {
+    s.split_whitespace().map(|s| s.to_owned()).collect()
+  }
+
+  #[test]
+  fn test_parse_args() {
+    assert_eq!(
+      parse_args(args("run 2021 16")),
+      Ok(Run {
+        year: 2021,
+        day: 16,
+        parts: vec![Part::One, Part::Two],
+        input: Input::Default,
+      })
+    );
+    assert_eq!(
+      parse_args(args("run 2021 16 --part 2 --input -")),
+      Ok(Run {
+        year: 2021,
+        day: 16,
+        parts: vec![Part::Two],
+        input: Input::Stdin,
+      })
+    );
+    assert_eq!(
+      parse_args(args("run 2015 4 --input foo.txt --part 1")),
+      Ok(Run {
+        year: 2015,
+        day: 4,
+        parts: vec![Part::One],
+        input: Input::Path("foo.txt".to_owned()),
+      })
+    );
+  }
+
+  #[test]
+  fn test_parse_args_errors() {
+    assert!(parse_args(args("")).is_err());
+    assert!(parse_args(args("walk 2021 16")).is_err());
+    assert!(parse_args(args("run 2021")).is_err());
+    assert!(parse_args(args("run 2021 sixteen")).is_err());
+    assert!(parse_args(args("run 2021 16 --part 3")).is_err());
+    assert!(parse_args(args("run 2021 16 --part")).is_err());
+    assert!(parse_args(args("run 2021 16 --verbose 1")).is_err());
+  }
+}
@@ -1,7 +1,6 @@
-use nom::branch::alt;
-use nom::character::complete::char;
-use nom::multi::many1;
-use nom::IResult;
+use aoc_common::error::{Error, Result};
+use aoc_common::nom_prelude::*;
+use aoc_common::solution::Solution;
 
 #[derive(Debug, Eq, PartialEq)]
 pub enum Direction {
@@ -27,14 +26,15 @@ pub fn directions(input: &str) -> IResult<&str, Vec<Direction>> {
   many1(direction)(input)
 }
 
-pub fn p1(input: &[Direction]) -> i32 {
-  input.iter().fold(0, |acc, d| match d {
+pub fn p1(input: &[Direction]) -> Result<i32> {
+  Ok(input.iter().fold(0, |acc, d| match d {
     Direction::Up => acc + 1,
     Direction::Down => acc - 1,
-  })
+  }))
 }
 
-pub fn p2(input: &[Direction]) -> i32 {
+/// The position of the instruction that first takes Santa to the basement, if any.
+pub fn p2(input: &[Direction]) -> Result<i32> {
   input
     .iter()
     .scan((0, 0_i32), |acc, d| {
@@ -43,11 +43,33 @@ pub fn p2(input: &[Direction]) -> i32 {
         Direction::Down => acc.1 -= 1,
       }
       acc.0 += 1; // Could use enumerate, but since we're using scan anyway...
-      Some(acc.clone())
+      Some(*acc)
     })
     .find(|(_, current_floor)| *current_floor == -1)
-    .unwrap()
-    .0
+    .map(|(position, _)| position)
+    .ok_or(Error::NoSolution)
+}
+
+pub struct Day;
+
+impl Solution for Day {
+  const YEAR: u32 = 2015;
+  const DAY: u32 = 1;
+  type Input<'a> = Vec<Direction>;
+  type Output1 = i32;
+  type Output2 = i32;
+
+  fn parse(input: &str) -> Result<Self::Input<'_>> {
+    parse_all(directions)(input)
+  }
+
+  fn part1(input: Self::Input<'_>) -> Result<i32> {
+    p1(&input)
+  }
+
+  fn part2(input: Self::Input<'_>) -> Result<i32> {
+    p2(&input)
+  }
 }
 
 #[cfg(test)]
@@ -66,22 +88,28 @@ mod test {
   #[test]
   fn p1() {
     let input = "(())(";
-    let (_, ds) = directions(&input).unwrap();
-    assert_eq!(super::p1(&ds), 1);
+    let (_, ds) = directions(input).unwrap();
+    assert_eq!(super::p1(&ds), Ok(1));
 
     let input = std::fs::read_to_string("./inputs/d01.txt").unwrap();
     let (_, ds) = directions(&input).unwrap();
-    assert_eq!(super::p1(&ds), 138);
+    assert_eq!(super::p1(&ds), Ok(138));
   }
 
   #[test]
   fn p2() {
     let input = "(()))((";
-    let (_, ds) = directions(&input).unwrap();
-    assert_eq!(super::p2(&ds), 5);
+    let (_, ds) = directions(input).unwrap();
+    assert_eq!(super::p2(&ds), Ok(5));
 
     let input = std::fs::read_to_string("./inputs/d01.txt").unwrap();
     let (_, ds) = directions(&input).unwrap();
-    assert_eq!(super::p2(&ds), 1771);
+    assert_eq!(super::p2(&ds), Ok(1771));
+  }
+
+  #[test]
+  fn test_no_solution() {
+    let (_, ds) = directions("(((").unwrap();
+    assert_eq!(super::p2(&ds), Err(Error::NoSolution));
   }
 }
@@ -1,9 +1,6 @@
-use nom::bytes::complete::tag;
-use nom::character::complete::digit1;
-use nom::combinator::map_res;
-use nom::multi::{many0, many1};
-use nom::sequence::delimited;
-use nom::IResult;
+use aoc_common::error::Result;
+use aoc_common::nom_prelude::*;
+use aoc_common::solution::Solution;
 
 #[derive(Debug, Eq, PartialEq)]
 pub struct Gift(u32, u32, u32);
@@ -28,12 +25,11 @@ impl Gift {
 }
 
 fn gift(input: &str) -> IResult<&str, Gift> {
-  let mut parse_dimension = map_res(digit1, |s: &str| s.parse::<u32>());
-  let (input, l) = parse_dimension(input)?;
+  let (input, l) = parse_u32(input)?;
   let (input, _) = tag("x")(input)?;
-  let (input, w) = parse_dimension(input)?;
+  let (input, w) = parse_u32(input)?;
   let (input, _) = tag("x")(input)?;
-  let (input, h) = parse_dimension(input)?;
+  let (input, h) = parse_u32(input)?;
   Ok((input, Gift(l, w, h)))
 }
 
@@ -43,18 +39,44 @@ pub fn gifts(input: &str) -> IResult<&str, Vec<Gift>> {
   many1(delimited_gift)(input)
 }
 
-pub fn p1(gifts: Vec<Gift>) -> u32 {
-  gifts
-    .iter()
-    .map(|g| 2 * g.side_areas().iter().sum::<u32>() + g.side_areas().iter().min().unwrap())
-    .sum()
+pub fn p1(gifts: Vec<Gift>) -> Result<u32> {
+  Ok(
+    gifts
+      .iter()
+      .map(|g| 2 * g.side_areas().iter().sum::<u32>() + g.side_areas().iter().min().unwrap())
+      .sum(),
+  )
 }
 
-pub fn p2(gifts: Vec<Gift>) -> u32 {
-  gifts
-    .iter()
-    .map(|g| g.side_perimeters().iter().min().unwrap() + g.volume())
-    .sum()
+pub fn p2(gifts: Vec<Gift>) -> Result<u32> {
+  Ok(
+    gifts
+      .iter()
+      .map(|g| g.side_perimeters().iter().min().unwrap() + g.volume())
+      .sum(),
+  )
+}
+
+pub struct Day;
+
+impl Solution for Day {
+  const YEAR: u32 = 2015;
+  const DAY: u32 = 2;
+  type Input<'a> = Vec<Gift>;
+  type Output1 = u32;
+  type Output2 = u32;
+
+  fn parse(input: &str) -> Result<Self::Input<'_>> {
+    parse_all(gifts)(input)
+  }
+
+  fn part1(input: Self::Input<'_>) -> Result<u32> {
+    p1(input)
+  }
+
+  fn part2(input: Self::Input<'_>) -> Result<u32> {
+    p2(input)
+  }
 }
 
 #[cfg(test)]
@@ -74,23 +96,23 @@ mod test {
   fn p1() {
     let input = "1x2x3\n
 3x2x1\n";
-    let (_, gs) = gifts(&input).unwrap();
-    assert_eq!(super::p1(gs), (((2 + 3 + 6) * 2) + 2) * 2);
+    let (_, gs) = gifts(input).unwrap();
+    assert_eq!(super::p1(gs), Ok((((2 + 3 + 6) * 2) + 2) * 2));
 
     let input = std::fs::read_to_string("./inputs/d02.txt").unwrap();
     let (_, gs) = gifts(&input).unwrap();
-    assert_eq!(super::p1(gs), 1588178);
+    assert_eq!(super::p1(gs), Ok(1588178));
   }
 
   #[test]
   fn p2() {
     let input = "1x2x3\n
 3x2x1\n";
-    let (_, gs) = gifts(&input).unwrap();
-    assert_eq!(super::p2(gs), (2 * (1 + 2) + 6) * 2);
+    let (_, gs) = gifts(input).unwrap();
+    assert_eq!(super::p2(gs), Ok((2 * (1 + 2) + 6) * 2));
 
     let input = std::fs::read_to_string("./inputs/d02.txt").unwrap();
     let (_, gs) = gifts(&input).unwrap();
-    assert_eq!(super::p2(gs), 3783758);
+    assert_eq!(super::p2(gs), Ok(3783758));
   }
 }
@@ -1,10 +1,8 @@
 use std::collections::HashSet;
 
-use nom::branch::alt;
-use nom::bytes::complete::tag;
-use nom::combinator::map;
-use nom::multi::many1;
-use nom::IResult;
+use aoc_common::error::Result;
+use aoc_common::nom_prelude::*;
+use aoc_common::solution::Solution;
 
 #[derive(Debug, Eq, PartialEq)]
 pub enum Direction {
@@ -26,10 +24,10 @@ pub fn directions(input: &str) -> IResult<&str, Vec<Direction>> {
   many1(direction)(input)
 }
 
-pub fn p1(input: Vec<Direction>) -> u32 {
+pub fn p1(input: Vec<Direction>) -> Result<u32> {
   let mut loc = (0, 0);
   let mut delivered = HashSet::new();
-  delivered.insert(loc.clone());
+  delivered.insert(loc);
   for d in input {
     match d {
       Direction::Up => loc.1 += 1,
@@ -37,17 +35,17 @@ pub fn p1(input: Vec<Direction>) -> u32 {
       Direction::Left => loc.0 -= 1,
       Direction::Right => loc.0 += 1,
     }
-    delivered.insert(loc.clone());
+    delivered.insert(loc);
   }
-  delivered.len() as u32
+  Ok(delivered.len() as u32)
 }
 
-pub fn p2(input: Vec<Direction>) -> u32 {
+pub fn p2(input: Vec<Direction>) -> Result<u32> {
   let mut santa = (0, 0);
   let mut robo = (0, 0);
   let mut delivered = HashSet::new();
-  delivered.insert(santa.clone());
-  delivered.insert(robo.clone());
+  delivered.insert(santa);
+  delivered.insert(robo);
   // alternatively could have just split the list, etc.
   for (i, d) in input.iter().enumerate() {
     let santa_step = if i % 2 == 0 { 1 } else { 0 };
@@ -70,10 +68,32 @@ pub fn p2(input: Vec<Direction>) -> u32 {
         robo.0 += robo_step;
       }
     }
-    delivered.insert(santa.clone());
-    delivered.insert(robo.clone());
+    delivered.insert(santa);
+    delivered.insert(robo);
+  }
+  Ok(delivered.len() as u32)
+}
+
+pub struct Day;
+
+impl Solution for Day {
+  const YEAR: u32 = 2015;
+  const DAY: u32 = 3;
+  type Input<'a> = Vec<Direction>;
+  type Output1 = u32;
+  type Output2 = u32;
+
+  fn parse(input: &str) -> Result<Self::Input<'_>> {
+    parse_all(directions)(input)
+  }
+
+  fn part1(input: Self::Input<'_>) -> Result<u32> {
+    p1(input)
+  }
+
+  fn part2(input: Self::Input<'_>) -> Result<u32> {
+    p2(input)
   }
-  delivered.len() as u32
 }
 
 #[cfg(test)]
@@ -92,22 +112,22 @@ mod test {
   #[test]
   fn p1() {
     let input = "^>v<<>";
-    let (_, ds) = directions(&input).unwrap();
-    assert_eq!(super::p1(ds), 5);
+    let (_, ds) = directions(input).unwrap();
+    assert_eq!(super::p1(ds), Ok(5));
 
     let input = std::fs::read_to_string("./inputs/d03.txt").unwrap();
     let (_, ds) = directions(&input).unwrap();
-    assert_eq!(super::p1(ds), 2081);
+    assert_eq!(super::p1(ds), Ok(2081));
   }
 
   #[test]
   fn p2() {
     let input = "^>v<<>";
-    let (_, ds) = directions(&input).unwrap();
-    assert_eq!(super::p2(ds), 4);
+    let (_, ds) = directions(input).unwrap();
+    assert_eq!(super::p2(ds), Ok(4));
 
     let input = std::fs::read_to_string("./inputs/d03.txt").unwrap();
     let (_, ds) = directions(&input).unwrap();
-    assert_eq!(super::p2(ds), 2341);
+    assert_eq!(super::p2(ds), Ok(2341));
   }
 }
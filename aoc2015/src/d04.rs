@@ -1,28 +1,42 @@
+use aoc_common::error::{Error, Result};
+use aoc_common::solution::Solution;
+
 pub const INPUT: &str = "ckczppom";
 
-pub fn p1(input: &str) -> u32 {
-  let mut i = 0;
-  loop {
-    let input = format!("{}{}", input, i);
-    let hash = md5::compute(&input);
-    let hash_string = format!("{:x}", hash);
-    if hash_string.starts_with("00000") {
-      return i;
-    }
-    i += 1;
-  }
+/// The first number that, appended to the key, gives a hash starting with `zeros` zeros.
+fn mine(key: &str, zeros: &str) -> Result<u32> {
+  (0..=u32::MAX)
+    .find(|i| format!("{:x}", md5::compute(format!("{}{}", key, i))).starts_with(zeros))
+    .ok_or(Error::NoSolution)
+}
+
+pub fn p1(input: &str) -> Result<u32> {
+  mine(input, "00000")
+}
+
+pub fn p2(input: &str) -> Result<u32> {
+  mine(input, "000000")
 }
 
-pub fn p2(input: &str) -> u32 {
-  let mut i = 0;
-  loop {
-    let input = format!("{}{}", input, i);
-    let hash = md5::compute(&input);
-    let hash_string = format!("{:x}", hash);
-    if hash_string.starts_with("000000") {
-      return i;
-    }
-    i += 1;
+pub struct Day;
+
+impl Solution for Day {
+  const YEAR: u32 = 2015;
+  const DAY: u32 = 4;
+  type Input<'a> = &'a str;
+  type Output1 = u32;
+  type Output2 = u32;
+
+  fn parse(input: &str) -> Result<Self::Input<'_>> {
+    Ok(input.trim())
+  }
+
+  fn part1(input: Self::Input<'_>) -> Result<u32> {
+    p1(input)
+  }
+
+  fn part2(input: Self::Input<'_>) -> Result<u32> {
+    p2(input)
   }
 }
 
@@ -32,11 +46,11 @@ mod test {
 
   #[test]
   fn p1() {
-    assert_eq!(super::p1(INPUT), 117946);
+    assert_eq!(super::p1(INPUT), Ok(117946));
   }
 
   #[test]
   fn p2() {
-    assert_eq!(super::p2(INPUT), 3938038);
+    assert_eq!(super::p2(INPUT), Ok(3938038));
   }
 }
@@ -1,3 +1,6 @@
+use aoc_common::error::Result;
+use aoc_common::solution::Solution;
+
 fn is_nice(s: &str) -> bool {
   let enough_vowels = s
     .chars()
@@ -26,12 +29,34 @@ fn better_is_nice(s: &str) -> bool {
   one_between && has_double_double(s)
 }
 
-pub fn p1(input: &[&str]) -> usize {
-  input.iter().filter(|s| is_nice(s)).count()
+pub fn p1(input: &[&str]) -> Result<usize> {
+  Ok(input.iter().filter(|s| is_nice(s)).count())
+}
+
+pub fn p2(input: &[&str]) -> Result<usize> {
+  Ok(input.iter().filter(|s| better_is_nice(s)).count())
 }
 
-pub fn p2(input: &[&str]) -> usize {
-  input.iter().filter(|s| better_is_nice(s)).count()
+pub struct Day;
+
+impl Solution for Day {
+  const YEAR: u32 = 2015;
+  const DAY: u32 = 5;
+  type Input<'a> = Vec<&'a str>;
+  type Output1 = usize;
+  type Output2 = usize;
+
+  fn parse(input: &str) -> Result<Self::Input<'_>> {
+    Ok(input.lines().collect())
+  }
+
+  fn part1(input: Self::Input<'_>) -> Result<usize> {
+    p1(&input)
+  }
+
+  fn part2(input: Self::Input<'_>) -> Result<usize> {
+    p2(&input)
+  }
 }
 
 #[cfg(test)]
@@ -42,11 +67,11 @@ mod test {
 aaa
 aaab";
     let input = input.lines().collect::<Vec<_>>();
-    assert_eq!(super::p1(&input), 1);
+    assert_eq!(super::p1(&input), Ok(1));
 
     let input = std::fs::read_to_string("./inputs/d05.txt").unwrap();
     let input = input.lines().collect::<Vec<_>>();
-    assert_eq!(super::p1(&input), 255);
+    assert_eq!(super::p1(&input), Ok(255));
   }
 
   #[test]
@@ -55,10 +80,10 @@ aaab";
 aaa
 abab";
     let input = input.lines().collect::<Vec<_>>();
-    assert_eq!(super::p2(&input), 1);
+    assert_eq!(super::p2(&input), Ok(1));
 
     let input = std::fs::read_to_string("./inputs/d05.txt").unwrap();
     let input = input.lines().collect::<Vec<_>>();
-    assert_eq!(super::p2(&input), 55);
+    assert_eq!(super::p2(&input), Ok(55));
   }
 }
@@ -1,34 +1,10 @@
-use itertools::Itertools;
-use nom::branch::alt;
-use nom::bytes::complete::tag;
-use nom::character::complete::digit1;
-use nom::combinator::map_res;
-use nom::multi::{many0, many1};
-use nom::sequence::{delimited, preceded, tuple};
-use nom::IResult;
-use std::collections::{HashMap, HashSet};
-
-#[derive(Debug, Eq, PartialEq, Hash, Ord, PartialOrd, Copy, Clone)]
-pub struct Point {
-  x: u32,
-  y: u32,
-}
-
-#[derive(Debug, Eq, PartialEq)]
-pub struct Rect {
-  xmin: u32,
-  ymin: u32,
-  xmax: u32,
-  ymax: u32,
-}
+use aoc_common::aabox::AABox;
+use aoc_common::error::Result;
+use aoc_common::grid::Grid;
+use aoc_common::nom_prelude::*;
+use aoc_common::solution::Solution;
 
-impl Rect {
-  fn values(&self) -> impl Iterator<Item = Point> {
-    (self.xmin..=self.xmax)
-      .cartesian_product(self.ymin..=self.ymax)
-      .map(|(x, y)| Point { x, y })
-  }
-}
+pub type Rect = AABox<2>;
 
 #[derive(Debug, Eq, PartialEq)]
 pub enum Instruction {
@@ -37,25 +13,27 @@ pub enum Instruction {
   Toggle(Rect),
 }
 
-fn point(input: &str) -> IResult<&str, Point> {
-  let mut parse_dimension = map_res(digit1, |s: &str| s.parse::<u32>());
-  let (input, x) = parse_dimension(input)?;
+fn point(input: &str) -> IResult<&str, [i64; 2]> {
+  let (input, x) = parse_u32(input)?;
   let (input, _) = tag(",")(input)?;
-  let (input, y) = parse_dimension(input)?;
-  Ok((input, Point { x, y }))
+  let (input, y) = parse_u32(input)?;
+  Ok((input, [x as i64, y as i64]))
 }
 
+const SIZE: usize = 1000;
+
+/// Every light, from `0,0` to `999,999`.
+const BOUNDS: Rect = AABox {
+  min: [0, 0],
+  max: [SIZE as i64, SIZE as i64],
+};
+
+/// Fails outright, rather than backtracking, on a malformed rect or one that reaches off the grid.
 fn rect(input: &str) -> IResult<&str, Rect> {
-  let (input, (p1, _, p2)) = tuple((point, tag(" through "), point))(input)?;
-  Ok((
-    input,
-    Rect {
-      xmin: p1.x,
-      ymin: p1.y,
-      xmax: p2.x,
-      ymax: p2.y,
-    },
-  ))
+  let corners = map(tuple((point, tag(" through "), point)), |(p1, _, p2)| {
+    Rect::from_corners(p1, p2)
+  });
+  cut(verify(corners, |r| BOUNDS.contains_box(r)))(input)
 }
 
 fn turn_on(input: &str) -> IResult<&str, Instruction> {
@@ -83,78 +61,104 @@ pub fn instructions(input: &str) -> IResult<&str, Vec<Instruction>> {
   many1(delimited_instruction)(input)
 }
 
-struct LightGrid {
-  // We're doing this the naive way. lol
-  lights: HashSet<Point>,
-}
+/// The lit lights, as disjoint rects.
+struct LitRects(Vec<Rect>);
 
-impl LightGrid {
-  fn new() -> Self {
-    Self {
-      lights: HashSet::new(),
-    }
+impl LitRects {
+  /// What is left of `rects` outside `r`.
+  fn cut(rects: &[Rect], r: Rect) -> Vec<Rect> {
+    rects.iter().flat_map(|lit| lit.subtract(r)).collect()
   }
 
   fn update(&mut self, instruction: Instruction) {
     match instruction {
-      Instruction::TurnOn(r) => r.values().for_each(|v| {
-        self.lights.insert(v);
-      }),
-      Instruction::TurnOff(r) => r.values().for_each(|v| {
-        self.lights.remove(&v);
-      }),
-      Instruction::Toggle(r) => r.values().for_each(|v| {
-        if !self.lights.remove(&v) {
-          self.lights.insert(v);
-        }
-      }),
+      Instruction::TurnOn(r) => {
+        self.0 = Self::cut(&self.0, r);
+        self.0.push(r);
+      }
+      Instruction::TurnOff(r) => self.0 = Self::cut(&self.0, r),
+      Instruction::Toggle(r) => {
+        let dark = self
+          .0
+          .iter()
+          .fold(vec![r], |dark, &lit| Self::cut(&dark, lit));
+        self.0 = Self::cut(&self.0, r);
+        self.0.extend(dark);
+      }
     }
   }
+
+  fn count(&self) -> u64 {
+    self.0.iter().map(Rect::volume).sum()
+  }
 }
 
-pub fn p1(instructions: Vec<Instruction>) -> usize {
-  let mut lights = LightGrid::new();
+pub fn p1(instructions: Vec<Instruction>) -> Result<u64> {
+  let mut lights = LitRects(Vec::new());
   for i in instructions {
     lights.update(i)
   }
-  lights.lights.iter().count()
+  Ok(lights.count())
 }
 
 struct DimmableLightGrid {
-  lights: HashMap<Point, i32>,
+  lights: Grid<u32>,
 }
 
 impl DimmableLightGrid {
   fn new() -> Self {
     Self {
-      lights: HashMap::new(),
+      lights: Grid::new(SIZE, SIZE, 0),
     }
   }
 
   fn update(&mut self, instruction: Instruction) {
-    let (step, r) = match instruction {
-      Instruction::TurnOn(r) => (1, r),
-      Instruction::TurnOff(r) => (-1, r),
-      Instruction::Toggle(r) => (2, r),
+    let (f, r): (fn(u32) -> u32, _) = match instruction {
+      Instruction::TurnOn(r) => (|b| b + 1, r),
+      Instruction::TurnOff(r) => (|b| b.saturating_sub(1), r),
+      Instruction::Toggle(r) => (|b| b + 2, r),
     };
-    r.values().for_each(|v| {
-      let entry = self.lights.entry(v).or_insert(0);
-      *entry = (*entry + step).max(0);
+    r.points().for_each(|[x, y]| {
+      let brightness = &mut self.lights[(x as usize, y as usize)];
+      *brightness = f(*brightness);
     })
   }
 }
 
-pub fn p2(instructions: Vec<Instruction>) -> u32 {
+pub fn p2(instructions: Vec<Instruction>) -> Result<u32> {
   let mut lights = DimmableLightGrid::new();
   for i in instructions {
     lights.update(i)
   }
-  lights.lights.values().copied().sum::<i32>() as u32
+  Ok(lights.lights.iter().sum())
+}
+
+pub struct Day;
+
+impl Solution for Day {
+  const YEAR: u32 = 2015;
+  const DAY: u32 = 6;
+  type Input<'a> = Vec<Instruction>;
+  type Output1 = u64;
+  type Output2 = u32;
+
+  fn parse(input: &str) -> Result<Self::Input<'_>> {
+    parse_all(instructions)(input)
+  }
+
+  fn part1(input: Self::Input<'_>) -> Result<u64> {
+    p1(input)
+  }
+
+  fn part2(input: Self::Input<'_>) -> Result<u32> {
+    p2(input)
+  }
 }
 
 #[cfg(test)]
 mod test {
   use super::*;
+  use aoc_common::error::Error;
   #[test]
   fn parse() {
     let input = "turn off 660,55 through 986,197
@@ -165,41 +169,46 @@ turn on 317,329 through 451,798";
       (
         "",
         vec![
-          Instruction::TurnOff(Rect {
-            xmin: 660,
-            ymin: 55,
-            xmax: 986,
-            ymax: 197,
-          }),
-          Instruction::Toggle(Rect {
-            xmin: 322,
-            ymin: 558,
-            xmax: 977,
-            ymax: 958,
-          }),
-          Instruction::TurnOn(Rect {
-            xmin: 317,
-            ymin: 329,
-            xmax: 451,
-            ymax: 798,
-          }),
+          Instruction::TurnOff(Rect::from_corners([660, 55], [986, 197])),
+          Instruction::Toggle(Rect::from_corners([322, 558], [977, 958])),
+          Instruction::TurnOn(Rect::from_corners([317, 329], [451, 798])),
         ]
       )
     );
   }
 
+  #[test]
+  fn out_of_range() {
+    assert_eq!(
+      parse_all(instructions)("toggle 0,0 through 1,1\nturn on 0,0 through 999,1000"),
+      Err(Error::Parse {
+        line: 2,
+        column: 9,
+        snippet: "0,0 through 999,1000".to_owned()
+      })
+    );
+    assert_eq!(
+      parse_all(instructions)("turn off -1,0 through 2,2"),
+      Err(Error::Parse {
+        line: 1,
+        column: 10,
+        snippet: "-1,0 through 2,2".to_owned()
+      })
+    );
+  }
+
   #[test]
   fn p1() {
     let input = "turn on 1,2 through 3,4
 turn off 3,4 through 5,6
 toggle 2,3 through 4,5
 ";
-    let (_, xs) = instructions(&input).unwrap();
-    assert_eq!(super::p1(xs), 11);
+    let (_, xs) = instructions(input).unwrap();
+    assert_eq!(super::p1(xs), Ok(11));
 
     let input = std::fs::read_to_string("./inputs/d06.txt").unwrap();
     let (_, xs) = instructions(&input).unwrap();
-    assert_eq!(super::p1(xs), 400410);
+    assert_eq!(super::p1(xs), Ok(400410));
   }
 
   #[test]
@@ -208,11 +217,11 @@ toggle 2,3 through 4,5
 turn off 3,4 through 5,6
 toggle 2,3 through 4,5
 ";
-    let (_, xs) = instructions(&input).unwrap();
-    assert_eq!(super::p2(xs), 26);
+    let (_, xs) = instructions(input).unwrap();
+    assert_eq!(super::p2(xs), Ok(26));
 
     let input = std::fs::read_to_string("./inputs/d06.txt").unwrap();
     let (_, xs) = instructions(&input).unwrap();
-    assert_eq!(super::p2(xs), 15343601);
+    assert_eq!(super::p2(xs), Ok(15343601));
   }
 }
@@ -1,10 +1,6 @@
-use nom::branch::alt;
-use nom::bytes::complete::tag;
-use nom::character::complete::{alpha1, digit1};
-use nom::combinator::{map, map_res};
-use nom::multi::{many0, many1};
-use nom::sequence::{delimited, tuple};
-use nom::IResult;
+use aoc_common::error::{Error, Result};
+use aoc_common::nom_prelude::*;
+use aoc_common::solution::Solution;
 use std::collections::HashMap;
 
 type Id = String;
@@ -64,18 +60,13 @@ fn identifier(input: &str) -> IResult<&str, Id> {
   map(alpha1, |s: &str| s.to_string())(input)
 }
 
-// TODO: is there an easy way to get `nom::number::complete::u16` to work for me.  The type checker is having trouble with the error type on it in the `tuple` combinator.
-fn u16p(input: &str) -> IResult<&str, u16> {
-  map_res(digit1, |s: &str| s.parse::<u16>())(input)
-}
-
 fn id_or_val_id(input: &str) -> IResult<&str, IdOrVal> {
   let (input, i) = identifier(input)?;
   Ok((input, IdOrVal::Id(i)))
 }
 
 fn id_or_val_val(input: &str) -> IResult<&str, IdOrVal> {
-  let (input, v) = u16p(input)?;
+  let (input, v) = parse_u16(input)?;
   Ok((input, IdOrVal::Val(v)))
 }
 
@@ -101,15 +92,25 @@ fn not(input: &str) -> IResult<&str, Instruction> {
 }
 
 fn rshift(input: &str) -> IResult<&str, Instruction> {
-  let (input, (from, _, shift, _, to)) =
-    tuple((id_or_val, tag(" RSHIFT "), u16p, tag(" -> "), identifier))(input)?;
+  let (input, (from, _, shift, _, to)) = tuple((
+    id_or_val,
+    tag(" RSHIFT "),
+    parse_u16,
+    tag(" -> "),
+    identifier,
+  ))(input)?;
   let shift = shift as usize;
   Ok((input, Instruction::RShift { from, shift, to }))
 }
 
 fn lshift(input: &str) -> IResult<&str, Instruction> {
-  let (input, (from, _, shift, _, to)) =
-    tuple((id_or_val, tag(" LSHIFT "), u16p, tag(" -> "), identifier))(input)?;
+  let (input, (from, _, shift, _, to)) = tuple((
+    id_or_val,
+    tag(" LSHIFT "),
+    parse_u16,
+    tag(" -> "),
+    identifier,
+  ))(input)?;
   let shift = shift as usize;
   Ok((input, Instruction::LShift { from, shift, to }))
 }
@@ -129,56 +130,78 @@ pub fn instructions(input: &str) -> IResult<&str, Vec<Instruction>> {
   many1(delimited_instruction)(input)
 }
 
-/// Recursive eval with memoization
-fn eval(insts: &HashMap<Id, Instruction>, memos: &mut HashMap<Id, u16>, id: &Id) -> u16 {
+/// Recursive eval with memoization.  `None` if some wire along the way has no signal.
+fn eval(insts: &HashMap<Id, Instruction>, memos: &mut HashMap<Id, u16>, id: &Id) -> Option<u16> {
   if let Some(val) = memos.get(id) {
-    return *val;
+    return Some(*val);
   }
-  let inst = insts.get(id).unwrap();
+  let inst = insts.get(id)?;
   let val = match inst {
     Instruction::And { from1, from2, .. } => {
-      eval_id_or_val(insts, memos, from1) & eval_id_or_val(insts, memos, from2)
+      eval_id_or_val(insts, memos, from1)? & eval_id_or_val(insts, memos, from2)?
     }
     Instruction::Or { from1, from2, .. } => {
-      eval_id_or_val(insts, memos, from1) | eval_id_or_val(insts, memos, from2)
+      eval_id_or_val(insts, memos, from1)? | eval_id_or_val(insts, memos, from2)?
     }
-    Instruction::Not { from, .. } => !eval_id_or_val(insts, memos, from),
-    Instruction::RShift { from, shift, .. } => eval_id_or_val(insts, memos, from) >> shift,
-    Instruction::LShift { from, shift, .. } => eval_id_or_val(insts, memos, from) << shift,
-    Instruction::Assign { from, .. } => eval_id_or_val(insts, memos, from),
+    Instruction::Not { from, .. } => !eval_id_or_val(insts, memos, from)?,
+    Instruction::RShift { from, shift, .. } => eval_id_or_val(insts, memos, from)? >> shift,
+    Instruction::LShift { from, shift, .. } => eval_id_or_val(insts, memos, from)? << shift,
+    Instruction::Assign { from, .. } => eval_id_or_val(insts, memos, from)?,
   };
   memos.insert(id.clone(), val);
-  val
+  Some(val)
 }
 
 fn eval_id_or_val(
   insts: &HashMap<Id, Instruction>,
   memos: &mut HashMap<Id, u16>,
   id_or_val: &IdOrVal,
-) -> u16 {
+) -> Option<u16> {
   match id_or_val {
     IdOrVal::Id(id) => eval(insts, memos, id),
-    IdOrVal::Val(val) => *val,
+    IdOrVal::Val(val) => Some(*val),
   }
 }
 
-pub fn p1(input: Vec<Instruction>) -> u16 {
+pub fn p1(input: Vec<Instruction>) -> Result<u16> {
   let insts = input
     .into_iter()
     .map(|i| (i.to(), i))
     .collect::<HashMap<_, _>>();
   let mut memos = HashMap::new();
-  eval(&insts, &mut memos, &"a".to_string())
+  eval(&insts, &mut memos, &"a".to_string()).ok_or(Error::NoSolution)
 }
 
-pub fn p2(input: Vec<Instruction>) -> u16 {
+pub fn p2(input: Vec<Instruction>) -> Result<u16> {
   let insts = input
     .into_iter()
     .map(|i| (i.to(), i))
     .collect::<HashMap<_, _>>();
   let mut memos = HashMap::new();
   memos.insert("b".to_string(), 3176);
-  eval(&insts, &mut memos, &"a".to_string())
+  eval(&insts, &mut memos, &"a".to_string()).ok_or(Error::NoSolution)
+}
+
+pub struct Day;
+
+impl Solution for Day {
+  const YEAR: u32 = 2015;
+  const DAY: u32 = 7;
+  type Input<'a> = Vec<Instruction>;
+  type Output1 = u16;
+  type Output2 = u16;
+
+  fn parse(input: &str) -> Result<Self::Input<'_>> {
+    parse_all(instructions)(input)
+  }
+
+  fn part1(input: Self::Input<'_>) -> Result<u16> {
+    p1(input)
+  }
+
+  fn part2(input: Self::Input<'_>) -> Result<u16> {
+    p2(input)
+  }
 }
 
 #[cfg(test)]
@@ -252,18 +275,18 @@ a -> g";
       .map(|i| (i.to(), i))
       .collect::<HashMap<_, _>>();
     let mut memos = HashMap::new();
-    assert_eq!(eval(&insts, &mut memos, &"a".to_string()), 1);
-    assert_eq!(eval(&insts, &mut memos, &"b".to_string()), 2);
-    assert_eq!(eval(&insts, &mut memos, &"c".to_string()), 3);
-    assert_eq!(eval(&insts, &mut memos, &"d".to_string()), 1);
-    assert_eq!(eval(&insts, &mut memos, &"e".to_string()), 65534);
-    assert_eq!(eval(&insts, &mut memos, &"f".to_string()), 1);
-    assert_eq!(eval(&insts, &mut memos, &"g".to_string()), 1);
+    assert_eq!(eval(&insts, &mut memos, &"a".to_string()), Some(1));
+    assert_eq!(eval(&insts, &mut memos, &"b".to_string()), Some(2));
+    assert_eq!(eval(&insts, &mut memos, &"c".to_string()), Some(3));
+    assert_eq!(eval(&insts, &mut memos, &"d".to_string()), Some(1));
+    assert_eq!(eval(&insts, &mut memos, &"e".to_string()), Some(65534));
+    assert_eq!(eval(&insts, &mut memos, &"f".to_string()), Some(1));
+    assert_eq!(eval(&insts, &mut memos, &"g".to_string()), Some(1));
 
     let input = std::fs::read_to_string("./inputs/d07.txt").unwrap();
     let (_, input) = instructions(&input).unwrap();
 
-    assert_eq!(super::p1(input), 3176);
+    assert_eq!(super::p1(input), Ok(3176));
   }
 
   #[test]
@@ -271,6 +294,13 @@ a -> g";
     let input = std::fs::read_to_string("./inputs/d07.txt").unwrap();
     let (_, input) = instructions(&input).unwrap();
 
-    assert_eq!(super::p2(input), 14710);
+    assert_eq!(super::p2(input), Ok(14710));
+  }
+
+  #[test]
+  fn test_no_solution() {
+    // `a` is wired to `b`, which nothing drives.
+    let (_, input) = instructions("b AND 1 -> a").unwrap();
+    assert_eq!(super::p1(input), Err(Error::NoSolution));
   }
 }
@@ -1,10 +1,6 @@
-use nom::branch::alt;
-use nom::bytes::complete::tag;
-use nom::character::complete::{anychar, none_of};
-use nom::combinator::map;
-use nom::multi::{many0, many1};
-use nom::sequence::{delimited, tuple};
-use nom::IResult;
+use aoc_common::error::Result;
+use aoc_common::nom_prelude::*;
+use aoc_common::solution::Solution;
 
 #[derive(Debug, Eq, PartialEq)]
 pub struct StringLiteral {
@@ -54,16 +50,16 @@ impl Char {
   }
 }
 
-pub fn p1(input: &[StringLiteral]) -> u32 {
+pub fn p1(input: &[StringLiteral]) -> Result<u32> {
   let lit = input.iter().map(StringLiteral::literal_bytes).sum::<u32>();
   let mem = input.iter().map(StringLiteral::memory_bytes).sum::<u32>();
-  lit - mem
+  Ok(lit - mem)
 }
 
-pub fn p2(input: &[StringLiteral]) -> u32 {
+pub fn p2(input: &[StringLiteral]) -> Result<u32> {
   let escaped = input.iter().map(StringLiteral::escaped_bytes).sum::<u32>();
   let lit = input.iter().map(StringLiteral::literal_bytes).sum::<u32>();
-  escaped - lit
+  Ok(escaped - lit)
 }
 
 fn quote(input: &str) -> IResult<&str, Char> {
@@ -81,7 +77,7 @@ fn hex(input: &str) -> IResult<&str, Char> {
 
 // Technically I should exclude the backslash character, but since `alt` "falls through", this is fine.
 fn char_char(input: &str) -> IResult<&str, Char> {
-  map(none_of(r#""\"#), |c| Char::Char(c))(input)
+  map(none_of(r#""\"#), Char::Char)(input)
 }
 
 fn char_p(input: &str) -> IResult<&str, Char> {
@@ -101,6 +97,28 @@ pub fn string_literals(input: &str) -> IResult<&str, Vec<StringLiteral>> {
   ))(input)
 }
 
+pub struct Day;
+
+impl Solution for Day {
+  const YEAR: u32 = 2015;
+  const DAY: u32 = 8;
+  type Input<'a> = Vec<StringLiteral>;
+  type Output1 = u32;
+  type Output2 = u32;
+
+  fn parse(input: &str) -> Result<Self::Input<'_>> {
+    parse_all(string_literals)(input)
+  }
+
+  fn part1(input: Self::Input<'_>) -> Result<u32> {
+    p1(&input)
+  }
+
+  fn part2(input: Self::Input<'_>) -> Result<u32> {
+    p2(&input)
+  }
+}
+
 #[cfg(test)]
 mod test {
   use super::*;
@@ -126,13 +144,13 @@ mod test {
   fn p1() {
     let input = std::fs::read_to_string("./inputs/d08.txt").unwrap();
     let (_, input) = string_literals(&input).unwrap();
-    assert_eq!(super::p1(&input), 1333);
+    assert_eq!(super::p1(&input), Ok(1333));
   }
 
   #[test]
   fn p2() {
     let input = std::fs::read_to_string("./inputs/d08.txt").unwrap();
     let (_, input) = string_literals(&input).unwrap();
-    assert_eq!(super::p2(&input), 2046);
+    assert_eq!(super::p2(&input), Ok(2046));
   }
 }
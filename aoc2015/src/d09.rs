@@ -1,10 +1,7 @@
+use aoc_common::error::{Error, Result};
+use aoc_common::nom_prelude::*;
+use aoc_common::solution::Solution;
 use itertools::Itertools;
-use nom::bytes::complete::tag;
-use nom::character::complete::{alpha1, digit1};
-use nom::combinator::{map, map_res};
-use nom::multi::{many0, many1};
-use nom::sequence::{delimited, tuple};
-use nom::IResult;
 use std::collections::HashMap;
 
 type DistanceEntry = (City, City, u32);
@@ -16,13 +13,7 @@ fn city(input: &str) -> IResult<&str, City> {
 }
 
 fn distance_entry(input: &str) -> IResult<&str, DistanceEntry> {
-  let (input, (c1, _, c2, _, d)) = tuple((
-    city,
-    tag(" to "),
-    city,
-    tag(" = "),
-    map_res(digit1, |s: &str| s.parse::<u32>()),
-  ))(input)?;
+  let (input, (c1, _, c2, _, d)) = tuple((city, tag(" to "), city, tag(" = "), parse_u32))(input)?;
   Ok((input, (c1, c2, d)))
 }
 
@@ -34,6 +25,15 @@ pub fn distance_entries(input: &str) -> IResult<&str, Vec<DistanceEntry>> {
   ))(input)
 }
 
+pub fn distances(input: &str) -> IResult<&str, Distances> {
+  map(distance_entries, |entries| {
+    entries
+      .into_iter()
+      .map(|(c1, c2, d)| ((c1, c2), d))
+      .collect()
+  })(input)
+}
+
 // TODO: learn more about graph traversal stuff, especially travelling salesman.
 // But 7! is not too much for a naive approach.
 
@@ -46,7 +46,8 @@ pub fn cities(distances: &Distances) -> Vec<City> {
     .collect()
 }
 
-pub fn path_distance(distances: &Distances, path: &[&City]) -> u32 {
+/// `None` if two neighbouring cities on the path have no road between them.
+pub fn path_distance(distances: &Distances, path: &[&City]) -> Option<u32> {
   path
     .iter()
     .zip(path.iter().skip(1))
@@ -54,16 +55,54 @@ pub fn path_distance(distances: &Distances, path: &[&City]) -> u32 {
       distances
         .get(&(c1.to_string(), c2.to_string()))
         .or_else(|| distances.get(&(c2.to_string(), c1.to_string())))
-        .unwrap()
     })
-    .sum::<u32>()
+    .sum::<Option<u32>>()
 }
 
-// The distances passed in can be unidirectional; we'll make them bidirectional ourselves
-pub fn p1(distances: &Distances) -> u32 {
+/// The length of every route that visits each city once.
+fn path_distances(distances: &Distances) -> Vec<u32> {
   let cs = cities(distances);
-  cs.iter().permutations(cs.len());
-  0
+  cs.iter()
+    .permutations(cs.len())
+    .filter_map(|path| path_distance(distances, &path))
+    .collect()
+}
+
+// The distances passed in can be unidirectional; we'll make them bidirectional ourselves
+pub fn p1(distances: &Distances) -> Result<u32> {
+  path_distances(distances)
+    .into_iter()
+    .min()
+    .ok_or(Error::NoSolution)
+}
+
+pub fn p2(distances: &Distances) -> Result<u32> {
+  path_distances(distances)
+    .into_iter()
+    .max()
+    .ok_or(Error::NoSolution)
+}
+
+pub struct Day;
+
+impl Solution for Day {
+  const YEAR: u32 = 2015;
+  const DAY: u32 = 9;
+  type Input<'a> = Distances;
+  type Output1 = u32;
+  type Output2 = u32;
+
+  fn parse(input: &str) -> Result<Self::Input<'_>> {
+    parse_all(distances)(input)
+  }
+
+  fn part1(input: Self::Input<'_>) -> Result<u32> {
+    p1(&input)
+  }
+
+  fn part2(input: Self::Input<'_>) -> Result<u32> {
+    p2(&input)
+  }
 }
 
 #[cfg(test)]
@@ -85,4 +124,40 @@ asdf to qwer = 3";
       )
     )
   }
+
+  #[test]
+  fn p1() {
+    let input = "London to Dublin = 464
+London to Belfast = 518
+Dublin to Belfast = 141";
+    let (_, ds) = distances(input).unwrap();
+    assert_eq!(super::p1(&ds), Ok(605));
+
+    let input = std::fs::read_to_string("./inputs/d09.txt").unwrap();
+    let (_, ds) = distances(&input).unwrap();
+    assert_eq!(super::p1(&ds), Ok(141));
+  }
+
+  #[test]
+  fn p2() {
+    let input = "London to Dublin = 464
+London to Belfast = 518
+Dublin to Belfast = 141";
+    let (_, ds) = distances(input).unwrap();
+    assert_eq!(super::p2(&ds), Ok(982));
+
+    let input = std::fs::read_to_string("./inputs/d09.txt").unwrap();
+    let (_, ds) = distances(&input).unwrap();
+    assert_eq!(super::p2(&ds), Ok(736));
+  }
+
+  #[test]
+  fn test_no_solution() {
+    // No road reaches Paris.
+    let input = "London to Dublin = 464
+Belfast to Paris = 141";
+    let (_, ds) = distances(input).unwrap();
+    assert_eq!(super::p1(&ds), Err(Error::NoSolution));
+    assert_eq!(super::p2(&ds), Err(Error::NoSolution));
+  }
 }
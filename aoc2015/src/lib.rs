@@ -8,3 +8,41 @@ pub mod d06;
 pub mod d07;
 pub mod d08;
 pub mod d09;
+
+use aoc_common::solution::Registry;
+
+/// Registers every day of 2015 solved so far.
+pub fn register(registry: &mut Registry) {
+  registry
+    .register::<d01::Day>()
+    .register::<d02::Day>()
+    .register::<d03::Day>()
+    .register::<d04::Day>()
+    .register::<d05::Day>()
+    .register::<d06::Day>()
+    .register::<d07::Day>()
+    .register::<d08::Day>()
+    .register::<d09::Day>();
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use aoc_common::solution::Part;
+
+  #[test]
+  fn register() {
+    let mut registry = Registry::new();
+    super::register(&mut registry);
+    assert_eq!(
+      registry.days().collect::<Vec<_>>(),
+      (1..=9).map(|day| (2015, day)).collect::<Vec<_>>()
+    );
+    assert_eq!(
+      registry
+        .solve(2015, 4, Part::One, "ckczppom\n")
+        .map(|r| r.map(|a| a.answer)),
+      Some(Ok("117946".to_owned()))
+    );
+  }
+}
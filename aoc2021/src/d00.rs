@@ -1,19 +1,43 @@
 //! https://adventofcode.com/2021/day/00
 
-use crate::nom_prelude::*;
+use aoc_common::error::Result;
+use aoc_common::nom_prelude::*;
+use aoc_common::solution::Solution;
 
 type Input = ();
 
-pub fn parse(input: &str) -> IResult<&str, Input> {
-  unimplemented!()
+pub fn parse(input: &str) -> Result<Input> {
+  parse_all(|_| unimplemented!())(input)
 }
 
-pub fn p1(_input: Input) -> usize {
-  0
+pub fn p1(_input: Input) -> Result<usize> {
+  Ok(0)
 }
 
-pub fn p2(_input: Input) -> usize {
-  0
+pub fn p2(_input: Input) -> Result<usize> {
+  Ok(0)
+}
+
+pub struct Day;
+
+impl Solution for Day {
+  const YEAR: u32 = 2021;
+  const DAY: u32 = 0;
+  type Input<'a> = Input;
+  type Output1 = usize;
+  type Output2 = usize;
+
+  fn parse(input: &str) -> Result<Self::Input<'_>> {
+    parse(input)
+  }
+
+  fn part1(input: Self::Input<'_>) -> Result<usize> {
+    p1(input)
+  }
+
+  fn part2(input: Self::Input<'_>) -> Result<usize> {
+    p2(input)
+  }
 }
 
 #[cfg(test)]
@@ -25,28 +49,28 @@ mod test {
   #[test]
   fn test_parse() {
     let input = "";
-    assert_eq!(parse(input).unwrap(), ("", ()))
+    assert_eq!(parse(input).unwrap(), ())
   }
 
   #[test]
   fn test_p1() {
     let input = TEST_INPUT;
-    let parsed = parse(input).unwrap().1;
-    assert_eq!(p1(parsed), 0);
+    let parsed = parse(input).unwrap();
+    assert_eq!(p1(parsed), Ok(0));
 
     let input = std::fs::read_to_string("./inputs/d00.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
-    assert_eq!(p1(parsed), 0);
+    let parsed = parse(&input).unwrap();
+    assert_eq!(p1(parsed), Ok(0));
   }
 
   #[test]
   fn test_p2() {
     let input = TEST_INPUT;
-    let parsed = parse(input).unwrap().1;
-    assert_eq!(p2(parsed), 0);
+    let parsed = parse(input).unwrap();
+    assert_eq!(p2(parsed), Ok(0));
 
     let input = std::fs::read_to_string("./inputs/d00.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
-    assert_eq!(p2(parsed), 0);
+    let parsed = parse(&input).unwrap();
+    assert_eq!(p2(parsed), Ok(0));
   }
 }
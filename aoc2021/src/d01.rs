@@ -3,6 +3,9 @@
 /// In part two, you actually only need to compare the first number of the first triple with the third number of the second triple.  If you use this approach, p2 only differs from p1 only in how much you skip in the second iterator.
 ///
 /// I had the amusing experience of half-way realizing this while I was writing my tests.  Somehow, I did not make the small additional leap to realizing that I could use this in my implementation.  My eye was too much on the goal, I guess.  On the other hand, I suppose that "make it work" comes before "make it fast", so it was reasonable to do a slower straightforward solution over one with a clever trick.  (Ok, the trick isn't that clever after all; but still!)
+use aoc_common::error::Result;
+use aoc_common::nom_prelude::parse_all;
+use aoc_common::solution::Solution;
 use itertools::Itertools;
 use nom::character::complete::{digit1, multispace0};
 use nom::combinator::{map, map_res};
@@ -14,27 +17,57 @@ use nom::IResult;
 #[derive(Debug, Eq, PartialEq)]
 pub struct Depth(u32);
 
-pub fn parse(input: &str) -> IResult<&str, Vec<Depth>> {
+fn parse_input(input: &str) -> IResult<&str, Vec<Depth>> {
   let depth = map(map_res(digit1, |s: &str| s.parse::<u32>()), Depth);
   many1(terminated(depth, multispace0))(input)
 }
 
-pub fn p1(depths: Vec<Depth>) -> usize {
-  depths
-    .iter()
-    .zip(depths.iter().skip(1))
-    .filter(|(d1, d2)| d1.0 < d2.0)
-    .count()
+pub fn parse(input: &str) -> Result<Vec<Depth>> {
+  parse_all(parse_input)(input)
 }
 
-pub fn p2(depths: Vec<Depth>) -> usize {
-  depths
-    .iter()
-    .tuple_windows()
-    .map(|(x, y, z)| x.0 + y.0 + z.0)
-    .tuple_windows()
-    .filter(|(x, y)| x < y)
-    .count()
+pub fn p1(depths: Vec<Depth>) -> Result<usize> {
+  Ok(
+    depths
+      .iter()
+      .zip(depths.iter().skip(1))
+      .filter(|(d1, d2)| d1.0 < d2.0)
+      .count(),
+  )
+}
+
+pub fn p2(depths: Vec<Depth>) -> Result<usize> {
+  Ok(
+    depths
+      .iter()
+      .tuple_windows()
+      .map(|(x, y, z)| x.0 + y.0 + z.0)
+      .tuple_windows()
+      .filter(|(x, y)| x < y)
+      .count(),
+  )
+}
+
+pub struct Day;
+
+impl Solution for Day {
+  const YEAR: u32 = 2021;
+  const DAY: u32 = 1;
+  type Input<'a> = Vec<Depth>;
+  type Output1 = usize;
+  type Output2 = usize;
+
+  fn parse(input: &str) -> Result<Self::Input<'_>> {
+    parse(input)
+  }
+
+  fn part1(input: Self::Input<'_>) -> Result<usize> {
+    p1(input)
+  }
+
+  fn part2(input: Self::Input<'_>) -> Result<usize> {
+    p2(input)
+  }
 }
 
 #[cfg(test)]
@@ -45,7 +78,7 @@ mod test {
   fn test_parse() {
     let input = "123
 321";
-    assert_eq!(parse(input).unwrap(), ("", vec![Depth(123), Depth(321)]))
+    assert_eq!(parse(input).unwrap(), vec![Depth(123), Depth(321)])
   }
 
   #[test]
@@ -53,12 +86,12 @@ mod test {
     let input = "123
 321
 123";
-    let ds = parse(input).unwrap().1;
-    assert_eq!(p1(ds), 1);
+    let ds = parse(input).unwrap();
+    assert_eq!(p1(ds), Ok(1));
 
     let input = std::fs::read_to_string("./inputs/d01.txt").unwrap();
-    let ds = parse(&input).unwrap().1;
-    assert_eq!(p1(ds), 1624);
+    let ds = parse(&input).unwrap();
+    assert_eq!(p1(ds), Ok(1624));
   }
 
   #[test]
@@ -68,11 +101,11 @@ mod test {
 123
 321
 123";
-    let ds = parse(input).unwrap().1;
-    assert_eq!(p2(ds), 1);
+    let ds = parse(input).unwrap();
+    assert_eq!(p2(ds), Ok(1));
 
     let input = std::fs::read_to_string("./inputs/d01.txt").unwrap();
-    let ds = parse(&input).unwrap().1;
-    assert_eq!(p2(ds), 1653);
+    let ds = parse(&input).unwrap();
+    assert_eq!(p2(ds), Ok(1653));
   }
 }
@@ -1,3 +1,6 @@
+use aoc_common::error::Result;
+use aoc_common::nom_prelude::parse_all;
+use aoc_common::solution::Solution;
 use nom::branch::alt;
 use nom::bytes::complete::tag;
 use nom::character::complete::{digit1, multispace0};
@@ -23,7 +26,7 @@ fn direction<'a, F: Fn(u32) -> Direction>(
   )
 }
 
-pub fn parse(input: &str) -> IResult<&str, Vec<Direction>> {
+fn parse_input(input: &str) -> IResult<&str, Vec<Direction>> {
   let forward = direction("forward ", Direction::Forward);
   let down = direction("down ", Direction::Down);
   let up = direction("up ", Direction::Up);
@@ -34,7 +37,11 @@ pub fn parse(input: &str) -> IResult<&str, Vec<Direction>> {
   ))(input)
 }
 
-pub fn p1(directions: Vec<Direction>) -> usize {
+pub fn parse(input: &str) -> Result<Vec<Direction>> {
+  parse_all(parse_input)(input)
+}
+
+pub fn p1(directions: Vec<Direction>) -> Result<usize> {
   let (x, y) = directions
     .into_iter()
     .fold((0_i32, 0_i32), |(x, y), d| match d {
@@ -42,10 +49,10 @@ pub fn p1(directions: Vec<Direction>) -> usize {
       Direction::Up(n) => (x, y - n as i32),
       Direction::Down(n) => (x, y + n as i32),
     });
-  (x * y) as usize
+  Ok((x * y) as usize)
 }
 
-pub fn p2(directions: Vec<Direction>) -> usize {
+pub fn p2(directions: Vec<Direction>) -> Result<usize> {
   let (x, y, _aim) = directions
     .into_iter()
     .fold((0_i32, 0_i32, 0_i32), |(x, y, aim), d| match d {
@@ -53,12 +60,35 @@ pub fn p2(directions: Vec<Direction>) -> usize {
       Direction::Up(n) => (x, y, aim - n as i32),
       Direction::Down(n) => (x, y, aim + n as i32),
     });
-  (x * y) as usize
+  Ok((x * y) as usize)
+}
+
+pub struct Day;
+
+impl Solution for Day {
+  const YEAR: u32 = 2021;
+  const DAY: u32 = 2;
+  type Input<'a> = Vec<Direction>;
+  type Output1 = usize;
+  type Output2 = usize;
+
+  fn parse(input: &str) -> Result<Self::Input<'_>> {
+    parse(input)
+  }
+
+  fn part1(input: Self::Input<'_>) -> Result<usize> {
+    p1(input)
+  }
+
+  fn part2(input: Self::Input<'_>) -> Result<usize> {
+    p2(input)
+  }
 }
 
 #[cfg(test)]
 mod test {
   use super::*;
+  use aoc_common::error::Error;
 
   #[test]
   fn test_parse() {
@@ -66,10 +96,19 @@ mod test {
     let input = "forward 5
     down 5
     up 3";
+    assert_eq!(parse(input).unwrap(), vec![Forward(5), Down(5), Up(3)]);
+
+    let input = "forward 5
+    sideways 5
+    up 3";
     assert_eq!(
-      parse(input).unwrap(),
-      ("", vec![Forward(5), Down(5), Up(3)])
-    )
+      parse(input),
+      Err(Error::TrailingInput {
+        line: 2,
+        column: 5,
+        snippet: "sideways 5".to_owned()
+      })
+    );
   }
 
   #[test]
@@ -80,12 +119,12 @@ mod test {
     up 3
     down 8
     forward 2";
-    let ds = parse(input).unwrap().1;
-    assert_eq!(p1(ds), 150);
+    let ds = parse(input).unwrap();
+    assert_eq!(p1(ds), Ok(150));
 
     let input = std::fs::read_to_string("./inputs/d02.txt").unwrap();
-    let ds = parse(&input).unwrap().1;
-    assert_eq!(p1(ds), 2091984);
+    let ds = parse(&input).unwrap();
+    assert_eq!(p1(ds), Ok(2091984));
   }
 
   #[test]
@@ -96,11 +135,11 @@ mod test {
     up 3
     down 8
     forward 2";
-    let ds = parse(input).unwrap().1;
-    assert_eq!(p2(ds), 900);
+    let ds = parse(input).unwrap();
+    assert_eq!(p2(ds), Ok(900));
 
     let input = std::fs::read_to_string("./inputs/d02.txt").unwrap();
-    let ds = parse(&input).unwrap().1;
-    assert_eq!(p2(ds), 2086261056);
+    let ds = parse(&input).unwrap();
+    assert_eq!(p2(ds), Ok(2086261056));
   }
 }
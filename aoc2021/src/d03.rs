@@ -1,8 +1,11 @@
+use aoc_common::error::Result;
 /// # Reflection
 ///
 /// I definitely paid for my decision to parse these numbers directly into u32s.  Specifically, I got bit in places where the width of the binary representation of the number is important (e.g. negating 0110 is not the same as negating 00000110).
 ///
 /// For part two, if I were to sort my numbers I could do things without cloning, since filtering would just be adjusting the bounds of a slice.
+use aoc_common::nom_prelude::parse_all;
+use aoc_common::solution::Solution;
 use nom::bytes::complete::take_while;
 use nom::character::complete::multispace0;
 use nom::combinator::map_res;
@@ -12,7 +15,7 @@ use nom::IResult;
 
 type InputItem = u32;
 
-pub fn parse(input: &str) -> IResult<&str, Vec<InputItem>> {
+fn parse_input(input: &str) -> IResult<&str, Vec<InputItem>> {
   let binary_digit = |c: char| c == '0' || c == '1';
   let binary_parse = |s: &str| u32::from_str_radix(s, 2);
   many1(delimited(
@@ -22,24 +25,28 @@ pub fn parse(input: &str) -> IResult<&str, Vec<InputItem>> {
   ))(input)
 }
 
+pub fn parse(input: &str) -> Result<Vec<InputItem>> {
+  parse_all(parse_input)(input)
+}
+
 fn ones_in_pos(xs: &[u32], loc: usize) -> usize {
   xs.iter().filter(|x| (*x & 1 << loc) != 0).count()
 }
 
-pub fn p1(xs: Vec<InputItem>, item_bits: usize) -> u32 {
+pub fn p1(xs: Vec<InputItem>, item_bits: usize) -> Result<u32> {
   let mut gamma = 0;
   let threshold = xs.len() / 2;
   for i in 0..item_bits {
     if ones_in_pos(&xs, i) > threshold {
-      gamma = gamma | 1 << i;
+      gamma |= 1 << i;
     }
   }
   // Don't want the leading bits to get flipped.
   let epsilon = !gamma & (u32::MAX >> (32 - item_bits));
-  epsilon * gamma
+  Ok(epsilon * gamma)
 }
 
-pub fn p2(xs: Vec<InputItem>, item_bits: usize) -> u32 {
+pub fn p2(xs: Vec<InputItem>, item_bits: usize) -> Result<u32> {
   let mut oxygen_candidates = xs.clone();
   let mut oxygen = 0;
   // Want to start with most significant first
@@ -76,7 +83,33 @@ pub fn p2(xs: Vec<InputItem>, item_bits: usize) -> u32 {
     }
   }
 
-  oxygen * co2
+  Ok(oxygen * co2)
+}
+
+pub struct Day;
+
+impl Solution for Day {
+  const YEAR: u32 = 2021;
+  const DAY: u32 = 3;
+  type Input<'a> = (Vec<InputItem>, usize);
+  type Output1 = u32;
+  type Output2 = u32;
+
+  fn parse(input: &str) -> Result<Self::Input<'_>> {
+    // The puzzle only makes sense with the width of the numbers, which we'd otherwise lose.
+    let item_bits = input.split_whitespace().next().map_or(0, str::len);
+    parse(input).map(|xs| (xs, item_bits))
+  }
+
+  fn part1(input: Self::Input<'_>) -> Result<u32> {
+    let (xs, item_bits) = input;
+    p1(xs, item_bits)
+  }
+
+  fn part2(input: Self::Input<'_>) -> Result<u32> {
+    let (xs, item_bits) = input;
+    p2(xs, item_bits)
+  }
 }
 
 #[cfg(test)]
@@ -88,7 +121,7 @@ mod test {
     let input = "00100
     11110
     10110";
-    assert_eq!(parse(input).unwrap(), ("", vec![0b00100, 0b11110, 0b10110]))
+    assert_eq!(parse(input).unwrap(), vec![0b00100, 0b11110, 0b10110])
   }
 
   #[test]
@@ -105,12 +138,12 @@ mod test {
     11001
     00010
     01010";
-    let parsed = parse(input).unwrap().1;
-    assert_eq!(p1(parsed, 5), 198);
+    let parsed = parse(input).unwrap();
+    assert_eq!(p1(parsed, 5), Ok(198));
 
     let input = std::fs::read_to_string("./inputs/d03.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
-    assert_eq!(p1(parsed, 12), 3374136);
+    let parsed = parse(&input).unwrap();
+    assert_eq!(p1(parsed, 12), Ok(3374136));
   }
 
   #[test]
@@ -127,11 +160,11 @@ mod test {
     11001
     00010
     01010";
-    let parsed = parse(input).unwrap().1;
-    assert_eq!(p2(parsed, 5), 230);
+    let parsed = parse(input).unwrap();
+    assert_eq!(p2(parsed, 5), Ok(230));
 
     let input = std::fs::read_to_string("./inputs/d03.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
-    assert_eq!(p2(parsed, 12), 4432698);
+    let parsed = parse(&input).unwrap();
+    assert_eq!(p2(parsed, 12), Ok(4432698));
   }
 }
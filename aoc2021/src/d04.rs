@@ -1,3 +1,6 @@
+use aoc_common::error::{Error, Result};
+use aoc_common::nom_prelude::parse_all;
+use aoc_common::solution::Solution;
 use nom::character::complete::{char as parse_char, digit1, multispace0, space0};
 use nom::combinator::{map, map_res};
 use nom::multi::{count, many1, separated_list1};
@@ -92,46 +95,76 @@ pub fn parse_board(input: &str) -> IResult<&str, Board> {
   ))
 }
 
-pub fn parse(input: &str) -> IResult<&str, Bingo> {
+fn parse_input(input: &str) -> IResult<&str, Bingo> {
   let (input, draws) = parse_draws(input)?;
   let (input, boards) = many1(parse_board)(input)?;
   Ok((input, Bingo { draws, boards }))
 }
 
-pub fn p1(mut input: Bingo) -> u32 {
+pub fn parse(input: &str) -> Result<Bingo> {
+  parse_all(parse_input)(input)
+}
+
+pub fn p1(mut input: Bingo) -> Result<u32> {
   for draw in input.draws {
     for board in input.boards.iter_mut() {
       if board.mark(draw) && board.has_victory() {
-        return board.unmarked().sum::<u32>() * draw;
+        return Ok(board.unmarked().sum::<u32>() * draw);
       }
     }
   }
-  panic!("no solution")
+  Err(Error::NoSolution)
 }
 
-pub fn p2(mut input: Bingo) -> u32 {
+pub fn p2(mut input: Bingo) -> Result<u32> {
+  // Every board has to win at some point for there to be a last one.
   let scored = input
     .boards
     .iter_mut()
     .map(|board| {
       for (i, draw) in input.draws.iter().enumerate() {
         if board.mark(*draw) && board.has_victory() {
-          return (board.unmarked().sum::<u32>() * *draw, i);
+          return Some((board.unmarked().sum::<u32>() * *draw, i));
         }
       }
-      panic!("no solution")
+      None
     })
+    .collect::<Option<Vec<_>>>()
+    .ok_or(Error::NoSolution)?;
+  scored
+    .into_iter()
     .max_by_key(|(_, i)| *i)
-    .unwrap();
-  scored.0
+    .map(|(score, _)| score)
+    .ok_or(Error::NoSolution)
+}
+
+pub struct Day;
+
+impl Solution for Day {
+  const YEAR: u32 = 2021;
+  const DAY: u32 = 4;
+  type Input<'a> = Bingo;
+  type Output1 = u32;
+  type Output2 = u32;
+
+  fn parse(input: &str) -> Result<Self::Input<'_>> {
+    parse(input)
+  }
+
+  fn part1(input: Self::Input<'_>) -> Result<u32> {
+    p1(input)
+  }
+
+  fn part2(input: Self::Input<'_>) -> Result<u32> {
+    p2(input)
+  }
 }
 
 #[cfg(test)]
 mod test {
   use super::*;
 
-  const TEST_INPUT: &'static str =
-    "7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1
+  const TEST_INPUT: &str = "7,4,9,5,11,17,23,2,0,14,21,24,10,16,13,6,15,25,12,22,18,20,8,19,3,26,1
 
   22 13 17 11  0
    8  2 23  4 24
@@ -154,7 +187,7 @@ mod test {
   #[test]
   fn test_parse() {
     let input = TEST_INPUT;
-    let output = parse(input).unwrap().1;
+    let output = parse(input).unwrap();
     assert_eq!(*output.draws.first().unwrap(), 7);
     assert_eq!(*output.draws.last().unwrap(), 1);
     assert_eq!(output.boards.len(), 3);
@@ -163,22 +196,34 @@ mod test {
   #[test]
   fn test_p1() {
     let input = TEST_INPUT;
-    let parsed = parse(input).unwrap().1;
-    assert_eq!(p1(parsed), 4512);
+    let parsed = parse(input).unwrap();
+    assert_eq!(p1(parsed), Ok(4512));
 
     let input = std::fs::read_to_string("./inputs/d04.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
-    assert_eq!(p1(parsed), 50008);
+    let parsed = parse(&input).unwrap();
+    assert_eq!(p1(parsed), Ok(50008));
   }
 
   #[test]
   fn test_p2() {
     let input = TEST_INPUT;
-    let parsed = parse(input).unwrap().1;
-    assert_eq!(p2(parsed), 1924);
+    let parsed = parse(input).unwrap();
+    assert_eq!(p2(parsed), Ok(1924));
 
     let input = std::fs::read_to_string("./inputs/d04.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
-    assert_eq!(p2(parsed), 17408);
+    let parsed = parse(&input).unwrap();
+    assert_eq!(p2(parsed), Ok(17408));
+  }
+
+  #[test]
+  fn test_no_solution() {
+    // Nobody has won after the first four draws.
+    let mut parsed = parse(TEST_INPUT).unwrap();
+    parsed.draws.truncate(4);
+    assert_eq!(p1(parsed), Err(Error::NoSolution));
+
+    let mut parsed = parse(TEST_INPUT).unwrap();
+    parsed.draws.truncate(4);
+    assert_eq!(p2(parsed), Err(Error::NoSolution));
   }
 }
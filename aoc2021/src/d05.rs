@@ -1,4 +1,7 @@
-use crate::nom_prelude::parse_u32;
+use aoc_common::error::Result;
+use aoc_common::nom_prelude::parse_all;
+use aoc_common::nom_prelude::parse_u32;
+use aoc_common::solution::Solution;
 use itertools::Itertools;
 use nom::bytes::complete::tag;
 use nom::character::complete::multispace0;
@@ -51,7 +54,7 @@ impl Line {
   }
 }
 
-pub fn parse(input: &str) -> IResult<&str, Vec<Line>> {
+fn parse_input(input: &str) -> IResult<&str, Vec<Line>> {
   let line = map(
     tuple((
       parse_u32,
@@ -72,9 +75,13 @@ pub fn parse(input: &str) -> IResult<&str, Vec<Line>> {
   many1(delimited(multispace0, line, multispace0))(input)
 }
 
+pub fn parse(input: &str) -> Result<Vec<Line>> {
+  parse_all(parse_input)(input)
+}
+
 // The naïve way, until we find we need more efficiency.
-pub fn p1(xs: Vec<Line>) -> usize {
-  let mut counter = crate::util::HashCounter::new();
+pub fn p1(xs: Vec<Line>) -> Result<usize> {
+  let mut counter = aoc_common::util::HashCounter::new();
   for line in xs
     .into_iter()
     .filter(|line| line.is_horizontal() || line.is_vertical())
@@ -87,17 +94,39 @@ pub fn p1(xs: Vec<Line>) -> usize {
       counter.inc(point);
     }
   }
-  counter.iter().filter(|(_, count)| *count > 1).count()
+  Ok(counter.iter().filter(|(_, count)| *count > 1).count())
 }
 
-pub fn p2(lines: Vec<Line>) -> usize {
-  let mut counter = crate::util::HashCounter::new();
+pub fn p2(lines: Vec<Line>) -> Result<usize> {
+  let mut counter = aoc_common::util::HashCounter::new();
   for line in lines {
     for point in line.points().into_iter() {
       counter.inc(point);
     }
   }
-  counter.iter().filter(|(_, count)| *count > 1).count()
+  Ok(counter.iter().filter(|(_, count)| *count > 1).count())
+}
+
+pub struct Day;
+
+impl Solution for Day {
+  const YEAR: u32 = 2021;
+  const DAY: u32 = 5;
+  type Input<'a> = Vec<Line>;
+  type Output1 = usize;
+  type Output2 = usize;
+
+  fn parse(input: &str) -> Result<Self::Input<'_>> {
+    parse(input)
+  }
+
+  fn part1(input: Self::Input<'_>) -> Result<usize> {
+    p1(input)
+  }
+
+  fn part2(input: Self::Input<'_>) -> Result<usize> {
+    p2(input)
+  }
 }
 
 #[cfg(test)]
@@ -110,23 +139,20 @@ mod test {
     8,0 -> 0,8";
     assert_eq!(
       parse(input).unwrap(),
-      (
-        "",
-        vec![
-          Line {
-            x1: 0,
-            y1: 9,
-            x2: 5,
-            y2: 9
-          },
-          Line {
-            x1: 8,
-            y1: 0,
-            x2: 0,
-            y2: 8
-          }
-        ]
-      )
+      vec![
+        Line {
+          x1: 0,
+          y1: 9,
+          x2: 5,
+          y2: 9
+        },
+        Line {
+          x1: 8,
+          y1: 0,
+          x2: 0,
+          y2: 8
+        }
+      ]
     );
   }
 
@@ -142,12 +168,12 @@ mod test {
     3,4 -> 1,4
     0,0 -> 8,8
     5,5 -> 8,2";
-    let parsed = parse(input).unwrap().1;
-    assert_eq!(p1(parsed), 5);
+    let parsed = parse(input).unwrap();
+    assert_eq!(p1(parsed), Ok(5));
 
     let input = std::fs::read_to_string("./inputs/d05.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
-    assert_eq!(p1(parsed), 6666);
+    let parsed = parse(&input).unwrap();
+    assert_eq!(p1(parsed), Ok(6666));
   }
 
   #[test]
@@ -162,11 +188,11 @@ mod test {
     3,4 -> 1,4
     0,0 -> 8,8
     5,5 -> 8,2";
-    let parsed = parse(input).unwrap().1;
-    assert_eq!(p2(parsed), 12);
+    let parsed = parse(input).unwrap();
+    assert_eq!(p2(parsed), Ok(12));
 
     let input = std::fs::read_to_string("./inputs/d05.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
-    assert_eq!(p2(parsed), 19081);
+    let parsed = parse(&input).unwrap();
+    assert_eq!(p2(parsed), Ok(19081));
   }
 }
@@ -1,4 +1,6 @@
-use nom::IResult;
+use aoc_common::error::Result;
+use aoc_common::nom_prelude::parse_all;
+use aoc_common::solution::Solution;
 
 #[derive(Debug, Eq, PartialEq)]
 pub struct LanternFish(u64);
@@ -7,7 +9,7 @@ pub struct School([u64; 9]);
 
 impl School {
   pub fn from_fish(fishes: Vec<LanternFish>) -> Self {
-    let mut counter = crate::util::HashCounter::new();
+    let mut counter = aoc_common::util::HashCounter::new();
     for fish in fishes {
       counter.inc(fish.0);
     }
@@ -21,9 +23,7 @@ impl School {
   pub fn next_gen(&self) -> Self {
     let mut next_cohorts = [0; 9];
     let new_fish = self.0[0];
-    for i in 1..next_cohorts.len() {
-      next_cohorts[i - 1] = self.0[i];
-    }
+    next_cohorts[..8].copy_from_slice(&self.0[1..]);
     next_cohorts[6] += new_fish;
     next_cohorts[8] += new_fish;
     Self(next_cohorts)
@@ -34,72 +34,93 @@ impl School {
   }
 }
 
-pub fn parse(input: &str) -> IResult<&str, Vec<LanternFish>> {
-  nom::multi::separated_list1(
+pub fn parse(input: &str) -> Result<Vec<LanternFish>> {
+  parse_all(nom::multi::separated_list1(
     nom::bytes::complete::tag(","),
-    nom::combinator::map(crate::nom_prelude::parse_u32, |u| LanternFish(u as u64)),
-  )(input)
+    nom::combinator::map(aoc_common::nom_prelude::parse_u32, |u| {
+      LanternFish(u as u64)
+    }),
+  ))(input)
 }
 
-pub fn p1(xs: Vec<LanternFish>) -> u64 {
+pub fn p1(xs: Vec<LanternFish>) -> Result<u64> {
   let mut school = School::from_fish(xs);
   for _ in 0..80 {
     school = school.next_gen()
   }
-  school.total_fish()
+  Ok(school.total_fish())
 }
 
-pub fn p2(xs: Vec<LanternFish>) -> u64 {
+pub fn p2(xs: Vec<LanternFish>) -> Result<u64> {
   let mut school = School::from_fish(xs);
   for _ in 0..256 {
     school = school.next_gen()
   }
-  school.total_fish()
+  Ok(school.total_fish())
+}
+
+pub struct Day;
+
+impl Solution for Day {
+  const YEAR: u32 = 2021;
+  const DAY: u32 = 6;
+  type Input<'a> = Vec<LanternFish>;
+  type Output1 = u64;
+  type Output2 = u64;
+
+  fn parse(input: &str) -> Result<Self::Input<'_>> {
+    parse(input)
+  }
+
+  fn part1(input: Self::Input<'_>) -> Result<u64> {
+    p1(input)
+  }
+
+  fn part2(input: Self::Input<'_>) -> Result<u64> {
+    p2(input)
+  }
 }
 
 #[cfg(test)]
 mod test {
   use super::*;
 
-  const TEST_INPUT: &'static str = "3,4,3,1,2";
+  const TEST_INPUT: &str = "3,4,3,1,2";
 
   #[test]
   fn test_parse() {
     let input = TEST_INPUT;
     assert_eq!(
       parse(input).unwrap(),
-      (
-        "",
-        vec![
-          LanternFish(3),
-          LanternFish(4),
-          LanternFish(3),
-          LanternFish(1),
-          LanternFish(2),
-        ]
-      )
+      vec![
+        LanternFish(3),
+        LanternFish(4),
+        LanternFish(3),
+        LanternFish(1),
+        LanternFish(2),
+      ]
     )
   }
 
   #[test]
   fn test_p1() {
     let input = TEST_INPUT;
-    let parsed = parse(input).unwrap().1;
-    assert_eq!(p1(parsed), 5934);
+    let parsed = parse(input).unwrap();
+    assert_eq!(p1(parsed), Ok(5934));
 
     let input = std::fs::read_to_string("./inputs/d06.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
-    assert_eq!(p1(parsed), 350149);
+    let parsed = parse(&input).unwrap();
+    assert_eq!(p1(parsed), Ok(350149));
   }
 
   #[test]
   fn test_p2() {
     let input = TEST_INPUT;
-    let parsed = parse(input).unwrap().1;
-    assert_eq!(p2(parsed), 26984457539);
+    let parsed = parse(input).unwrap();
+    assert_eq!(p2(parsed), Ok(26984457539));
 
     let input = std::fs::read_to_string("./inputs/d06.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
-    assert_eq!(p2(parsed), 1590327954513);
+    let parsed = parse(&input).unwrap();
+    assert_eq!(p2(parsed), Ok(1590327954513));
   }
 }
@@ -1,7 +1,9 @@
-use crate::nom_prelude::*;
+use aoc_common::error::{Error, Result};
+use aoc_common::nom_prelude::*;
+use aoc_common::solution::Solution;
 use std::collections::HashSet;
 
-#[derive(Debug, Eq, PartialEq, Hash)]
+#[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
 pub enum Wire {
   A,
   B,
@@ -27,9 +29,9 @@ impl SevenSegment {
       7 => Some(8),
       6 => {
         // could be 0, 6, 9
-        if self.get_4().intersection(d).count() == 4 {
+        if self.get_4()?.intersection(d).count() == 4 {
           Some(9)
-        } else if self.get_1().intersection(d).count() == 2 {
+        } else if self.get_1()?.intersection(d).count() == 2 {
           Some(0)
         } else {
           Some(6)
@@ -37,50 +39,51 @@ impl SevenSegment {
       }
       5 => {
         // could be 2, 3, 5
-        if self.get_4().intersection(d).count() == 2 {
+        if self.get_4()?.intersection(d).count() == 2 {
           Some(2)
-        } else if self.get_1().intersection(d).count() == 2 {
+        } else if self.get_1()?.intersection(d).count() == 2 {
           Some(3)
         } else {
           Some(5)
         }
       }
-      _ => panic!("invalid digit"),
+      _ => None,
     }
   }
 
-  pub fn get_4(&self) -> &HashSet<Wire> {
-    self.wire_diagram.iter().find(|w| w.len() == 4).unwrap()
+  /// `None` if the diagram has no 4, and so can't tell some digits apart.
+  pub fn get_4(&self) -> Option<&HashSet<Wire>> {
+    self.wire_diagram.iter().find(|w| w.len() == 4)
   }
 
-  pub fn get_1(&self) -> &HashSet<Wire> {
-    self.wire_diagram.iter().find(|w| w.len() == 2).unwrap()
+  /// `None` if the diagram has no 1.
+  pub fn get_1(&self) -> Option<&HashSet<Wire>> {
+    self.wire_diagram.iter().find(|w| w.len() == 2)
   }
 }
 
 type InputItem = SevenSegment;
 
 fn parse_wires(input: &str) -> IResult<&str, HashSet<Wire>> {
-  map(
-    many1(map(one_of("abcdefg"), |c| match c {
-      'a' => Wire::A,
-      'b' => Wire::B,
-      'c' => Wire::C,
-      'd' => Wire::D,
-      'e' => Wire::E,
-      'f' => Wire::F,
-      'g' => Wire::G,
-      _ => panic!("must be one of the above"),
-    })),
-    |wires| wires.into_iter().collect::<HashSet<_>>(),
-  )(input)
+  let wire = alt((
+    value(Wire::A, char('a')),
+    value(Wire::B, char('b')),
+    value(Wire::C, char('c')),
+    value(Wire::D, char('d')),
+    value(Wire::E, char('e')),
+    value(Wire::F, char('f')),
+    value(Wire::G, char('g')),
+  ));
+  map(many1(wire), |wires| {
+    wires.into_iter().collect::<HashSet<_>>()
+  })(input)
 }
 
 pub fn parse_wires_list(input: &str) -> IResult<&str, Vec<HashSet<Wire>>> {
   many1(delimited(space0, parse_wires, space0))(input)
 }
 
-pub fn parse(input: &str) -> IResult<&str, Vec<InputItem>> {
+fn parse_input(input: &str) -> IResult<&str, Vec<InputItem>> {
   // It's silly that I have to wrap this in a closure.
   let seven_segment = map(
     separated_pair(
@@ -96,18 +99,24 @@ pub fn parse(input: &str) -> IResult<&str, Vec<InputItem>> {
   many1(delimited(multispace0, seven_segment, multispace0))(input)
 }
 
-pub fn p1(xs: Vec<InputItem>) -> usize {
-  xs.iter()
-    .map(|x| {
-      x.display
-        .iter()
-        .filter(|digit_display| matches!(digit_display.len(), 2 | 3 | 4 | 7))
-        .count()
-    })
-    .sum()
+pub fn parse(input: &str) -> Result<Vec<InputItem>> {
+  parse_all(parse_input)(input)
+}
+
+pub fn p1(xs: Vec<InputItem>) -> Result<usize> {
+  Ok(
+    xs.iter()
+      .map(|x| {
+        x.display
+          .iter()
+          .filter(|digit_display| matches!(digit_display.len(), 2 | 3 | 4 | 7))
+          .count()
+      })
+      .sum(),
+  )
 }
 
-pub fn p2(xs: Vec<InputItem>) -> u32 {
+pub fn p2(xs: Vec<InputItem>) -> Result<u32> {
   xs.iter()
     .map(|seven_segment| {
       seven_segment
@@ -115,21 +124,44 @@ pub fn p2(xs: Vec<InputItem>) -> u32 {
         .iter()
         .rev()
         .enumerate()
-        .fold(0, |acc, (i, d)| {
-          let p = 10_u32.pow(i as u32);
-          let d = seven_segment.digit(&d).unwrap();
-          let acc = acc + (d * p);
-          acc
+        .try_fold(0_u32, |acc, (i, d)| {
+          let d = seven_segment.digit(d).ok_or(Error::NoSolution)?;
+          10_u32
+            .checked_pow(i as u32)
+            .and_then(|p| acc.checked_add(d.checked_mul(p)?))
+            .ok_or(Error::NoSolution)
         })
     })
-    .sum::<u32>()
+    .sum()
+}
+
+pub struct Day;
+
+impl Solution for Day {
+  const YEAR: u32 = 2021;
+  const DAY: u32 = 8;
+  type Input<'a> = Vec<InputItem>;
+  type Output1 = usize;
+  type Output2 = u32;
+
+  fn parse(input: &str) -> Result<Self::Input<'_>> {
+    parse(input)
+  }
+
+  fn part1(input: Self::Input<'_>) -> Result<usize> {
+    p1(input)
+  }
+
+  fn part2(input: Self::Input<'_>) -> Result<u32> {
+    p2(input)
+  }
 }
 
 #[cfg(test)]
 mod test {
   use super::*;
 
-  const TEST_INPUT: &'static str =
+  const TEST_INPUT: &str =
     "be cfbegad cbdgef fgaecd cgeb fdcge agebfd fecdb fabcd edb | fdgacbe cefdb cefbgd gcbe
   edbfga begcd cbg gc gcadebf fbgde acbgfd abcde gfcbed gfec | fcgedb cgb dgebacf gc
   fgaebd cg bdaec gdafb agbcfd gdcbef bgcad gfac gcb cdgabef | cg cg fdcagb cbg
@@ -151,7 +183,7 @@ mod test {
     assert_eq!(output.len(), 3);
 
     let input = "ab cde | fgab";
-    let output = &parse(input).unwrap().1[0];
+    let output = &parse(input).unwrap()[0];
     assert_eq!(output.wire_diagram.len(), 2);
     assert_eq!(output.display.len(), 1);
     assert_eq!(output.wire_diagram[0].len(), 2);
@@ -165,22 +197,32 @@ mod test {
   #[test]
   fn test_p1() {
     let input = TEST_INPUT;
-    let parsed = parse(input).unwrap().1;
-    assert_eq!(p1(parsed), 26);
+    let parsed = parse(input).unwrap();
+    assert_eq!(p1(parsed), Ok(26));
 
     let input = std::fs::read_to_string("./inputs/d08.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
-    assert_eq!(p1(parsed), 479);
+    let parsed = parse(&input).unwrap();
+    assert_eq!(p1(parsed), Ok(479));
   }
 
   #[test]
   fn test_p2() {
     let input = TEST_INPUT;
-    let parsed = parse(input).unwrap().1;
-    assert_eq!(p2(parsed), 61229);
+    let parsed = parse(input).unwrap();
+    assert_eq!(p2(parsed), Ok(61229));
 
     let input = std::fs::read_to_string("./inputs/d08.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
-    assert_eq!(p2(parsed), 1041746);
+    let parsed = parse(&input).unwrap();
+    assert_eq!(p2(parsed), Ok(1041746));
+  }
+
+  #[test]
+  fn test_no_solution() {
+    // No 4 to tell the six-segment digits apart.
+    let parsed = parse("ab abc abcdefg | abcdef").unwrap();
+    assert_eq!(p2(parsed), Err(Error::NoSolution));
+    // Too many digits for a u32.
+    let parsed = parse("ab abcd | ab ab ab ab ab ab ab ab ab ab ab").unwrap();
+    assert_eq!(p2(parsed), Err(Error::NoSolution));
   }
 }
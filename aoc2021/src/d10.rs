@@ -1,88 +1,98 @@
-use crate::nom_prelude::*;
+use aoc_common::error::{Error, Result};
+use aoc_common::nom_prelude::*;
+use aoc_common::solution::Solution;
 
-pub fn parse(input: &str) -> IResult<&str, Vec<&str>> {
-  Ok(("", input.lines().collect()))
+const OPENERS: &str = "([{<";
+const CLOSERS: &str = ")]}>";
+
+pub fn parse(input: &str) -> Result<Vec<&str>> {
+  parse_all(lines_of(is_a("()[]{}<>")))(input)
+}
+
+/// A bracket kind, as its index into `OPENERS` and `CLOSERS`.
+type Kind = usize;
+
+enum Status {
+  /// The first closer that doesn't match its opener.
+  Corrupted(Kind),
+  /// The openers still waiting to be closed, innermost last.
+  Incomplete(Vec<Kind>),
 }
 
-fn score_error(c: char) -> u32 {
-  match c {
-    ')' => 3,
-    ']' => 57,
-    '}' => 1197,
-    '>' => 25137,
-    c => {
-      println!("non-matching character {:?}", c);
-      0
+fn check(line: &str) -> Status {
+  let mut stack = Vec::new();
+  for c in line.chars() {
+    if let Some(k) = OPENERS.find(c) {
+      stack.push(k);
+    } else if let Some(k) = CLOSERS.find(c) {
+      if stack.pop_if(|last| *last == k).is_none() {
+        return Status::Corrupted(k);
+      }
     }
   }
+  Status::Incomplete(stack)
 }
 
-pub fn p1(input: Vec<&str>) -> u32 {
-  input
-    .iter()
-    .map(|line| {
-      let mut stack = Vec::new();
-      for c in line.chars().filter(|c| "{}[]<>()".contains(*c)) {
-        match c {
-          '{' => stack.push('}'),
-          '[' => stack.push(']'),
-          '<' => stack.push('>'),
-          '(' => stack.push(')'),
-          c => match stack.last() {
-            Some(last) if c == *last => {
-              stack.pop();
-            }
-            _ => {
-              return score_error(c);
-            }
-          },
-        }
-      }
-      0
-    })
-    .sum::<u32>()
+pub fn p1(input: Vec<&str>) -> Result<u32> {
+  const SCORES: [u32; 4] = [3, 57, 1197, 25137];
+  Ok(
+    input
+      .iter()
+      .map(|line| match check(line) {
+        Status::Corrupted(k) => SCORES[k],
+        Status::Incomplete(_) => 0,
+      })
+      .sum(),
+  )
 }
 
-pub fn p2(input: Vec<&str>) -> u64 {
+pub fn p2(input: Vec<&str>) -> Result<u64> {
   let mut scores = input
     .iter()
-    .filter_map(|line| {
-      let mut stack = Vec::new();
-      for c in line.chars().filter(|c| "{}[]<>()".contains(*c)) {
-        match c {
-          '{' => stack.push('}'),
-          '[' => stack.push(']'),
-          '<' => stack.push('>'),
-          '(' => stack.push(')'),
-          c => match stack.last() {
-            Some(last) if c == *last => {
-              stack.pop();
-            }
-            _ => return None,
-          },
-        }
-      }
-      Some(stack.into_iter().rev().fold(0_u64, |acc, c| {
-        (acc * 5)
-          + match c {
-            ')' => 1,
-            ']' => 2,
-            '}' => 3,
-            '>' => 4,
-            _ => panic!("non matching character"),
-          }
-      }))
+    .filter_map(|line| match check(line) {
+      Status::Corrupted(_) => None,
+      Status::Incomplete(stack) => Some(
+        stack
+          .into_iter()
+          .rev()
+          .fold(0_u64, |acc, k| acc * 5 + k as u64 + 1),
+      ),
     })
     .collect::<Vec<_>>();
   scores.sort();
-  scores[scores.len() / 2]
+  scores
+    .get(scores.len() / 2)
+    .copied()
+    .ok_or(Error::NoSolution)
+}
+
+pub struct Day;
+
+impl Solution for Day {
+  const YEAR: u32 = 2021;
+  const DAY: u32 = 10;
+  type Input<'a> = Vec<&'a str>;
+  type Output1 = u32;
+  type Output2 = u64;
+
+  fn parse(input: &str) -> Result<Self::Input<'_>> {
+    parse(input)
+  }
+
+  fn part1(input: Self::Input<'_>) -> Result<u32> {
+    p1(input)
+  }
+
+  fn part2(input: Self::Input<'_>) -> Result<u64> {
+    p2(input)
+  }
 }
 
 #[cfg(test)]
 mod test {
   use super::*;
 
-  const TEST_INPUT: &'static str = r#"[({(<(())[]>[[{[]{<()<>>
+  const TEST_INPUT: &str = r#"[({(<(())[]>[[{[]{<()<>>
     [(()[<>])]({[<{<<[]>>(
     {([(<{}[<>[]}>{[]{[(<()>
     (((({<>}<{<{<>}{[]{[]{}
@@ -96,28 +106,47 @@ mod test {
   #[test]
   fn test_parse() {
     let input = TEST_INPUT;
-    assert_eq!(parse(input).unwrap().1.len(), 10)
+    assert_eq!(parse(input).unwrap().len(), 10)
   }
 
   #[test]
   fn test_p1() {
     let input = TEST_INPUT;
-    let parsed = parse(input).unwrap().1;
-    assert_eq!(p1(parsed), 26397);
+    let parsed = parse(input).unwrap();
+    assert_eq!(p1(parsed), Ok(26397));
 
     let input = std::fs::read_to_string("./inputs/d10.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
-    assert_eq!(p1(parsed), 266301);
+    let parsed = parse(&input).unwrap();
+    assert_eq!(p1(parsed), Ok(266301));
   }
 
   #[test]
   fn test_p2() {
     let input = TEST_INPUT;
-    let parsed = parse(input).unwrap().1;
-    assert_eq!(p2(parsed), 288957);
+    let parsed = parse(input).unwrap();
+    assert_eq!(p2(parsed), Ok(288957));
 
     let input = std::fs::read_to_string("./inputs/d10.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
-    assert_eq!(p2(parsed), 3404870164);
+    let parsed = parse(&input).unwrap();
+    assert_eq!(p2(parsed), Ok(3404870164));
+  }
+
+  #[test]
+  fn test_no_solution() {
+    let parsed = parse("{([(<{}[<>[]}>{[]{[(<()>").unwrap();
+    assert_eq!(p2(parsed), Err(Error::NoSolution));
+    assert_eq!(p2(vec![]), Err(Error::NoSolution));
+  }
+
+  #[test]
+  fn test_parse_error() {
+    assert_eq!(
+      parse("[]\n  (x)"),
+      Err(Error::TrailingInput {
+        line: 2,
+        column: 4,
+        snippet: "x)".to_owned()
+      })
+    );
   }
 }
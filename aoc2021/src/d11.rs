@@ -1,116 +1,104 @@
-use itertools::Itertools;
-
-use crate::nom_prelude::*;
-
-type Coord = (i8, i8);
+use aoc_common::error::{Error, Result};
+use aoc_common::grid::{char_grid, Coord, Grid};
+use aoc_common::nom_prelude::*;
+use aoc_common::solution::Solution;
+use std::collections::HashSet;
 
 #[derive(Debug, Eq, PartialEq)]
-pub struct OctoGrid([u8; 100]);
+pub struct OctoGrid(Grid<u8>);
 
 impl OctoGrid {
-  fn from_vec(v: Vec<u8>) -> Result<Self, ()> {
-    let arr: [u8; 100] = v.try_into().map_err(|_| ())?;
-    Ok(Self(arr))
-  }
-
-  pub fn at(&self, (x, y): Coord) -> Option<u8> {
-    if 0 <= x && x < 10 && 0 <= y && y < 10 {
-      Some(self.0[(y as usize * 10) + x as usize])
-    } else {
-      None
-    }
-  }
-
-  fn reset(&mut self, (x, y): Coord) {
-    if 0 <= x && x < 10 && 0 <= y && y < 10 {
-      self.0[(y as usize * 10) + x as usize] = 0;
-    }
+  pub fn at(&self, c: Coord) -> Option<u8> {
+    self.0.get(c).copied()
   }
 
   /// Returns true if "flashed".
-  fn inc(&mut self, (x, y): Coord) -> bool {
-    let mut flashed = false;
-    if 0 <= x && x < 10 && 0 <= y && y < 10 {
-      self.0[(y as usize * 10) + x as usize] += 1;
-      if self.0[(y as usize * 10) + x as usize] == 10 {
-        flashed = true;
-      }
-    }
-    flashed
-  }
-
-  fn neighbors((x, y): Coord) -> impl Iterator<Item = Coord> {
-    [
-      (x - 1, y - 1),
-      (x, y - 1),
-      (x + 1, y - 1),
-      (x + 1, y),
-      (x + 1, y + 1),
-      (x, y + 1),
-      (x - 1, y + 1),
-      (x - 1, y),
-    ]
-    .into_iter()
-  }
-
-  fn coords() -> impl Iterator<Item = Coord> {
-    (0..10).cartesian_product(0..10)
+  fn inc(&mut self, c: Coord) -> bool {
+    let energy = &mut self.0[c];
+    *energy += 1;
+    *energy == 10
   }
 
   /// Returns number of flashes
   fn step(&mut self) -> usize {
     let mut flashed = Vec::new();
-    for c in Self::coords() {
+    for c in self.0.coords() {
       // I wanted to do this recursively, but this is easier.
       let mut to_inc = vec![c];
-      while !to_inc.is_empty() {
-        let c = to_inc.pop().unwrap();
+      while let Some(c) = to_inc.pop() {
         if self.inc(c) {
-          to_inc.extend(Self::neighbors(c));
+          to_inc.extend(self.0.neighbors8(c));
           flashed.push(c);
         }
       }
     }
 
     for c in flashed.iter().cloned() {
-      self.reset(c);
+      self.0[c] = 0;
     }
     flashed.len()
   }
 }
 
-pub fn parse(input: &str) -> IResult<&str, OctoGrid> {
-  let entry = many1(delimited(
-    multispace0,
-    map_parser(take(1_u32), parse_u8),
-    multispace0,
-  ));
-  map_res(entry, OctoGrid::from_vec)(input)
+fn parse_input(input: &str) -> IResult<&str, OctoGrid> {
+  let energy = |c: char| c.to_digit(10).map(|d| d as u8);
+  map(char_grid(energy), OctoGrid)(input)
 }
 
-pub fn p1(mut g: OctoGrid) -> usize {
+pub fn parse(input: &str) -> Result<OctoGrid> {
+  parse_all(parse_input)(input)
+}
+
+pub fn p1(mut g: OctoGrid) -> Result<usize> {
   let mut sum = 0;
   for _ in 0..100 {
     sum += g.step();
   }
-  sum
+  Ok(sum)
 }
 
-pub fn p2(mut g: OctoGrid) -> usize {
+/// `NoSolution` if the octopuses get back to an earlier state without all flashing together, as they then never will.
+pub fn p2(mut g: OctoGrid) -> Result<usize> {
+  let mut seen = HashSet::new();
   let mut i = 0;
   loop {
     i += 1;
-    if g.step() == 100 {
-      return i;
+    if g.step() == g.0.len() {
+      return Ok(i);
+    }
+    if !seen.insert(g.0.iter().copied().collect::<Vec<_>>()) {
+      return Err(Error::NoSolution);
     }
   }
 }
 
+pub struct Day;
+
+impl Solution for Day {
+  const YEAR: u32 = 2021;
+  const DAY: u32 = 11;
+  type Input<'a> = OctoGrid;
+  type Output1 = usize;
+  type Output2 = usize;
+
+  fn parse(input: &str) -> Result<Self::Input<'_>> {
+    parse(input)
+  }
+
+  fn part1(input: Self::Input<'_>) -> Result<usize> {
+    p1(input)
+  }
+
+  fn part2(input: Self::Input<'_>) -> Result<usize> {
+    p2(input)
+  }
+}
+
 #[cfg(test)]
 mod test {
   use super::*;
 
-  const TEST_INPUT: &'static str = "5483143223
+  const TEST_INPUT: &str = "5483143223
   2745854711
   5264556173
   6141336146
@@ -124,7 +112,7 @@ mod test {
   #[test]
   fn test_parse() {
     let input = TEST_INPUT;
-    let parsed = parse(input).unwrap().1;
+    let parsed = parse(input).unwrap();
     assert_eq!(parsed.at((0, 0)), Some(5));
     assert_eq!(parsed.at((9, 0)), Some(3));
     assert_eq!(parsed.at((9, 9)), Some(6));
@@ -133,7 +121,7 @@ mod test {
   #[test]
   fn test_step() {
     let input = TEST_INPUT;
-    let mut parsed = parse(input).unwrap().1;
+    let mut parsed = parse(input).unwrap();
 
     let after_step_1 = parse(
       "6594254334
@@ -147,8 +135,7 @@ mod test {
     5957959665
     6394862637",
     )
-    .unwrap()
-    .1;
+    .unwrap();
     parsed.step();
     assert_eq!(parsed, after_step_1);
 
@@ -164,8 +151,7 @@ mod test {
     9000000876
     8700006848",
     )
-    .unwrap()
-    .1;
+    .unwrap();
     parsed.step();
     assert_eq!(parsed, after_step_2);
   }
@@ -173,22 +159,29 @@ mod test {
   #[test]
   fn test_p1() {
     let input = TEST_INPUT;
-    let parsed = parse(input).unwrap().1;
-    assert_eq!(p1(parsed), 1656);
+    let parsed = parse(input).unwrap();
+    assert_eq!(p1(parsed), Ok(1656));
 
     let input = std::fs::read_to_string("./inputs/d11.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
-    assert_eq!(p1(parsed), 1585);
+    let parsed = parse(&input).unwrap();
+    assert_eq!(p1(parsed), Ok(1585));
   }
 
   #[test]
   fn test_p2() {
     let input = TEST_INPUT;
-    let parsed = parse(input).unwrap().1;
-    assert_eq!(p2(parsed), 195);
+    let parsed = parse(input).unwrap();
+    assert_eq!(p2(parsed), Ok(195));
 
     let input = std::fs::read_to_string("./inputs/d11.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
-    assert_eq!(p2(parsed), 382);
+    let parsed = parse(&input).unwrap();
+    assert_eq!(p2(parsed), Ok(382));
+  }
+
+  #[test]
+  fn test_no_solution() {
+    assert_eq!(p2(parse("0").unwrap()), Ok(10));
+    // These never all flash on the same step.
+    assert_eq!(p2(parse("002").unwrap()), Err(Error::NoSolution));
   }
 }
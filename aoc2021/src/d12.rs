@@ -1,7 +1,9 @@
 use std::collections::{HashMap, HashSet};
 use itertools::Itertools;
 
-use crate::nom_prelude::*;
+use aoc_common::error::{Error, Result};
+use aoc_common::nom_prelude::*;
+use aoc_common::solution::Solution;
 
 #[derive(Debug, Eq, PartialEq, Hash, Copy, Clone)]
 pub enum Location<'a> {
@@ -21,6 +23,7 @@ type Connection<'a> = (Location<'a>, Location<'a>);
 
 type Path<'a> = Vec<Location<'a>>;
 
+#[derive(Default)]
 pub struct Cavern<'a> {
   locations: HashMap<Location<'a>, HashSet<Location<'a>>>,
 }
@@ -46,6 +49,12 @@ impl<'a> Cavern<'a> {
     c
   }
 
+  /// Two large caves next to each other could be walked between forever.
+  pub fn has_endless_paths(&self) -> bool {
+    self.locations.iter().any(|(from, tos)| from.can_revisit_repeatedly() && tos.iter().any(Location::can_revisit_repeatedly))
+  }
+
+  /// No paths at all if there is no start.
   pub fn paths(&self, loc_test: fn(&Path, &Location) -> bool) -> Vec<Path<'a>> {
     self.path_step(vec![Location::Start], loc_test)
   }
@@ -54,10 +63,10 @@ impl<'a> Cavern<'a> {
     match path_so_far.last() {
       Some(Location::End) => vec![path_so_far],
       Some(loc) => {
-        self.locations.get(loc).unwrap().iter().filter(|loc| loc_test(&path_so_far, loc)).flat_map(|loc| {
+        self.locations.get(loc).into_iter().flatten().filter(|loc| loc_test(&path_so_far, loc)).flat_map(|loc| {
           // Sigh, but it's easy.
           let mut new_path_so_far = path_so_far.clone();
-          new_path_so_far.push(loc.clone());
+          new_path_so_far.push(*loc);
           self.path_step(new_path_so_far, loc_test)
         }).collect()
       }
@@ -66,27 +75,34 @@ impl<'a> Cavern<'a> {
   }
 }
 
-pub fn parse_location(input: &str) -> IResult<&str, Location> {
+pub fn parse_location(input: &str) -> IResult<&str, Location<'_>> {
   map(alpha1, |s| match s {
     "start" => Location::Start,
     "end" => Location::End,
-    s_prime if &s_prime.to_lowercase() == s => Location::Small(s),
+    s_prime if s_prime.to_lowercase() == s => Location::Small(s),
     s => Location::Large(s),
   })(input)
 }
 
-pub fn parse(input: &str) -> IResult<&str, Vec<Connection>> {
+fn parse_input(input: &str) -> IResult<&str, Vec<Connection<'_>>> {
   let connection = separated_pair(parse_location, tag("-"), parse_location);
   many1(delimited(multispace0, connection, multispace0))(input)
 }
 
+pub fn parse(input: &str) -> Result<Vec<Connection<'_>>> {
+  parse_all(parse_input)(input)
+}
+
 fn p1_loc_test(path_so_far: &Path, loc: &Location) -> bool {
   loc.can_revisit_repeatedly() || !path_so_far.contains(loc)
 }
 
-pub fn p1(conns: Vec<Connection>) -> usize {
+pub fn p1(conns: Vec<Connection>) -> Result<usize> {
   let c = Cavern::from_connections(conns);
-  c.paths(p1_loc_test).iter().filter(|p| matches!(p.last(), Some(Location::End))).count()
+  if c.has_endless_paths() {
+    return Err(Error::NoSolution);
+  }
+  Ok(c.paths(p1_loc_test).iter().filter(|p| matches!(p.last(), Some(Location::End))).count())
 }
 
 fn p2_loc_test(path_so_far: &Path, loc: &Location) -> bool {
@@ -100,16 +116,41 @@ fn p2_loc_test(path_so_far: &Path, loc: &Location) -> bool {
 }
 
 // lol, I'm doing this in a pretty slow manner.
-pub fn p2(conns: Vec<Connection>) -> usize {
+pub fn p2(conns: Vec<Connection>) -> Result<usize> {
   let c = Cavern::from_connections(conns);
-  c.paths(p2_loc_test).iter().filter(|p| matches!(p.last(), Some(Location::End))).count()
+  if c.has_endless_paths() {
+    return Err(Error::NoSolution);
+  }
+  Ok(c.paths(p2_loc_test).iter().filter(|p| matches!(p.last(), Some(Location::End))).count())
+}
+
+pub struct Day;
+
+impl Solution for Day {
+  const YEAR: u32 = 2021;
+  const DAY: u32 = 12;
+  type Input<'a> = Vec<Connection<'a>>;
+  type Output1 = usize;
+  type Output2 = usize;
+
+  fn parse(input: &str) -> Result<Self::Input<'_>> {
+    parse(input)
+  }
+
+  fn part1(input: Self::Input<'_>) -> Result<usize> {
+    p1(input)
+  }
+
+  fn part2(input: Self::Input<'_>) -> Result<usize> {
+    p2(input)
+  }
 }
 
 #[cfg(test)]
 mod test {
   use super::*;
 
-  const TEST_INPUT: &'static str = "fs-end
+  const TEST_INPUT: &str = "fs-end
   he-DX
   fs-he
   start-DX
@@ -133,7 +174,7 @@ mod test {
     let input = "start-A
     b-end";
     assert_eq!(
-      parse(input).unwrap().1,
+      parse(input).unwrap(),
       vec![
         (Location::Start, Location::Large("A")),
         (Location::Small("b"), Location::End),
@@ -144,22 +185,30 @@ mod test {
   #[test]
   fn test_p1() {
     let input = TEST_INPUT;
-    let parsed = parse(input).unwrap().1;
-    assert_eq!(p1(parsed), 226);
+    let parsed = parse(input).unwrap();
+    assert_eq!(p1(parsed), Ok(226));
 
     let input = std::fs::read_to_string("./inputs/d12.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
-    assert_eq!(p1(parsed), 4104);
+    let parsed = parse(&input).unwrap();
+    assert_eq!(p1(parsed), Ok(4104));
   }
 
   #[test]
   fn test_p2() {
     let input = TEST_INPUT;
-    let parsed = parse(input).unwrap().1;
-    assert_eq!(p2(parsed), 3509);
+    let parsed = parse(input).unwrap();
+    assert_eq!(p2(parsed), Ok(3509));
 
     let input = std::fs::read_to_string("./inputs/d12.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
-    assert_eq!(p2(parsed), 119760);
+    let parsed = parse(&input).unwrap();
+    assert_eq!(p2(parsed), Ok(119760));
+  }
+
+  #[test]
+  fn test_no_solution() {
+    assert_eq!(p1(parse("A-b").unwrap()), Ok(0));
+    assert_eq!(p2(parse("A-b").unwrap()), Ok(0));
+    assert_eq!(p1(parse("start-A\nA-B\nB-end").unwrap()), Err(Error::NoSolution));
+    assert_eq!(p2(parse("start-A\nA-A\nA-end").unwrap()), Err(Error::NoSolution));
   }
 }
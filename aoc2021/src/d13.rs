@@ -1,6 +1,8 @@
 //! https://adventofcode.com/2021/day/13
 
-use crate::nom_prelude::*;
+use aoc_common::error::{Error, Result};
+use aoc_common::nom_prelude::*;
+use aoc_common::solution::Solution;
 use std::collections::HashSet;
 
 type Point = (i32, i32);
@@ -21,17 +23,18 @@ pub struct DisplayGrid(HashSet<Point>);
 
 impl std::fmt::Display for DisplayGrid {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-    let max_x = self.0.iter().map(|(x, _)| x).max().unwrap();
-    let max_y = self.0.iter().map(|(_, y)| y).max().unwrap();
-    for y in 0..=(*max_y) {
-      for x in 0..=(*max_x) {
+    // Nothing to show without any points.
+    let max_x = self.0.iter().map(|(x, _)| *x).max().unwrap_or(-1);
+    let max_y = self.0.iter().map(|(_, y)| *y).max().unwrap_or(-1);
+    for y in 0..=max_y {
+      for x in 0..=max_x {
         if self.0.contains(&(x, y)) {
           write!(f, "#")?;
         } else {
           write!(f, ".")?;
         }
       }
-      write!(f, "\n")?;
+      writeln!(f)?;
     }
     Ok(())
   }
@@ -47,41 +50,69 @@ fn fold_points(pts: HashSet<Point>, fold_instruction: FoldInstruction) -> HashSe
     .collect()
 }
 
-pub fn parse(input: &str) -> IResult<&str, Instructions> {
+fn parse_input(input: &str) -> IResult<&str, Instructions> {
   let point = separated_pair(parse_i32, tag(","), parse_i32);
-  let fold_instruction = map(
-    tuple((alt((tag("fold along x="), tag("fold along y="))), parse_i32)),
-    |(s, u)| match s {
-      "fold along x=" => FoldInstruction::Left(u),
-      "fold along y=" => FoldInstruction::Up(u),
-      _ => panic!("incorrect tag"),
-    },
-  );
+  let fold_instruction = alt((
+    map(
+      preceded(tag("fold along x="), parse_i32),
+      FoldInstruction::Left,
+    ),
+    map(
+      preceded(tag("fold along y="), parse_i32),
+      FoldInstruction::Up,
+    ),
+  ));
   map(
     tuple((lines_of(point), lines_of(fold_instruction))),
     |(points, folds)| Instructions { points, folds },
   )(input)
 }
 
-pub fn p1(instructions: Instructions) -> usize {
+pub fn parse(input: &str) -> Result<Instructions> {
+  parse_all(parse_input)(input)
+}
+
+pub fn p1(instructions: Instructions) -> Result<usize> {
   let points = instructions.points.into_iter().collect();
-  let instruction = instructions.folds.first().unwrap();
-  fold_points(points, *instruction).len()
+  let instruction = instructions.folds.first().ok_or(Error::NoSolution)?;
+  Ok(fold_points(points, *instruction).len())
 }
 
-pub fn p2(instructions: Instructions) -> String {
+pub fn p2(instructions: Instructions) -> Result<String> {
   let mut points = instructions.points.into_iter().collect();
   for fold in instructions.folds {
     points = fold_points(points, fold);
   }
-  format!("{}", DisplayGrid(points))
+  Ok(format!("{}", DisplayGrid(points)))
+}
+
+pub struct Day;
+
+impl Solution for Day {
+  const YEAR: u32 = 2021;
+  const DAY: u32 = 13;
+  type Input<'a> = Instructions;
+  type Output1 = usize;
+  type Output2 = String;
+
+  fn parse(input: &str) -> Result<Self::Input<'_>> {
+    parse(input)
+  }
+
+  fn part1(input: Self::Input<'_>) -> Result<usize> {
+    p1(input)
+  }
+
+  fn part2(input: Self::Input<'_>) -> Result<String> {
+    p2(input)
+  }
 }
 
 #[cfg(test)]
 mod test {
   use super::*;
 
-  const TEST_INPUT: &'static str = "6,10
+  const TEST_INPUT: &str = "6,10
   0,14
   9,10
   0,3
@@ -112,33 +143,30 @@ mod test {
     fold along x=2";
     assert_eq!(
       parse(input).unwrap(),
-      (
-        "",
-        Instructions {
-          points: vec![(6, 10), (0, 14)],
-          folds: vec![FoldInstruction::Up(7), FoldInstruction::Left(2)],
-        }
-      )
+      Instructions {
+        points: vec![(6, 10), (0, 14)],
+        folds: vec![FoldInstruction::Up(7), FoldInstruction::Left(2)],
+      }
     )
   }
 
   #[test]
   fn test_p1() {
     let input = TEST_INPUT;
-    let parsed = parse(input).unwrap().1;
-    assert_eq!(p1(parsed), 17);
+    let parsed = parse(input).unwrap();
+    assert_eq!(p1(parsed), Ok(17));
 
     let input = std::fs::read_to_string("./inputs/d13.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
-    assert_eq!(p1(parsed), 638);
+    let parsed = parse(&input).unwrap();
+    assert_eq!(p1(parsed), Ok(638));
   }
 
   #[test]
   fn test_p2() {
     let input = TEST_INPUT;
-    let parsed = parse(input).unwrap().1;
+    let parsed = parse(input).unwrap();
     assert_eq!(
-      p2(parsed),
+      p2(parsed).unwrap(),
       "#####
 #...#
 #...#
@@ -148,9 +176,9 @@ mod test {
     );
 
     let input = std::fs::read_to_string("./inputs/d13.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
+    let parsed = parse(&input).unwrap();
     assert_eq!(
-      p2(parsed),
+      p2(parsed).unwrap(),
       ".##....##..##..#..#.###...##..###..###.
 #..#....#.#..#.#.#..#..#.#..#.#..#.#..#
 #.......#.#....##...###..#..#.#..#.###.
@@ -159,4 +187,14 @@ mod test {
 .##...##...##..#..#.###..#..#.#....###.\n"
     );
   }
+
+  #[test]
+  fn test_no_solution() {
+    let unfolded = Instructions {
+      points: vec![(1, 1)],
+      folds: vec![],
+    };
+    assert_eq!(p1(unfolded), Err(Error::NoSolution));
+    assert_eq!(DisplayGrid(HashSet::new()).to_string(), "");
+  }
 }
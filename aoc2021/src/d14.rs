@@ -1,6 +1,8 @@
 //! https://adventofcode.com/2021/day/14
 
-use crate::nom_prelude::*;
+use aoc_common::error::{Error, Result};
+use aoc_common::nom_prelude::*;
+use aoc_common::solution::Solution;
 use itertools::Itertools;
 use std::collections::HashMap;
 
@@ -24,7 +26,7 @@ impl Polymer {
         .cloned()
         .map(Some)
         .interleave(new_bases)
-        .filter_map(|e| e)
+        .flatten()
         .collect::<Vec<_>>(),
     )
   }
@@ -82,16 +84,18 @@ impl BetterPolymer {
   }
 }
 
-impl From<Polymer> for BetterPolymer {
-  fn from(p: Polymer) -> Self {
-    let start = p.0.first().copied().unwrap();
-    let end = p.0.last().copied().unwrap();
+/// `NoSolution` for an empty polymer, which has no ends.
+impl TryFrom<Polymer> for BetterPolymer {
+  type Error = Error;
+  fn try_from(p: Polymer) -> Result<Self> {
+    let start = p.0.first().copied().ok_or(Error::NoSolution)?;
+    let end = p.0.last().copied().ok_or(Error::NoSolution)?;
     let mut pairs = HashMap::new();
     for k in p.0.iter().copied().tuple_windows() {
       let e = pairs.entry(k).or_insert(0);
       *e += 1;
     }
-    Self { pairs, start, end }
+    Ok(Self { pairs, start, end })
   }
 }
 
@@ -100,7 +104,7 @@ pub fn parse_base(input: &str) -> IResult<&str, Base> {
   one_of("ABCDEFGHIJKLMNOPQRSTUVWXYZ")(input)
 }
 
-pub fn parse(input: &str) -> IResult<&str, Input> {
+fn parse_input(input: &str) -> IResult<&str, Input> {
   let polymer = map(many1(parse_base), Polymer);
   let rule = separated_pair(tuple((parse_base, parse_base)), tag(" -> "), parse_base);
   let rules = lines_of(rule);
@@ -110,17 +114,21 @@ pub fn parse(input: &str) -> IResult<&str, Input> {
   )(input)
 }
 
-pub fn p1((mut poly, rules): Input) -> usize {
+pub fn parse(input: &str) -> Result<Input> {
+  parse_all(parse_input)(input)
+}
+
+pub fn p1((mut poly, rules): Input) -> Result<usize> {
   for _ in 0..10 {
     poly = poly.step(&rules);
   }
   let counts = poly.0.into_iter().counts();
-  let max = counts.iter().map(|(_, c)| c).max().unwrap();
-  let min = counts.iter().map(|(_, c)| c).min().unwrap();
-  max - min
+  let max = counts.values().max().ok_or(Error::NoSolution)?;
+  let min = counts.values().min().ok_or(Error::NoSolution)?;
+  Ok(max - min)
 }
 
-pub fn aborted_p2((mut poly, rules): Input) -> usize {
+pub fn aborted_p2((mut poly, rules): Input) -> Result<usize> {
   // Too large for brute force solution;  need to find a way to simplify the problem.
 
   // This is not the general answer, but we can solve it for each pair after 20 iterations, memoize the results, then use the memoized results
@@ -145,36 +153,28 @@ pub fn aborted_p2((mut poly, rules): Input) -> usize {
     .0
     .into_iter()
     .tuple_windows()
-    .fold(HashMap::new(), |acc, (a, b)| {
-      let c = memos.get(&(a, b)).unwrap();
-      add_hash_maps(&acc, c)
+    .fold(HashMap::new(), |acc, (a, b)| match memos.get(&(a, b)) {
+      Some(c) => add_hash_maps(&acc, c),
+      None => acc,
     });
 
-  for (k, v) in final_counts.iter() {
-    println!("{:?}: {:?}", k, v);
-  }
-
-  let max = final_counts.iter().map(|(_, c)| c).max().unwrap();
-  let min = final_counts.iter().map(|(_, c)| c).min().unwrap();
-  println!("max {:?}", max);
-  println!("min {:?}", min);
-  max - min
+  let max = final_counts.values().max().ok_or(Error::NoSolution)?;
+  let min = final_counts.values().min().ok_or(Error::NoSolution)?;
+  Ok(max - min)
 
   // This solution is still orders of magnitude slower than what I think an optimal solution would be.
 }
 
-pub fn p2((poly, rules): Input) -> usize {
+pub fn p2((poly, rules): Input) -> Result<usize> {
   // duh, the answer is to memoize how many of each _pair_ you get after each generation.
-  let mut poly = BetterPolymer::from(poly);
+  let mut poly = BetterPolymer::try_from(poly)?;
   for _ in 0..40 {
     poly = poly.step(&rules);
   }
   let final_counts = poly.letter_counts();
-  let max = final_counts.iter().map(|(_, c)| c).max().unwrap();
-  let min = final_counts.iter().map(|(_, c)| c).min().unwrap();
-  println!("max {:?}", max);
-  println!("min {:?}", min);
-  max - min
+  let max = final_counts.values().max().ok_or(Error::NoSolution)?;
+  let min = final_counts.values().min().ok_or(Error::NoSolution)?;
+  Ok(max - min)
 }
 
 fn add_hash_maps(m1: &HashMap<Base, usize>, m2: &HashMap<Base, usize>) -> HashMap<Base, usize> {
@@ -186,11 +186,33 @@ fn add_hash_maps(m1: &HashMap<Base, usize>, m2: &HashMap<Base, usize>) -> HashMa
   new_m
 }
 
+pub struct Day;
+
+impl Solution for Day {
+  const YEAR: u32 = 2021;
+  const DAY: u32 = 14;
+  type Input<'a> = Input;
+  type Output1 = usize;
+  type Output2 = usize;
+
+  fn parse(input: &str) -> Result<Self::Input<'_>> {
+    parse(input)
+  }
+
+  fn part1(input: Self::Input<'_>) -> Result<usize> {
+    p1(input)
+  }
+
+  fn part2(input: Self::Input<'_>) -> Result<usize> {
+    p2(input)
+  }
+}
+
 #[cfg(test)]
 mod test {
   use super::*;
 
-  const TEST_INPUT: &'static str = "NNCB
+  const TEST_INPUT: &str = "NNCB
 
   CH -> B
   HH -> N
@@ -212,7 +234,7 @@ mod test {
   #[test]
   fn test_parse() {
     let input = TEST_INPUT;
-    let parsed = parse(input).unwrap().1;
+    let parsed = parse(input).unwrap();
     assert_eq!(parsed.0, Polymer(vec!['N', 'N', 'C', 'B']));
     assert_eq!(parsed.1.get(&('C', 'H')), Some(&'B'));
     assert_eq!(parsed.1.get(&('C', 'N')), Some(&'C'))
@@ -221,22 +243,35 @@ mod test {
   #[test]
   fn test_p1() {
     let input = TEST_INPUT;
-    let parsed = parse(input).unwrap().1;
-    assert_eq!(p1(parsed), 1588);
+    let parsed = parse(input).unwrap();
+    assert_eq!(p1(parsed), Ok(1588));
 
     let input = std::fs::read_to_string("./inputs/d14.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
-    assert_eq!(p1(parsed), 2975);
+    let parsed = parse(&input).unwrap();
+    assert_eq!(p1(parsed), Ok(2975));
   }
 
   #[test]
   fn test_p2() {
     let input = TEST_INPUT;
-    let parsed = parse(input).unwrap().1;
-    assert_eq!(p2(parsed), 2188189693529);
+    let parsed = parse(input).unwrap();
+    assert_eq!(p2(parsed), Ok(2188189693529));
 
     let input = std::fs::read_to_string("./inputs/d14.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
-    assert_eq!(p2(parsed), 3015383850689);
+    let parsed = parse(&input).unwrap();
+    assert_eq!(p2(parsed), Ok(3015383850689));
+  }
+
+  #[test]
+  fn test_no_solution() {
+    assert_eq!(
+      p1((Polymer(vec![]), HashMap::new())),
+      Err(Error::NoSolution)
+    );
+    assert_eq!(
+      p2((Polymer(vec![]), HashMap::new())),
+      Err(Error::NoSolution)
+    );
+    assert_eq!(p2(parse("N\n\nAB -> C").unwrap()), Ok(0));
   }
 }
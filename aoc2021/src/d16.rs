@@ -1,6 +1,9 @@
 //! https://adventofcode.com/2021/day/16
 
-use crate::nom_prelude::*;
+use aoc_common::error::{Error, Result};
+use aoc_common::nom_prelude::*;
+use aoc_common::solution::Solution;
+use std::io::{BufReader, Bytes, Read};
 use Bit::*;
 
 #[derive(Debug, Eq, PartialEq, Clone, Copy)]
@@ -21,18 +24,26 @@ impl Bit {
   pub fn number_from_bits(bits: &[Bit]) -> u64 {
     let mut num = 0;
     for (offset, _) in bits.iter().rev().enumerate().filter(|(_, bit)| **bit == I) {
-      num = num | 1 << offset
+      num |= 1 << offset
     }
     num
   }
+
+  /// The low `width` bits of `n`, most significant first.
+  pub fn push_number(bits: &mut Vec<Bit>, n: u64, width: usize) {
+    bits.extend(
+      (0..width)
+        .rev()
+        .map(|offset| if n >> offset & 1 == 1 { I } else { O }),
+    );
+  }
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Eq, PartialEq, Clone)]
 pub enum Packet {
-  // TODO: make number a number if we know they will always be small enough
   Literal {
     version: u8,
-    number: Vec<Bit>,
+    number: u64,
   },
   Operation {
     version: u8,
@@ -41,7 +52,7 @@ pub enum Packet {
   },
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
 pub enum TypeId {
   Add,
   Mul,
@@ -54,7 +65,7 @@ pub enum TypeId {
 
 impl TryFrom<u8> for TypeId {
   type Error = ();
-  fn try_from(u: u8) -> Result<Self, Self::Error> {
+  fn try_from(u: u8) -> std::result::Result<Self, Self::Error> {
     match u {
       0 => Ok(Self::Add),
       1 => Ok(Self::Mul),
@@ -68,7 +79,94 @@ impl TryFrom<u8> for TypeId {
   }
 }
 
-// TODO: redo all of this in nom.
+impl From<TypeId> for u8 {
+  fn from(t: TypeId) -> u8 {
+    match t {
+      TypeId::Add => 0,
+      TypeId::Mul => 1,
+      TypeId::Min => 2,
+      TypeId::Max => 3,
+      TypeId::GT => 5,
+      TypeId::LT => 6,
+      TypeId::Eq => 7,
+    }
+  }
+}
+
+impl TypeId {
+  /// GT, LT and EQ take exactly two subpackets, and the others at least one.
+  pub fn takes(self, n: usize) -> bool {
+    match self {
+      Self::GT | Self::LT | Self::Eq => n == 2,
+      _ => n > 0,
+    }
+  }
+
+  /// Folds the next subpacket's value into those before it.  `None` if a sum or product overflows.
+  pub fn combine(self, acc: u64, v: u64) -> Option<u64> {
+    match self {
+      Self::Add => acc.checked_add(v),
+      Self::Mul => acc.checked_mul(v),
+      Self::Min => Some(acc.min(v)),
+      Self::Max => Some(acc.max(v)),
+      Self::GT => Some((acc > v) as u64),
+      Self::LT => Some((acc < v) as u64),
+      Self::Eq => Some((acc == v) as u64),
+    }
+  }
+}
+
+impl std::fmt::Display for TypeId {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let s = match self {
+      Self::Add => "+",
+      Self::Mul => "*",
+      Self::Min => "min",
+      Self::Max => "max",
+      Self::GT => ">",
+      Self::LT => "<",
+      Self::Eq => "=",
+    };
+    write!(f, "{}", s)
+  }
+}
+
+/// An S-expression such as `(+ 1 (* 2 3))`.  A version other than zero follows its packet's number or operator
+/// after an `@`, as in `(+@3 1 2@7)`.
+impl std::fmt::Display for Packet {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let version = |f: &mut std::fmt::Formatter<'_>, v| match v {
+      0 => Ok(()),
+      v => write!(f, "@{}", v),
+    };
+    match self {
+      Self::Literal { version: v, number } => {
+        write!(f, "{}", number)?;
+        version(f, *v)
+      }
+      Self::Operation {
+        version: v,
+        type_id,
+        subpackets,
+      } => {
+        write!(f, "({}", type_id)?;
+        version(f, *v)?;
+        for p in subpackets {
+          write!(f, " {}", p)?;
+        }
+        write!(f, ")")
+      }
+    }
+  }
+}
+
+impl std::str::FromStr for Packet {
+  type Err = Error;
+  fn from_str(s: &str) -> Result<Self> {
+    parse_all(parse_expr)(s)
+  }
+}
+
 impl Packet {
   pub fn version(&self) -> u8 {
     match self {
@@ -77,56 +175,97 @@ impl Packet {
     }
   }
 
-  pub fn from_bits(bits: &[Bit]) -> (Self, usize) {
-    let version = Bit::number_from_bits(&bits[0..3]) as u8;
-    match Bit::number_from_bits(&bits[3..6]) {
-      4 => {
-        let mut offset = 6;
-        let mut number = Vec::new();
-        loop {
-          number.append(&mut bits[(offset + 1)..(offset + 5)].to_vec());
-          let old_offset = offset;
-          offset = offset + 5;
-          if bits[old_offset] == O {
-            break;
-          }
+  /// Decodes a whole transmission: one packet, then nothing but zero padding.
+  pub fn decode(bytes: &[u8]) -> std::result::Result<Self, DecodeError> {
+    let total = bytes.len() * 8;
+    let located = |e: BitsError| DecodeError {
+      offset: total - e.remaining,
+      kind: e.kind,
+    };
+    let (rest, packet) = packet((bytes, 0), 0).map_err(|e| match e {
+      Err::Error(e) | Err::Failure(e) => located(e),
+      Err::Incomplete(_) => located(BitsError {
+        remaining: 0,
+        kind: DecodeErrorKind::Truncated,
+      }),
+    })?;
+    let padding = rest.0.split_first().is_none_or(|(first, others)| {
+      first & (0xff >> rest.1) == 0 && others.iter().all(|&b| b == 0)
+    });
+    if !padding {
+      return std::result::Result::Err(located(BitsError {
+        remaining: remaining(rest),
+        kind: DecodeErrorKind::TrailingBits,
+      }));
+    }
+    Ok(packet)
+  }
+
+  /// The inverse of `decode`, before padding.  Subpackets are given by their length in bits where that fits in the 15 bits
+  /// allowed, and by their count where that fits in 11.  `None` if some operator has too many for either.
+  pub fn to_bits(&self) -> Option<Vec<Bit>> {
+    let mut bits = Vec::new();
+    self.write_bits(&mut bits)?;
+    Some(bits)
+  }
+
+  fn write_bits(&self, bits: &mut Vec<Bit>) -> Option<()> {
+    Bit::push_number(bits, self.version() as u64, 3);
+    match self {
+      Self::Literal { number, .. } => {
+        Bit::push_number(bits, 4, 3);
+        let groups = (64 - number.leading_zeros() as usize).div_ceil(4).max(1);
+        for group in (0..groups).rev() {
+          bits.push(if group > 0 { I } else { O });
+          Bit::push_number(bits, number >> (4 * group), 4);
         }
-        (Self::Literal { version, number }, offset)
       }
-      type_id => {
-        let mut offset = 7;
-        let mut subpackets = Vec::new();
-        let type_id = TypeId::try_from(type_id as u8).unwrap();
-        if bits[6] == O {
-          // length in bits
-          let subpackets_bit_len = Bit::number_from_bits(&bits[offset..offset + 15]);
-          offset += 15;
-          let final_offset = offset + subpackets_bit_len as usize;
-          while offset < final_offset {
-            let (subpacket, offset_offset) = Packet::from_bits(&bits[offset..]);
-            offset += offset_offset;
-            subpackets.push(subpacket);
-          }
+      Self::Operation {
+        type_id,
+        subpackets,
+        ..
+      } => {
+        Bit::push_number(bits, u8::from(*type_id) as u64, 3);
+        let mut sub_bits = Vec::new();
+        for p in subpackets {
+          p.write_bits(&mut sub_bits)?;
+        }
+        if sub_bits.len() < 1 << 15 {
+          bits.push(O);
+          Bit::push_number(bits, sub_bits.len() as u64, 15);
+        } else if subpackets.len() < 1 << 11 {
+          bits.push(I);
+          Bit::push_number(bits, subpackets.len() as u64, 11);
         } else {
-          // number of subpackets
-          let num_subpackets = Bit::number_from_bits(&bits[offset..offset + 11]);
-          offset += 11;
-          for _ in 0..num_subpackets {
-            let (subpacket, offset_offset) = Packet::from_bits(&bits[offset..]);
-            offset += offset_offset;
-            subpackets.push(subpacket);
-          }
+          return None;
         }
-        (
-          Packet::Operation {
-            version,
-            type_id,
-            subpackets,
-          },
-          offset,
-        )
+        bits.append(&mut sub_bits);
       }
     }
+    Some(())
+  }
+
+  /// The transmission as bytes, padded with zeros.
+  pub fn to_bytes(&self) -> Option<Vec<u8>> {
+    let bytes = self
+      .to_bits()?
+      .chunks(8)
+      .map(|byte| (Bit::number_from_bits(byte) << (8 - byte.len())) as u8)
+      .collect();
+    Some(bytes)
+  }
+
+  /// The transmission as hex, padded with zeros to a whole number of digits.
+  pub fn to_hex(&self) -> Option<String> {
+    let hex = self
+      .to_bits()?
+      .chunks(4)
+      .map(|nibble| {
+        let n = Bit::number_from_bits(nibble) << (4 - nibble.len());
+        char::from_digit(n as u32, 16).unwrap().to_ascii_uppercase()
+      })
+      .collect();
+    Some(hex)
   }
 
   pub fn sum_versions(&self) -> u64 {
@@ -140,177 +279,1000 @@ impl Packet {
     }
   }
 
-  pub fn eval(&self) -> u64 {
+  /// `Overflow` for a sum or product too big for a `u64`, and `Arity` for an operator with the wrong number of
+  /// subpackets, which `decode` and `parse_expr` never build.
+  pub fn eval(&self) -> std::result::Result<u64, DecodeErrorKind> {
     match self {
-      Self::Literal { number, .. } => Bit::number_from_bits(&number),
+      Self::Literal { number, .. } => Ok(*number),
       Self::Operation {
         type_id,
         subpackets,
         ..
       } => {
-        let mut nums = subpackets.iter().map(Packet::eval);
-        match type_id {
-          TypeId::Add => nums.sum(),
-          TypeId::Mul => nums.product(),
-          TypeId::Min => nums.min().unwrap(), // Should always have at least one item.
-          TypeId::Max => nums.max().unwrap(),
-          TypeId::GT => {
-            if nums.next().unwrap() > nums.next().unwrap() {
-              1
-            } else {
-              0
-            }
-          }
-          TypeId::LT => {
-            if nums.next().unwrap() < nums.next().unwrap() {
-              1
-            } else {
-              0
-            }
-          }
-          TypeId::Eq => {
-            if nums.next().unwrap() == nums.next().unwrap() {
-              1
-            } else {
-              0
+        let arity = DecodeErrorKind::Arity(*type_id, subpackets.len());
+        if !type_id.takes(subpackets.len()) {
+          return std::result::Result::Err(arity);
+        }
+        let mut values = subpackets.iter().map(Packet::eval);
+        let first = values.next().ok_or(arity)??;
+        values.try_fold(first, |acc, v| {
+          type_id.combine(acc, v?).ok_or(DecodeErrorKind::Overflow)
+        })
+      }
+    }
+  }
+}
+
+/// Why a transmission couldn't be decoded.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum DecodeErrorKind {
+  /// The transmission ended partway through a packet.
+  Truncated,
+  /// A literal, or the value of a sum or product, too big for a `u64`.
+  Overflow,
+  UnknownTypeId(u8),
+  /// The operator can't take this many subpackets; see [`TypeId::takes`].
+  Arity(TypeId, usize),
+  /// The subpackets ran past the length their operator gave.
+  LengthMismatch,
+  /// Something other than zero padding after the outermost packet.
+  TrailingBits,
+  /// Operators nested more than [`MAX_DEPTH`] deep, which `decode` won't build; `evaluate` has no such limit.
+  TooDeep,
+  /// A character that is neither a hex digit nor whitespace, when reading a stream.
+  NotHex(u8),
+}
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct DecodeError {
+  /// Bits from the start of the transmission.  For a packet that is wrong as a whole, this is where it starts.
+  pub offset: usize,
+  pub kind: DecodeErrorKind,
+}
+
+/// Bytes, and how many bits of the first one are already used.
+type Bits<'a> = (&'a [u8], usize);
+
+/// A `DecodeError` before it knows its offset; the parsers only see the input that is left.
+#[derive(Debug)]
+struct BitsError {
+  remaining: usize,
+  kind: DecodeErrorKind,
+}
+
+type BitsResult<'a, O> = IResult<Bits<'a>, O, BitsError>;
+
+fn remaining(input: Bits) -> usize {
+  input.0.len() * 8 - input.1
+}
+
+impl<'a> nom::error::ParseError<Bits<'a>> for BitsError {
+  /// nom's bit parsers only fail by running out.
+  fn from_error_kind(input: Bits<'a>, _: nom::error::ErrorKind) -> Self {
+    Self {
+      remaining: remaining(input),
+      kind: DecodeErrorKind::Truncated,
+    }
+  }
+
+  fn append(_: Bits<'a>, _: nom::error::ErrorKind, other: Self) -> Self {
+    other
+  }
+}
+
+fn fail<O>(at: Bits, kind: DecodeErrorKind) -> BitsResult<O> {
+  std::result::Result::Err(Err::Failure(BitsError {
+    remaining: remaining(at),
+    kind,
+  }))
+}
+
+fn number<'a>(width: usize) -> impl FnMut(Bits<'a>) -> BitsResult<'a, u64> {
+  nom::bits::complete::take(width)
+}
+
+fn literal(mut input: Bits) -> BitsResult<u64> {
+  let mut n: u64 = 0;
+  loop {
+    let (rest, (more, group)) = pair(number(1), number(4))(input)?;
+    if n >> 60 != 0 {
+      return fail(input, DecodeErrorKind::Overflow);
+    }
+    n = n << 4 | group;
+    input = rest;
+    if more == 0 {
+      return Ok((input, n));
+    }
+  }
+}
+
+/// How deeply `decode` lets operators nest, so that it and the recursion over the packets it builds stay well clear of
+/// the end of the stack.
+pub const MAX_DEPTH: usize = 500;
+
+/// The subpackets of an operator `depth` deep.
+fn subpackets(input: Bits, depth: usize) -> BitsResult<Vec<Packet>> {
+  let (input, length_type) = number(1)(input)?;
+  if length_type == 1 {
+    let (input, n) = number(11)(input)?;
+    return count(|input| packet(input, depth + 1), n as usize)(input);
+  }
+  let (mut input, len) = number(15)(input)?;
+  let end = match remaining(input).checked_sub(len as usize) {
+    Some(end) => end,
+    None => return fail(input, DecodeErrorKind::Truncated),
+  };
+  let mut subpackets = Vec::new();
+  while remaining(input) > end {
+    let (rest, p) = packet(input, depth + 1)?;
+    if remaining(rest) < end {
+      return fail(input, DecodeErrorKind::LengthMismatch);
+    }
+    subpackets.push(p);
+    input = rest;
+  }
+  Ok((input, subpackets))
+}
+
+/// A packet `depth` operators deep.
+fn packet(input: Bits, depth: usize) -> BitsResult<Packet> {
+  let start = input;
+  let (input, (version, type_id)) = pair(number(3), number(3))(input)?;
+  let version = version as u8;
+  if type_id == 4 {
+    let (input, number) = literal(input)?;
+    return Ok((input, Packet::Literal { version, number }));
+  }
+  let type_id = match TypeId::try_from(type_id as u8) {
+    Ok(t) => t,
+    _ => return fail(start, DecodeErrorKind::UnknownTypeId(type_id as u8)),
+  };
+  if depth == MAX_DEPTH {
+    return fail(start, DecodeErrorKind::TooDeep);
+  }
+  let (input, subpackets) = subpackets(input, depth)?;
+  if !type_id.takes(subpackets.len()) {
+    return fail(start, DecodeErrorKind::Arity(type_id, subpackets.len()));
+  }
+  Ok((
+    input,
+    Packet::Operation {
+      version,
+      type_id,
+      subpackets,
+    },
+  ))
+}
+
+fn parse_version(input: &str) -> IResult<&str, u8> {
+  map(
+    opt(preceded(tag("@"), verify(parse_u8, |&v| v < 8))),
+    Option::unwrap_or_default,
+  )(input)
+}
+
+fn parse_type_id(input: &str) -> IResult<&str, TypeId> {
+  alt((
+    value(TypeId::Add, tag("+")),
+    value(TypeId::Mul, tag("*")),
+    value(TypeId::Min, tag("min")),
+    value(TypeId::Max, tag("max")),
+    value(TypeId::GT, tag(">")),
+    value(TypeId::LT, tag("<")),
+    value(TypeId::Eq, tag("=")),
+  ))(input)
+}
+
+fn parse_literal(input: &str) -> IResult<&str, Packet> {
+  map(
+    pair(map_res(digit1, |s: &str| s.parse::<u64>()), parse_version),
+    |(number, version)| Packet::Literal { version, number },
+  )(input)
+}
+
+fn parse_operation(input: &str) -> IResult<&str, Packet> {
+  map(
+    verify(
+      delimited(
+        pair(tag("("), multispace0),
+        tuple((
+          parse_type_id,
+          parse_version,
+          many0(preceded(multispace1, parse_expr)),
+        )),
+        pair(multispace0, tag(")")),
+      ),
+      |(type_id, _, subpackets): &(TypeId, u8, Vec<Packet>)| type_id.takes(subpackets.len()),
+    ),
+    |(type_id, version, subpackets)| Packet::Operation {
+      version,
+      type_id,
+      subpackets,
+    },
+  )(input)
+}
+
+/// The inverse of `Packet`'s `Display`.
+pub fn parse_expr(input: &str) -> IResult<&str, Packet> {
+  alt((parse_literal, parse_operation))(input)
+}
+
+/// Works out an expression, giving its value and the transmission that would carry it.  `NoSolution` if the
+/// value overflows or no transmission can carry it.
+pub fn calculate(expr: &str) -> Result<(u64, String)> {
+  let packet: Packet = expr.parse()?;
+  let value = packet.eval().map_err(|_| Error::NoSolution)?;
+  let hex = packet.to_hex().ok_or(Error::NoSolution)?;
+  Ok((value, hex))
+}
+
+#[derive(Debug)]
+pub enum StreamError {
+  Io(std::io::Error),
+  Decode(DecodeError),
+}
+
+impl From<std::io::Error> for StreamError {
+  fn from(e: std::io::Error) -> Self {
+    Self::Io(e)
+  }
+}
+
+/// Both answers for a transmission, worked out without building its packets.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct Summary {
+  pub version_sum: u64,
+  pub value: u64,
+}
+
+/// Bits read lazily from hex text, a digit at a time.  Whitespace is skipped.
+struct HexBits<R: Read> {
+  bytes: Bytes<BufReader<R>>,
+  /// The low `len` bits are read but not yet taken.
+  buffer: u64,
+  len: usize,
+  /// Bits taken so far.
+  offset: usize,
+}
+
+impl<R: Read> HexBits<R> {
+  fn new(reader: R) -> Self {
+    Self {
+      bytes: BufReader::new(reader).bytes(),
+      buffer: 0,
+      len: 0,
+      offset: 0,
+    }
+  }
+
+  fn error<T>(&self, offset: usize, kind: DecodeErrorKind) -> std::result::Result<T, StreamError> {
+    std::result::Result::Err(StreamError::Decode(DecodeError { offset, kind }))
+  }
+
+  /// The next hex digit, or `None` at the end.
+  fn digit(&mut self) -> std::result::Result<Option<u64>, StreamError> {
+    for byte in self.bytes.by_ref() {
+      let byte = byte?;
+      if byte.is_ascii_whitespace() {
+        continue;
+      }
+      return match (byte as char).to_digit(16) {
+        Some(d) => Ok(Some(d as u64)),
+        None => self.error(self.offset + self.len, DecodeErrorKind::NotHex(byte)),
+      };
+    }
+    Ok(None)
+  }
+
+  /// The next `width` bits as a number.  `width` is at most 15, so the buffer never holds more than 18 bits.
+  fn take(&mut self, width: usize) -> std::result::Result<u64, StreamError> {
+    while self.len < width {
+      match self.digit()? {
+        Some(d) => {
+          self.buffer = self.buffer << 4 | d;
+          self.len += 4;
+        }
+        None => return self.error(self.offset, DecodeErrorKind::Truncated),
+      }
+    }
+    self.len -= width;
+    self.offset += width;
+    let n = self.buffer >> self.len;
+    self.buffer &= (1 << self.len) - 1;
+    Ok(n)
+  }
+
+  /// Checks that nothing but zero padding is left.
+  fn finish(&mut self) -> std::result::Result<(), StreamError> {
+    let mut padding = self.buffer == 0;
+    while padding {
+      match self.digit()? {
+        Some(d) => padding = d == 0,
+        None => return Ok(()),
+      }
+    }
+    self.error(self.offset, DecodeErrorKind::TrailingBits)
+  }
+
+  fn literal(&mut self) -> std::result::Result<u64, StreamError> {
+    let mut n: u64 = 0;
+    loop {
+      let start = self.offset;
+      let more = self.take(1)?;
+      let group = self.take(4)?;
+      if n >> 60 != 0 {
+        return self.error(start, DecodeErrorKind::Overflow);
+      }
+      n = n << 4 | group;
+      if more == 0 {
+        return Ok(n);
+      }
+    }
+  }
+
+  /// Reads a packet, folding each subpacket's value into its operator's as soon as it is read.  The operators still
+  /// being read are kept on a stack rather than by recursion, however deeply they nest.
+  fn packet(&mut self) -> std::result::Result<Summary, StreamError> {
+    let mut version_sum = 0;
+    let mut open: Vec<Open> = Vec::new();
+    loop {
+      let start = self.offset;
+      version_sum += self.take(3)?;
+      let type_id = self.take(3)? as u8;
+      // A packet read in full, with where it started.
+      let mut done = if type_id == 4 {
+        Some((start, self.literal()?))
+      } else {
+        let type_id = match TypeId::try_from(type_id) {
+          Ok(t) => t,
+          _ => return self.error(start, DecodeErrorKind::UnknownTypeId(type_id)),
+        };
+        // Unlike `decode`, this can't see a length running past the end until it gets there, so that is reported as
+        // `Truncated` inside the subpackets.
+        let until = if self.take(1)? == 1 {
+          Until::Count(self.take(11)?)
+        } else {
+          Until::Offset(self.take(15)? as usize + self.offset)
+        };
+        open.push(Open {
+          start,
+          type_id,
+          value: None,
+          n: 0,
+          until,
+        });
+        None
+      };
+      loop {
+        if let Some((sub_start, value)) = done.take() {
+          let op = match open.last_mut() {
+            Some(op) => op,
+            None => return Ok(Summary { version_sum, value }),
+          };
+          match &mut op.until {
+            Until::Count(left) => *left -= 1,
+            Until::Offset(end) if self.offset > *end => {
+              return self.error(sub_start, DecodeErrorKind::LengthMismatch)
             }
+            Until::Offset(_) => (),
           }
+          op.n += 1;
+          op.value = match op.value {
+            None => Some(value),
+            Some(acc) => match op.type_id.combine(acc, value) {
+              Some(v) => Some(v),
+              None => return self.error(op.start, DecodeErrorKind::Overflow),
+            },
+          };
+        }
+        if !open.last().is_some_and(|op| op.finished(self.offset)) {
+          break;
+        }
+        let op = open.pop().unwrap();
+        match op.value {
+          Some(value) if op.type_id.takes(op.n) => done = Some((op.start, value)),
+          _ => return self.error(op.start, DecodeErrorKind::Arity(op.type_id, op.n)),
         }
       }
     }
   }
 }
 
-type Input = Vec<Bit>;
+/// An operator in a stream whose subpackets are still being read.
+struct Open {
+  start: usize,
+  type_id: TypeId,
+  /// The subpackets read so far, folded together.
+  value: Option<u64>,
+  n: usize,
+  until: Until,
+}
+
+impl Open {
+  fn finished(&self, offset: usize) -> bool {
+    match self.until {
+      Until::Count(left) => left == 0,
+      Until::Offset(end) => offset >= end,
+    }
+  }
+}
+
+/// Where an operator's subpackets end.
+enum Until {
+  /// How many are left.
+  Count(u64),
+  /// The offset just past the last.
+  Offset(usize),
+}
+
+/// Decodes and evaluates a hex transmission from `reader`, holding only a few bits and the open packets at a time.
+pub fn evaluate(reader: impl Read) -> std::result::Result<Summary, StreamError> {
+  let mut bits = HexBits::new(reader);
+  let summary = bits.packet()?;
+  bits.finish()?;
+  Ok(summary)
+}
 
-pub fn parse(input: &str) -> IResult<&str, Input> {
+type Input = Packet;
+
+fn parse_hex(input: &str) -> IResult<&str, Vec<u8>> {
   let hex_digit = map_res(take(1_usize), |u: &str| u8::from_str_radix(u, 16));
-  let (input, bytes) = many1(hex_digit)(input)?;
-  let bits = bytes
-    .into_iter()
-    .flat_map(|byte| {
-      (0..4)
-        .rev()
-        .map(move |offset| Bit::from_byte_and_offset(byte, offset))
-    })
-    .collect();
-  Ok((input, bits))
+  map(many1(hex_digit), |nibbles| {
+    nibbles
+      .chunks(2)
+      .map(|pair| pair[0] << 4 | pair.get(1).unwrap_or(&0))
+      .collect()
+  })(input)
+}
+
+pub fn parse(input: &str) -> Result<Input> {
+  let bytes = parse_all(parse_hex)(input)?;
+  Packet::decode(&bytes).map_err(|e| {
+    // Point at the hex digit holding the bad bit.
+    let start = input.trim_start();
+    let digit = (e.offset / 4).min(start.trim_end().len());
+    Error::parse(input, &start[digit..])
+  })
 }
 
-pub fn p1(input: Input) -> u64 {
-  let (p, _) = Packet::from_bits(&input);
-  p.sum_versions()
+pub fn p1(input: Input) -> Result<u64> {
+  Ok(input.sum_versions())
 }
 
-pub fn p2(input: Input) -> u64 {
-  let (p, _) = Packet::from_bits(&input);
-  p.eval()
+pub fn p2(input: Input) -> Result<u64> {
+  input.eval().map_err(|_| Error::NoSolution)
+}
+
+pub struct Day;
+
+impl Solution for Day {
+  const YEAR: u32 = 2021;
+  const DAY: u32 = 16;
+  type Input<'a> = Input;
+  type Output1 = u64;
+  type Output2 = u64;
+
+  fn parse(input: &str) -> Result<Self::Input<'_>> {
+    parse(input)
+  }
+
+  fn part1(input: Self::Input<'_>) -> Result<u64> {
+    p1(input)
+  }
+
+  fn part2(input: Self::Input<'_>) -> Result<u64> {
+    p2(input)
+  }
 }
 
 #[cfg(test)]
 mod test {
   use super::*;
+  use aoc_common::test_util::XorShift;
 
-  const TEST_LITERAL: &'static str = "D2FE28";
-  const TEST_OPERATION: &'static str = "38006F45291200";
+  const TEST_LITERAL: &str = "D2FE28";
+  const TEST_OPERATION: &str = "38006F45291200";
 
   #[test]
   fn test_parse() {
-    let input = TEST_LITERAL;
-    assert_eq!(
-      parse(input).unwrap(),
-      (
-        "",
-        vec![I, I, O, I, O, O, I, O, I, I, I, I, I, I, I, O, O, O, I, O, I, O, O, O]
-      )
-    )
+    assert_eq!(parse_hex(TEST_LITERAL), Ok(("", vec![0xd2, 0xfe, 0x28])));
+    assert_eq!(parse_hex("D2F"), Ok(("", vec![0xd2, 0xf0])));
   }
 
   #[test]
   fn test_literal() {
-    let input = TEST_LITERAL;
-    let literal = parse(input).unwrap().1;
     assert_eq!(
-      Packet::from_bits(&literal),
-      (
-        Packet::Literal {
-          version: 6,
-          number: vec![O, I, I, I, I, I, I, O, O, I, O, I,]
-        },
-        21
-      )
+      parse(TEST_LITERAL).unwrap(),
+      Packet::Literal {
+        version: 6,
+        number: 2021
+      }
     );
   }
 
   #[test]
   fn test_operation() {
-    let input = TEST_OPERATION;
-    let operation = parse(input).unwrap().1;
-    match Packet::from_bits(&operation) {
-      (Packet::Operation { subpackets, .. }, _) => assert_eq!(subpackets.len(), 2),
+    match parse(TEST_OPERATION).unwrap() {
+      Packet::Operation { subpackets, .. } => assert_eq!(subpackets.len(), 2),
       _ => panic!("parsed wrong"),
     }
   }
 
+  #[test]
+  fn test_decode_errors() {
+    let error = |offset, kind| Err(DecodeError { offset, kind });
+    use DecodeErrorKind::*;
+    assert_eq!(Packet::decode(&[0xd2, 0xfe]), error(16, Truncated));
+    assert_eq!(Packet::decode(&[]), error(0, Truncated));
+    assert_eq!(Packet::decode(&[0xd2, 0xfe, 0x29]), error(21, TrailingBits));
+    assert_eq!(
+      Packet::decode(&[0xd2, 0xfe, 0x28, 0, 1]),
+      error(21, TrailingBits)
+    );
+
+    // Seventeen groups of 1111 is more than 64 bits; the error points at the seventeenth.
+    let mut long = vec![O, O, O, I, O, O];
+    for _ in 0..17 {
+      long.extend([I, I, I, I, I]);
+    }
+    long.extend([O, O, O, O, O]);
+    assert_eq!(
+      Packet::decode(&to_bytes(&long)),
+      error(6 + 16 * 5, Overflow)
+    );
+
+    let literal = |number| Packet::Literal { version: 0, number };
+    let gt = Packet::Operation {
+      version: 0,
+      type_id: TypeId::GT,
+      subpackets: vec![literal(1)],
+    };
+    assert_eq!(
+      Packet::decode(&gt.to_bytes().unwrap()),
+      error(0, Arity(TypeId::GT, 1))
+    );
+    let nested = Packet::Operation {
+      version: 0,
+      type_id: TypeId::Add,
+      subpackets: vec![literal(1), gt],
+    };
+    assert_eq!(
+      Packet::decode(&nested.to_bytes().unwrap()),
+      error(22 + 11, Arity(TypeId::GT, 1))
+    );
+
+    // The example operator says its subpackets take 27 bits; claim fewer than the first one needs.
+    let mut bytes = parse_hex(TEST_OPERATION).unwrap().1;
+    bytes[1] = 0x00;
+    bytes[2] = 0x17;
+    assert_eq!(Packet::decode(&bytes), error(22, LengthMismatch));
+    // And more than there is.
+    bytes[1] = 0x7f;
+    assert_eq!(Packet::decode(&bytes), error(22, Truncated));
+
+    assert_eq!(
+      parse("D2FE"),
+      Err(Error::Parse {
+        line: 1,
+        column: 5,
+        snippet: String::new()
+      })
+    );
+    assert!(matches!(
+      parse("D2FE29"),
+      Err(Error::Parse { column: 6, .. })
+    ));
+  }
+
+  /// `depth` sums of one subpacket each, around a literal 1, all version 1.
+  fn nested(depth: usize) -> Vec<Bit> {
+    let mut bits = Vec::new();
+    for _ in 0..depth {
+      Bit::push_number(&mut bits, 1, 3);
+      Bit::push_number(&mut bits, 0, 3);
+      Bit::push_number(&mut bits, 1, 1);
+      Bit::push_number(&mut bits, 1, 11);
+    }
+    Bit::push_number(&mut bits, 1, 3);
+    Bit::push_number(&mut bits, 4, 3);
+    Bit::push_number(&mut bits, 1, 5);
+    bits
+  }
+
+  fn to_bytes(bits: &[Bit]) -> Vec<u8> {
+    bits
+      .chunks(8)
+      .map(|byte| (Bit::number_from_bits(byte) << (8 - byte.len())) as u8)
+      .collect()
+  }
+
+  #[test]
+  fn test_decode_deep() {
+    let deepest = Packet::decode(&to_bytes(&nested(MAX_DEPTH))).unwrap();
+    assert_eq!(deepest.sum_versions(), MAX_DEPTH as u64 + 1);
+    assert_eq!(deepest.eval(), Ok(1));
+    assert_eq!(
+      Packet::decode(&to_bytes(&nested(MAX_DEPTH + 1))),
+      Err(DecodeError {
+        offset: 18 * MAX_DEPTH,
+        kind: DecodeErrorKind::TooDeep
+      })
+    );
+    let hex: String = to_bytes(&nested(200_000))
+      .iter()
+      .map(|b| format!("{:02X}", b))
+      .collect();
+    assert!(matches!(parse(&hex), Err(Error::Parse { .. })));
+  }
+
+  #[test]
+  fn test_evaluate() {
+    let decode_error = |hex: &str| match evaluate(hex.as_bytes()) {
+      Err(StreamError::Decode(e)) => Some(e),
+      _ => None,
+    };
+    let error = |offset, kind| Some(DecodeError { offset, kind });
+    use DecodeErrorKind::*;
+    assert_eq!(decode_error("D2FE"), error(16, Truncated));
+    assert_eq!(decode_error(""), error(0, Truncated));
+    assert_eq!(decode_error("D2FE29"), error(21, TrailingBits));
+    assert_eq!(decode_error("D2FE2800\n01"), error(21, TrailingBits));
+    assert_eq!(decode_error("D2FG28"), error(12, NotHex(b'G')));
+    let gt = Packet::Operation {
+      version: 0,
+      type_id: TypeId::GT,
+      subpackets: vec![Packet::Literal {
+        version: 0,
+        number: 1,
+      }],
+    };
+    assert_eq!(
+      decode_error(&gt.to_hex().unwrap()),
+      error(0, Arity(TypeId::GT, 1))
+    );
+    let mut bytes = parse_hex(TEST_OPERATION).unwrap().1;
+    bytes[1] = 0x00;
+    bytes[2] = 0x17;
+    let hex: String = bytes.iter().map(|b| format!("{:02X}", b)).collect();
+    assert_eq!(decode_error(&hex), error(22, LengthMismatch));
+    let hex = |expr: &str| expr.parse::<Packet>().unwrap().to_hex().unwrap();
+    assert_eq!(
+      decode_error(&hex("(+ 18446744073709551615 1)")),
+      error(0, Overflow)
+    );
+    // The product is the packet that overflows, after the 22-bit header and an 11-bit literal.
+    assert_eq!(
+      decode_error(&hex("(max 1 (* 4294967296 4294967296))")),
+      error(22 + 11, Overflow)
+    );
+
+    let summary = |p: &Packet| Summary {
+      version_sum: p.sum_versions(),
+      value: p.eval().unwrap(),
+    };
+    let input = std::fs::read_to_string("./inputs/d16.txt").unwrap();
+    let file = std::fs::File::open("./inputs/d16.txt").unwrap();
+    assert_eq!(evaluate(file).unwrap(), summary(&parse(&input).unwrap()));
+    for hex in [
+      "8A004A801A8002F478",
+      "C0015000016115A2E0802F182340",
+      "9C0141080250320F1802104A08",
+    ] {
+      assert_eq!(
+        evaluate(hex.as_bytes()).unwrap(),
+        summary(&parse(hex).unwrap())
+      );
+    }
+
+    // Small literals, so that sums and products stay in range.
+    let mut rng = XorShift(0x2545_f491_4f6c_dd1d);
+    for _ in 0..500 {
+      let mut packet = random_packet(&mut rng, 2);
+      shrink(&mut packet);
+      assert_eq!(
+        evaluate(packet.to_hex().unwrap().as_bytes()).unwrap(),
+        summary(&packet)
+      );
+    }
+    // Full-size literals, which often overflow; streaming must fail just where `eval` does.
+    for _ in 0..500 {
+      let packet = random_packet(&mut rng, 3);
+      match (evaluate(packet.to_hex().unwrap().as_bytes()), packet.eval()) {
+        (Ok(streamed), Ok(value)) => assert_eq!(streamed.value, value),
+        (Err(StreamError::Decode(e)), Err(kind)) => assert_eq!(e.kind, kind),
+        (streamed, evaluated) => panic!("{:?} but {:?} for {}", streamed, evaluated, packet),
+      }
+    }
+
+    // Far deeper than `decode` goes.
+    let hex: String = to_bytes(&nested(200_000))
+      .iter()
+      .map(|b| format!("{:02X}", b))
+      .collect();
+    assert_eq!(
+      evaluate(hex.as_bytes()).unwrap(),
+      Summary {
+        version_sum: 200_001,
+        value: 1
+      }
+    );
+
+    // A couple of megabytes of hex.
+    let literals = |n| {
+      (0..n)
+        .map(|i| Packet::Literal {
+          version: 1,
+          number: i,
+        })
+        .collect()
+    };
+    let big = Packet::Operation {
+      version: 0,
+      type_id: TypeId::Add,
+      subpackets: (0..1000)
+        .map(|_| Packet::Operation {
+          version: 0,
+          type_id: TypeId::Max,
+          subpackets: literals(1000),
+        })
+        .collect(),
+    };
+    let hex = big.to_hex().unwrap();
+    assert!(hex.len() > 2_000_000);
+    assert_eq!(
+      evaluate(hex.as_bytes()).unwrap(),
+      Summary {
+        version_sum: 1_000_000,
+        value: 999_000
+      }
+    );
+  }
+
+  fn shrink(packet: &mut Packet) {
+    match packet {
+      Packet::Literal { number, .. } => *number %= 8,
+      Packet::Operation { subpackets, .. } => subpackets.iter_mut().for_each(shrink),
+    }
+  }
+
+  #[test]
+  fn test_display() {
+    assert_eq!(parse(TEST_LITERAL).unwrap().to_string(), "2021@6");
+    assert_eq!(
+      parse(TEST_OPERATION).unwrap().to_string(),
+      "(<@1 10@6 20@2)"
+    );
+    assert_eq!(
+      parse("9C0141080250320F1802104A08").unwrap().to_string(),
+      "(=@4 (+@2 1@2 3@4) (*@6 2 2@2))"
+    );
+  }
+
+  #[test]
+  fn test_parse_expr() {
+    let literal = |number| Packet::Literal { version: 0, number };
+    assert_eq!(
+      "( *@5   (min 3 1@7)\n 2 )".parse(),
+      Ok(Packet::Operation {
+        version: 5,
+        type_id: TypeId::Mul,
+        subpackets: vec![
+          Packet::Operation {
+            version: 0,
+            type_id: TypeId::Min,
+            subpackets: vec![
+              literal(3),
+              Packet::Literal {
+                version: 7,
+                number: 1
+              }
+            ],
+          },
+          literal(2),
+        ],
+      })
+    );
+    assert!("(> 1)".parse::<Packet>().is_err());
+    assert!("(+)".parse::<Packet>().is_err());
+    assert!("1@8".parse::<Packet>().is_err());
+    assert!("18446744073709551616".parse::<Packet>().is_err());
+    assert!("(+ 1 2".parse::<Packet>().is_err());
+    assert!("(+ 1 2) 3".parse::<Packet>().is_err());
+  }
+
+  #[test]
+  fn test_calculate() {
+    assert_eq!(calculate("(+ 1 (* 2 3))").unwrap().0, 7);
+    assert_eq!(calculate("(max 5 (min 9 4) (> 2 1))").unwrap().0, 5);
+    let (value, hex) = calculate("(= (+ 1 3) (* 2 2))").unwrap();
+    assert_eq!(value, 1);
+    assert_eq!(p2(parse(&hex).unwrap()), Ok(1));
+    // Too many terms for any transmission.
+    let wide = format!("(+{})", " 0".repeat(3000));
+    assert_eq!(calculate(&wide), Err(Error::NoSolution));
+
+    assert_eq!(calculate("(+ 18446744073709551614 1)").unwrap().0, u64::MAX);
+    assert_eq!(
+      calculate("(+ 18446744073709551615 1)"),
+      Err(Error::NoSolution)
+    );
+    assert_eq!(
+      calculate("(min 1 (* 4294967296 4294967296))"),
+      Err(Error::NoSolution)
+    );
+    let lonely = Packet::Operation {
+      version: 0,
+      type_id: TypeId::Eq,
+      subpackets: vec![Packet::Literal {
+        version: 0,
+        number: 1,
+      }],
+    };
+    assert_eq!(lonely.eval(), Err(DecodeErrorKind::Arity(TypeId::Eq, 1)));
+  }
+
+  #[test]
+  fn test_encode() {
+    let literal = parse(TEST_LITERAL).unwrap();
+    assert_eq!(literal.to_hex().unwrap(), TEST_LITERAL);
+    let operation = parse(TEST_OPERATION).unwrap();
+    // The example carries a spare byte of padding.
+    assert_eq!(operation.to_hex().unwrap() + "0", TEST_OPERATION);
+
+    let zero = Packet::Literal {
+      version: 0,
+      number: 0,
+    };
+    assert_eq!(zero.to_bits(), Some(vec![O, O, O, I, O, O, O, O, O, O, O]));
+    assert_eq!(zero.to_bytes(), Some(vec![0x10, 0x00]));
+
+    let input = std::fs::read_to_string("./inputs/d16.txt").unwrap();
+    let packet = parse(&input).unwrap();
+    assert_eq!(Packet::decode(&packet.to_bytes().unwrap()), Ok(packet));
+  }
+
+  fn random_packet(rng: &mut XorShift, depth: u32) -> Packet {
+    let version = rng.range(0, 7) as u8;
+    if depth == 0 || rng.range(0, 2) == 0 {
+      return Packet::Literal {
+        version,
+        number: rng.next_u64() >> rng.range(0, 63),
+      };
+    }
+    use TypeId::*;
+    let type_id = [Add, Mul, Min, Max, GT, LT, Eq][rng.range(0, 6) as usize];
+    let count = match type_id {
+      GT | LT | Eq => 2,
+      _ => rng.range(1, 4),
+    };
+    Packet::Operation {
+      version,
+      type_id,
+      subpackets: (0..count).map(|_| random_packet(rng, depth - 1)).collect(),
+    }
+  }
+
+  #[test]
+  fn test_round_trip() {
+    let mut rng = XorShift(0x9e37_79b9_7f4a_7c15);
+    for _ in 0..500 {
+      let packet = random_packet(&mut rng, 4);
+      assert_eq!(
+        Packet::decode(&packet.to_bytes().unwrap()),
+        Ok(packet.clone())
+      );
+      assert_eq!(packet.to_string().parse(), Ok(packet.clone()));
+      assert_eq!(parse(&packet.to_hex().unwrap()), Ok(packet));
+    }
+
+    // Enough subpackets that their length no longer fits in 15 bits, so they are counted instead.
+    let big = Packet::Operation {
+      version: 1,
+      type_id: TypeId::Add,
+      subpackets: vec![
+        Packet::Literal {
+          version: 7,
+          number: u64::MAX
+        };
+        500
+      ],
+    };
+    assert_eq!(big.to_bits().unwrap()[6], I);
+    assert_eq!(Packet::decode(&big.to_bytes().unwrap()), Ok(big));
+
+    // Past 2047 subpackets and 2^15 - 1 bits, neither length type can carry them.
+    let wide = |n| Packet::Operation {
+      version: 0,
+      type_id: TypeId::Max,
+      subpackets: vec![
+        Packet::Literal {
+          version: 0,
+          number: u64::MAX
+        };
+        n
+      ],
+    };
+    let widest = wide(2047);
+    assert_eq!(Packet::decode(&widest.to_bytes().unwrap()), Ok(widest));
+    assert_eq!(wide(2048).to_bits(), None);
+    assert_eq!(wide(5000).to_hex(), None);
+    let narrow = |n| Packet::Operation {
+      version: 0,
+      type_id: TypeId::Max,
+      subpackets: vec![
+        Packet::Literal {
+          version: 0,
+          number: 0
+        };
+        n
+      ],
+    };
+    // 11 bits apiece, so 2978 of them still fit in 15 bits of length.
+    let longest = narrow(2978);
+    assert_eq!(longest.to_bits().unwrap()[6], O);
+    assert_eq!(Packet::decode(&longest.to_bytes().unwrap()), Ok(longest));
+    assert_eq!(narrow(2979).to_bits(), None);
+  }
+
   #[test]
   fn test_p1() {
     let input = "8A004A801A8002F478";
-    let parsed = parse(input).unwrap().1;
-    assert_eq!(p1(parsed), 16);
+    let parsed = parse(input).unwrap();
+    assert_eq!(p1(parsed), Ok(16));
 
     let input = "620080001611562C8802118E34";
-    let parsed = parse(input).unwrap().1;
-    assert_eq!(p1(parsed), 12);
+    let parsed = parse(input).unwrap();
+    assert_eq!(p1(parsed), Ok(12));
 
     let input = "C0015000016115A2E0802F182340";
-    let parsed = parse(input).unwrap().1;
-    assert_eq!(p1(parsed), 23);
+    let parsed = parse(input).unwrap();
+    assert_eq!(p1(parsed), Ok(23));
 
     let input = "A0016C880162017C3686B18A3D4780";
-    let parsed = parse(input).unwrap().1;
-    assert_eq!(p1(parsed), 31);
+    let parsed = parse(input).unwrap();
+    assert_eq!(p1(parsed), Ok(31));
 
     let input = std::fs::read_to_string("./inputs/d16.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
-    assert_eq!(p1(parsed), 947);
+    let parsed = parse(&input).unwrap();
+    assert_eq!(p1(parsed), Ok(947));
   }
 
   #[test]
   fn test_p2() {
     let input = "C200B40A82";
-    let parsed = parse(input).unwrap().1;
-    assert_eq!(p2(parsed), 3);
+    let parsed = parse(input).unwrap();
+    assert_eq!(p2(parsed), Ok(3));
 
     let input = "04005AC33890";
-    let parsed = parse(input).unwrap().1;
-    assert_eq!(p2(parsed), 54);
+    let parsed = parse(input).unwrap();
+    assert_eq!(p2(parsed), Ok(54));
 
     let input = "880086C3E88112";
-    let parsed = parse(input).unwrap().1;
-    assert_eq!(p2(parsed), 7);
+    let parsed = parse(input).unwrap();
+    assert_eq!(p2(parsed), Ok(7));
 
     let input = "CE00C43D881120";
-    let parsed = parse(input).unwrap().1;
-    assert_eq!(p2(parsed), 9);
+    let parsed = parse(input).unwrap();
+    assert_eq!(p2(parsed), Ok(9));
 
     let input = "D8005AC2A8F0";
-    let parsed = parse(input).unwrap().1;
-    assert_eq!(p2(parsed), 1);
+    let parsed = parse(input).unwrap();
+    assert_eq!(p2(parsed), Ok(1));
 
     let input = "F600BC2D8F";
-    let parsed = parse(input).unwrap().1;
-    assert_eq!(p2(parsed), 0);
+    let parsed = parse(input).unwrap();
+    assert_eq!(p2(parsed), Ok(0));
 
     let input = "9C005AC2F8F0";
-    let parsed = parse(input).unwrap().1;
-    assert_eq!(p2(parsed), 0);
+    let parsed = parse(input).unwrap();
+    assert_eq!(p2(parsed), Ok(0));
 
     let input = "9C0141080250320F1802104A08";
-    let parsed = parse(input).unwrap().1;
-    assert_eq!(p2(parsed), 1);
+    let parsed = parse(input).unwrap();
+    assert_eq!(p2(parsed), Ok(1));
 
     let input = std::fs::read_to_string("./inputs/d16.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
-    assert_eq!(p2(parsed), 660797830937);
+    let parsed = parse(&input).unwrap();
+    assert_eq!(p2(parsed), Ok(660797830937));
   }
 }
@@ -1,7 +1,9 @@
 //! https://adventofcode.com/2021/day/17
 
-use crate::nom_prelude::*;
-use crate::vector::V;
+use aoc_common::error::{Error, Result};
+use aoc_common::nom_prelude::*;
+use aoc_common::solution::Solution;
+use aoc_common::vector::V;
 use itertools::Itertools;
 
 #[derive(Debug, PartialEq, Eq)]
@@ -37,7 +39,7 @@ impl Target {
 
 type Input = Target;
 
-pub fn parse(input: &str) -> IResult<&str, Input> {
+fn parse_input(input: &str) -> IResult<&str, Input> {
   // target area: x=20..30, y=-10..-5
   let (input, (_, xmin, _, xmax, _, ymin, _, ymax)) = tuple((
     tag("target area: x="),
@@ -60,7 +62,11 @@ pub fn parse(input: &str) -> IResult<&str, Input> {
   ))
 }
 
-pub fn p1(target: Input) -> usize {
+pub fn parse(input: &str) -> Result<Input> {
+  parse_all(parse_input)(input)
+}
+
+pub fn p1(target: Input) -> Result<usize> {
   // We could brute force this, but I wonder if it can be done analytically (I was able to do it by hand for my input, but only because I had some "full stop" trajectories which worked; with too narrow a hit box, this would not be possible).
   // Solve for range of steps.
   // steps_to_stopped = v0
@@ -73,75 +79,103 @@ pub fn p1(target: Input) -> usize {
   // brute force for now
   // don't need to explore any more than direct hit
   let x = target.xmin.abs().max(target.xmax.abs());
-  let lower_x = -1 * x;
+  let lower_x = -x;
   let y = target.ymin.abs().max(target.ymax.abs());
-  let lower_y = -1 * y;
+  let lower_y = -y;
   (lower_x..=x)
     .cartesian_product(lower_y..=y)
     .filter(|(x, y)| target.trajectory_hits(V(*x, *y)))
     .map(|(_, y)| (y * (y + 1)) / 2)
     .max()
-    .unwrap() as usize
+    .map(|height| height as usize)
+    .ok_or(Error::NoSolution)
 }
 
-pub fn p2(target: Input) -> usize {
+pub fn p2(target: Input) -> Result<usize> {
   // brute force for now
   // don't need to explore any more than direct hit
   let x = target.xmin.abs().max(target.xmax.abs());
-  let lower_x = -1 * x;
+  let lower_x = -x;
   let y = target.ymin.abs().max(target.ymax.abs());
-  let lower_y = -1 * y;
+  let lower_y = -y;
+
+  Ok(
+    (lower_x..=x)
+      .cartesian_product(lower_y..=y)
+      .filter(|(x, y)| target.trajectory_hits(V(*x, *y)))
+      .count(),
+  )
+}
 
-  (lower_x..=x)
-    .cartesian_product(lower_y..=y)
-    .filter(|(x, y)| target.trajectory_hits(V(*x, *y)))
-    .count()
+pub struct Day;
+
+impl Solution for Day {
+  const YEAR: u32 = 2021;
+  const DAY: u32 = 17;
+  type Input<'a> = Input;
+  type Output1 = usize;
+  type Output2 = usize;
+
+  fn parse(input: &str) -> Result<Self::Input<'_>> {
+    parse(input)
+  }
+
+  fn part1(input: Self::Input<'_>) -> Result<usize> {
+    p1(input)
+  }
+
+  fn part2(input: Self::Input<'_>) -> Result<usize> {
+    p2(input)
+  }
 }
 
 #[cfg(test)]
 mod test {
   use super::*;
 
-  const TEST_INPUT: &'static str = "target area: x=20..30, y=-10..-5";
+  const TEST_INPUT: &str = "target area: x=20..30, y=-10..-5";
 
   #[test]
   fn test_parse() {
     let input = TEST_INPUT;
     assert_eq!(
       parse(input).unwrap(),
-      (
-        "",
-        Target {
-          xmin: 20,
-          xmax: 30,
-          ymin: -10,
-          ymax: -5
-        }
-      )
+      Target {
+        xmin: 20,
+        xmax: 30,
+        ymin: -10,
+        ymax: -5
+      }
     )
   }
 
   #[test]
   fn test_p1() {
     let input = TEST_INPUT;
-    let parsed = parse(input).unwrap().1;
-    assert_eq!(p1(parsed), 45);
+    let parsed = parse(input).unwrap();
+    assert_eq!(p1(parsed), Ok(45));
 
     let input = std::fs::read_to_string("./inputs/d17.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
-    assert_eq!(p1(parsed), 7750);
+    let parsed = parse(&input).unwrap();
+    assert_eq!(p1(parsed), Ok(7750));
   }
 
   #[test]
   fn test_p2() {
     let input = TEST_INPUT;
-    let parsed = parse(input).unwrap().1;
+    let parsed = parse(input).unwrap();
 
     assert!(parsed.trajectory_hits(V(6, 7)));
-    assert_eq!(p2(parsed), 112);
+    assert_eq!(p2(parsed), Ok(112));
 
     let input = std::fs::read_to_string("./inputs/d17.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
-    assert_eq!(p2(parsed), 4120);
+    let parsed = parse(&input).unwrap();
+    assert_eq!(p2(parsed), Ok(4120));
+  }
+
+  #[test]
+  fn test_no_solution() {
+    let parsed = parse("target area: x=30..20, y=-10..-5").unwrap();
+    assert_eq!(p1(parsed), Err(Error::NoSolution));
   }
 }
@@ -2,7 +2,9 @@
 
 use itertools::Itertools;
 
-use crate::nom_prelude::*;
+use aoc_common::error::Result;
+use aoc_common::nom_prelude::*;
+use aoc_common::solution::Solution;
 
 type Input = Vec<SnailFishNum>;
 
@@ -13,7 +15,7 @@ pub enum SnailFishNum {
 }
 
 impl SnailFishNum {
-  fn magnitude(&self) -> u32 {
+  pub fn magnitude(&self) -> u32 {
     match self {
       Self::Regular(u) => *u,
       Self::Pair(left, right) => 3 * left.magnitude() + 2 * right.magnitude(),
@@ -92,6 +94,64 @@ impl SnailFishNum {
     }
   }
 
+  /// The path to the pair `explode` would explode next.
+  fn explode_path(&self, path: &mut Vec<Side>) -> bool {
+    match self {
+      Self::Regular(_) => false,
+      Self::Pair(..) if path.len() == 4 => true,
+      Self::Pair(left, right) => {
+        for (side, n) in [(Side::L, left), (Side::R, right)] {
+          path.push(side);
+          if n.explode_path(path) {
+            return true;
+          }
+          path.pop();
+        }
+        false
+      }
+    }
+  }
+
+  /// The number `split` would split next.
+  fn split_value(&self) -> Option<u32> {
+    match self {
+      Self::Regular(u) => Some(*u).filter(|&u| u >= 10),
+      Self::Pair(left, right) => left.split_value().or_else(|| right.split_value()),
+    }
+  }
+
+  /// Like `reduce`, but keeps every number along the way.
+  pub fn reduce_trace(&mut self) -> Vec<Step> {
+    let mut steps = Vec::new();
+    loop {
+      let mut path = Vec::new();
+      let action = if self.explode_path(&mut path) {
+        self.explode(0);
+        Action::Explode(path)
+      } else if let Some(u) = self.split_value() {
+        self.split();
+        Action::Split(u)
+      } else {
+        return steps;
+      };
+      steps.push(Step {
+        action,
+        number: self.clone(),
+      });
+    }
+  }
+
+  /// Like `+`, but keeps every number along the way, starting with the unreduced sum.
+  pub fn add_trace(self, rhs: Self) -> Vec<Step> {
+    let mut n = SnailFishNum::Pair(Box::new(self), Box::new(rhs));
+    let mut steps = vec![Step {
+      action: Action::Addition,
+      number: n.clone(),
+    }];
+    steps.append(&mut n.reduce_trace());
+    steps
+  }
+
   pub fn reduce(&mut self) {
     loop {
       let (_, _, reduced) = self.explode(0);
@@ -122,48 +182,228 @@ impl std::fmt::Display for SnailFishNum {
   }
 }
 
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Side {
+  L,
+  R,
+}
+
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub enum Action {
+  Addition,
+  /// The pair at this path from the root.
+  Explode(Vec<Side>),
+  /// This regular number.
+  Split(u32),
+}
+
+impl std::fmt::Display for Action {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::Addition => write!(f, "addition"),
+      Self::Explode(path) => {
+        write!(f, "explode at ")?;
+        path.iter().try_for_each(|side| write!(f, "{:?}", side))
+      }
+      Self::Split(u) => write!(f, "split of {}", u),
+    }
+  }
+}
+
+/// A number in the middle of being reduced, and how it got there.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct Step {
+  pub action: Action,
+  pub number: SnailFishNum,
+}
+
+impl std::fmt::Display for Step {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "after {}: {}", self.action, self.number)
+  }
+}
+
+/// A snailfish number as its regular numbers from left to right, each with the number of pairs around it.  The
+/// pairs themselves are implied: two neighbours at the same depth, with nothing deeper between them, are a pair.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct FlatSnailFishNum(Vec<(u32, u8)>);
+
+impl From<&SnailFishNum> for FlatSnailFishNum {
+  fn from(n: &SnailFishNum) -> Self {
+    fn flatten(n: &SnailFishNum, depth: u8, out: &mut Vec<(u32, u8)>) {
+      match n {
+        SnailFishNum::Regular(u) => out.push((*u, depth)),
+        SnailFishNum::Pair(left, right) => {
+          flatten(left, depth + 1, out);
+          flatten(right, depth + 1, out);
+        }
+      }
+    }
+    let mut out = Vec::new();
+    flatten(n, 0, &mut out);
+    Self(out)
+  }
+}
+
+impl FlatSnailFishNum {
+  /// Rebuilds the tree bottom up, combining the top two of a stack whenever they are at the same depth.
+  fn fold<T>(&self, regular: impl Fn(u32) -> T, pair: impl Fn(T, T) -> T) -> T {
+    let mut stack: Vec<(T, u8)> = Vec::new();
+    for &(u, depth) in &self.0 {
+      let (mut t, mut depth) = (regular(u), depth);
+      while let Some((left, _)) = stack.pop_if(|(_, d)| *d == depth) {
+        t = pair(left, t);
+        depth -= 1;
+      }
+      stack.push((t, depth));
+    }
+    stack.pop().unwrap().0
+  }
+
+  pub fn magnitude(&self) -> u32 {
+    self.fold(|u| u, |left, right| 3 * left + 2 * right)
+  }
+
+  /// Explodes the pair whose left number is at `i`.
+  fn explode(&mut self, i: usize) {
+    let (left, depth) = self.0[i];
+    let (right, _) = self.0.remove(i + 1);
+    if i > 0 {
+      self.0[i - 1].0 += left;
+    }
+    if let Some((u, _)) = self.0.get_mut(i + 1) {
+      *u += right;
+    }
+    self.0[i] = (0, depth - 1);
+  }
+
+  /// Adding reduced numbers only ever nests by one more pair, so everything too deep is exploded in one sweep, and a
+  /// split need only explode its own pair.
+  pub fn reduce(&mut self) {
+    let mut i = 0;
+    while i < self.0.len() {
+      if self.0[i].1 > 4 {
+        self.explode(i);
+      }
+      i += 1;
+    }
+    while let Some(i) = self.0.iter().position(|&(u, _)| u >= 10) {
+      let (u, depth) = self.0[i];
+      self.0[i] = (u / 2, depth + 1);
+      self.0.insert(i + 1, (u - u / 2, depth + 1));
+      if depth + 1 > 4 {
+        self.explode(i);
+      }
+    }
+  }
+}
+
+impl std::ops::Add for FlatSnailFishNum {
+  type Output = FlatSnailFishNum;
+  fn add(mut self, rhs: Self) -> Self::Output {
+    self.0.extend(rhs.0);
+    for (_, depth) in &mut self.0 {
+      *depth += 1;
+    }
+    self.reduce();
+    self
+  }
+}
+
+impl std::fmt::Display for FlatSnailFishNum {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let s = self.fold(
+      |u| u.to_string(),
+      |left, right| format!("[{},{}]", left, right),
+    );
+    write!(f, "{}", s)
+  }
+}
+
 pub fn parse_regular(input: &str) -> IResult<&str, SnailFishNum> {
   map(parse_u32, SnailFishNum::Regular)(input)
 }
 
-pub fn parse_pair(input: &str) -> IResult<&str, SnailFishNum> {
-  map(
-    delimited(
-      tag("["),
-      separated_pair(parse_snail_fish_num, tag(","), parse_snail_fish_num),
-      tag("]"),
-    ),
-    |(n1, n2)| SnailFishNum::Pair(Box::new(n1), Box::new(n2)),
-  )(input)
+/// A pair whose own pairs are nested at most `depth` deep.
+pub fn parse_pair(depth: u8) -> impl Fn(&str) -> IResult<&str, SnailFishNum> {
+  move |input| {
+    map(
+      delimited(
+        tag("["),
+        separated_pair(
+          parse_snail_fish_num(depth),
+          tag(","),
+          parse_snail_fish_num(depth),
+        ),
+        tag("]"),
+      ),
+      |(n1, n2)| SnailFishNum::Pair(Box::new(n1), Box::new(n2)),
+    )(input)
+  }
 }
 
-pub fn parse_snail_fish_num(input: &str) -> IResult<&str, SnailFishNum> {
-  alt((parse_regular, parse_pair))(input)
+/// A number whose pairs are nested at most `depth` deep.  A pair any deeper fails rather than errors.
+pub fn parse_snail_fish_num(depth: u8) -> impl Fn(&str) -> IResult<&str, SnailFishNum> {
+  move |input| match depth {
+    0 => cut(parse_regular)(input),
+    _ => alt((parse_regular, parse_pair(depth - 1)))(input),
+  }
 }
 
-pub fn parse(input: &str) -> IResult<&str, Input> {
-  lines_of(parse_snail_fish_num)(input)
+/// Only reduced numbers, with no pair inside four others; exploding relies on it.
+pub fn parse(input: &str) -> Result<Input> {
+  parse_all(lines_of(parse_snail_fish_num(4)))(input)
 }
 
-pub fn p1(input: Input) -> u32 {
-  let sum = input.into_iter().reduce(std::ops::Add::add).unwrap();
-  sum.magnitude()
+pub fn p1(input: Input) -> Result<u32> {
+  let sum = input
+    .iter()
+    .map(FlatSnailFishNum::from)
+    .reduce(std::ops::Add::add)
+    .unwrap();
+  Ok(sum.magnitude())
 }
 
-pub fn p2(input: Input) -> u32 {
-  input
-    .iter()
-    .cartesian_product(input.iter())
-    .map(|(x, y)| (x.clone() + y.clone()).magnitude())
-    .max()
-    .unwrap()
+pub fn p2(input: Input) -> Result<u32> {
+  let flat = input.iter().map(FlatSnailFishNum::from).collect_vec();
+  Ok(
+    flat
+      .iter()
+      .cartesian_product(flat.iter())
+      .map(|(x, y)| (x.clone() + y.clone()).magnitude())
+      .max()
+      .unwrap(),
+  )
+}
+
+pub struct Day;
+
+impl Solution for Day {
+  const YEAR: u32 = 2021;
+  const DAY: u32 = 18;
+  type Input<'a> = Input;
+  type Output1 = u32;
+  type Output2 = u32;
+
+  fn parse(input: &str) -> Result<Self::Input<'_>> {
+    parse(input)
+  }
+
+  fn part1(input: Self::Input<'_>) -> Result<u32> {
+    p1(input)
+  }
+
+  fn part2(input: Self::Input<'_>) -> Result<u32> {
+    p2(input)
+  }
 }
 
 #[cfg(test)]
 mod test {
   use super::*;
+  use aoc_common::error::Error;
 
-  const TEST_INPUT: &'static str = "[[[0,[5,8]],[[1,7],[9,6]]],[[4,[1,2]],[[1,4],2]]]
+  const TEST_INPUT: &str = "[[[0,[5,8]],[[1,7],[9,6]]],[[4,[1,2]],[[1,4],2]]]
   [[[5,[2,8]],4],[5,[[9,9],0]]]
   [6,[[[6,2],[5,6]],[[7,6],[4,7]]]]
   [[[6,[0,7]],[0,9]],[4,[9,[9,0]]]]
@@ -183,32 +423,152 @@ mod test {
     [[[[1,2],[3,4]],[[5,6],[7,8]]],9]
     [[[9,[3,8]],[[0,9],6]],[[[3,7],[4,9]],3]]
     [[[[1,3],[5,3]],[[1,3],[8,7]]],[[[4,9],[6,9]],[[8,2],[7,3]]]]";
-    let parsed = parse(input).unwrap().1;
+    let parsed = parse(input).unwrap();
     for (i, line) in input.lines().enumerate() {
       let line = line.trim();
       assert_eq!(&format!("{}", parsed[i]), line);
     }
   }
 
+  #[test]
+  fn test_flat() {
+    let input = std::fs::read_to_string("./inputs/d18.txt").unwrap();
+    let trees = parse(&input).unwrap();
+    let flats = trees.iter().map(FlatSnailFishNum::from).collect_vec();
+    for (tree, flat) in trees.iter().zip(&flats) {
+      assert_eq!(flat.to_string(), tree.to_string());
+      assert_eq!(flat.magnitude(), tree.magnitude());
+    }
+    for ((x, y), (fx, fy)) in trees
+      .iter()
+      .cartesian_product(&trees)
+      .zip(flats.iter().cartesian_product(&flats))
+    {
+      let sum = x.clone() + y.clone();
+      let flat_sum = fx.clone() + fy.clone();
+      assert_eq!(flat_sum, FlatSnailFishNum::from(&sum));
+      assert_eq!(flat_sum.to_string(), sum.to_string());
+    }
+
+    let parsed = parse("[[[[4,3],4],4],[7,[[8,4],9]]]\n[1,1]").unwrap();
+    let sum = FlatSnailFishNum::from(&parsed[0]) + FlatSnailFishNum::from(&parsed[1]);
+    assert_eq!(sum.to_string(), "[[[[0,7],4],[[7,8],[6,0]]],[8,1]]");
+    assert_eq!(
+      FlatSnailFishNum::from(&SnailFishNum::Regular(12)).to_string(),
+      "12"
+    );
+
+    // As deep as a reduced number goes, on either side.
+    let nines = "[[[[9,9],[9,9]],[[9,9],[9,9]]],[[[9,9],[9,9]],[[9,9],[9,9]]]]";
+    for input in [
+      "[1,1]\n[1,[1,[1,[2,3]]]]".to_owned(),
+      "[[[[1,2],3],4],5]\n[1,1]".to_owned(),
+      format!("{}\n{}", nines, nines),
+    ] {
+      let parsed = parse(&input).unwrap();
+      let sum = parsed[0].clone() + parsed[1].clone();
+      let flat_sum = FlatSnailFishNum::from(&parsed[0]) + FlatSnailFishNum::from(&parsed[1]);
+      assert_eq!(flat_sum, FlatSnailFishNum::from(&sum));
+      assert_eq!(flat_sum.magnitude(), sum.magnitude());
+    }
+    // Any deeper isn't reduced, and neither version can add it.
+    assert_eq!(
+      parse("[1,1]\n[1,[1,[1,[1,[2,3]]]]]"),
+      Err(Error::Parse {
+        line: 2,
+        column: 13,
+        snippet: "[2,3]]]]]".to_owned()
+      })
+    );
+    assert_eq!(
+      parse("[[[[[[1,2],3],4],5],6],7]\n[1,1]"),
+      Err(Error::Parse {
+        line: 1,
+        column: 5,
+        snippet: "[[1,2],3],4],5],6],7]".to_owned()
+      })
+    );
+  }
+
+  #[test]
+  fn test_trace() {
+    let parsed = parse("[[[[4,3],4],4],[7,[[8,4],9]]]\n[1,1]").unwrap();
+    let steps = parsed[0].clone().add_trace(parsed[1].clone());
+    let trace = steps.iter().map(|s| s.to_string()).collect_vec();
+    assert_eq!(
+      trace,
+      [
+        "after addition: [[[[[4,3],4],4],[7,[[8,4],9]]],[1,1]]",
+        "after explode at LLLL: [[[[0,7],4],[7,[[8,4],9]]],[1,1]]",
+        "after explode at LRRL: [[[[0,7],4],[15,[0,13]]],[1,1]]",
+        "after split of 15: [[[[0,7],4],[[7,8],[0,13]]],[1,1]]",
+        "after split of 13: [[[[0,7],4],[[7,8],[0,[6,7]]]],[1,1]]",
+        "after explode at LRRR: [[[[0,7],4],[[7,8],[6,0]]],[8,1]]",
+      ]
+    );
+    assert_eq!(
+      steps.last().unwrap().number,
+      parsed[0].clone() + parsed[1].clone()
+    );
+    assert_eq!(parsed[1].clone().reduce_trace(), vec![]);
+  }
+
+  /// `cargo test --release -p aoc2021 bench_flat -- --ignored --nocapture`
+  #[test]
+  #[ignore]
+  fn bench_flat() {
+    let input = std::fs::read_to_string("./inputs/d18.txt").unwrap();
+    let trees = parse(&input).unwrap();
+    let flats = trees.iter().map(FlatSnailFishNum::from).collect_vec();
+    let runs = 10;
+
+    let start = std::time::Instant::now();
+    for _ in 0..runs {
+      let best = trees
+        .iter()
+        .cartesian_product(&trees)
+        .map(|(x, y)| (x.clone() + y.clone()).magnitude())
+        .max();
+      assert_eq!(best, Some(4909));
+    }
+    let tree_time = start.elapsed() / runs;
+
+    let start = std::time::Instant::now();
+    for _ in 0..runs {
+      let best = flats
+        .iter()
+        .cartesian_product(&flats)
+        .map(|(x, y)| (x.clone() + y.clone()).magnitude())
+        .max();
+      assert_eq!(best, Some(4909));
+    }
+    let flat_time = start.elapsed() / runs;
+
+    println!(
+      "every pair's sum: tree {:?}, flat {:?}",
+      tree_time, flat_time
+    );
+  }
+
   #[test]
   fn test_p1() {
     let input = TEST_INPUT;
-    let parsed = parse(input).unwrap().1;
-    assert_eq!(p1(parsed), 4140);
+    let parsed = parse(input).unwrap();
+    assert_eq!(p1(parsed), Ok(4140));
 
     let input = std::fs::read_to_string("./inputs/d18.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
-    assert_eq!(p1(parsed), 3892);
+    let parsed = parse(&input).unwrap();
+    assert_eq!(p1(parsed), Ok(3892));
   }
 
   #[test]
   fn test_p2() {
     let input = TEST_INPUT;
-    let parsed = parse(input).unwrap().1;
-    assert_eq!(p2(parsed), 3993);
+    let parsed = parse(input).unwrap();
+    assert_eq!(p2(parsed), Ok(3993));
 
     let input = std::fs::read_to_string("./inputs/d18.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
-    assert_eq!(p2(parsed), 4909);
+    let parsed = parse(&input).unwrap();
+    assert_eq!(p2(parsed), Ok(4909));
   }
 }
@@ -2,48 +2,117 @@
 
 use itertools::Itertools;
 
-use crate::nom_prelude::*;
-use crate::vector::V3;
-use std::collections::HashSet;
-
-// lol all this because I didn't want to do the rotations by hand (and was reminded that group generators for non abelian groups are not quite as helpful for getting all elements as I thought)
-#[derive(Debug, Eq, PartialEq, Copy, Clone, Hash)]
-pub struct RotMatrix((usize, bool), (usize, bool), (usize, bool));
-
-impl RotMatrix {
-  fn apply(self, v: V3) -> V3 {
-    let x = v.get(self.0 .0) * if self.0 .1 { 1 } else { -1 };
-    let y = v.get(self.1 .0) * if self.1 .1 { 1 } else { -1 };
-    let z = v.get(self.2 .0) * if self.2 .1 { 1 } else { -1 };
-    V3(x, y, z)
+use aoc_common::error::{Error, Result};
+use aoc_common::nom_prelude::*;
+use aoc_common::solution::Solution;
+use aoc_common::vector::{Transform, V3};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// The offset between two beacons with its signs and order thrown away, which no rotation changes.
+pub type Fingerprint = [i32; 3];
+
+fn fingerprint(a: V3, b: V3) -> Fingerprint {
+  let d = a - b;
+  let mut f = [d.0.abs(), d.1.abs(), d.2.abs()];
+  f.sort_unstable();
+  f
+}
+
+/// The pairs of readings with each fingerprint.
+pub type Fingerprints = HashMap<Fingerprint, Vec<(usize, usize)>>;
+
+/// What it takes for two scanners to be accepted as overlapping.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct AlignConfig {
+  /// Beacons both scanners must see.
+  pub shared_beacons: usize,
+  /// How far a scanner sees along each axis.  A beacon one scanner reports inside the other's range must be
+  /// reported by the other too.
+  pub range: i32,
+}
+
+impl Default for AlignConfig {
+  fn default() -> Self {
+    Self {
+      shared_beacons: 12,
+      range: 1000,
+    }
   }
+}
 
-  fn det(self) -> i32 {
-    let sign_from_first = if self.0 .0 == 1 { -1 } else { 1 };
-    let sign_from_sub = if self.1 .0 < self.2 .0 { 1 } else { -1 };
-    let x = if self.0 .1 { 1 } else { -1 };
-    let y = if self.1 .1 { 1 } else { -1 };
-    let z = if self.2 .1 { 1 } else { -1 };
-    sign_from_first * sign_from_sub * x * y * z
+impl AlignConfig {
+  /// The shared beacons make this many shared pairs, so this many shared fingerprints.
+  fn shared_pairs(&self) -> usize {
+    self.shared_beacons * self.shared_beacons.saturating_sub(1) / 2
   }
 
-  pub fn all() -> Vec<Self> {
-    let bools = (0..3)
-      .map(|_| [true, false].into_iter())
-      .multi_cartesian_product();
-    (0..=2)
-      .permutations(3)
-      .cartesian_product(bools)
-      .map(|(vals, bools)| {
-        RotMatrix(
-          (vals[0], bools[0]),
-          (vals[1], bools[1]),
-          (vals[2], bools[2]),
-        )
-      })
-      // Rotations are all the transformations with determinant 1.
-      .filter(|m| m.det() == 1)
-      .collect_vec()
+  fn sees(&self, scanner: V3, beacon: V3) -> bool {
+    let d = beacon - scanner;
+    d.0.abs() <= self.range && d.1.abs() <= self.range && d.2.abs() <= self.range
+  }
+}
+
+/// Every scanner placed relative to scanner 0, and which alignments placed them.
+#[derive(Debug, Eq, PartialEq, Clone)]
+pub struct ScannerGraph {
+  /// Indexed like the scanners.
+  pub placements: Vec<Transform>,
+  /// `(i, j)` where scanner `j` was placed by aligning it with scanner `i`.
+  pub edges: Vec<(usize, usize)>,
+}
+
+impl ScannerGraph {
+  /// Aligns the scanners outwards from scanner 0, only trying pairs with enough fingerprints in common.  `None`
+  /// if there are no scanners or some scanner can't be reached.
+  pub fn build(scanners: &[Scanner]) -> Option<Self> {
+    Self::build_with(scanners, &AlignConfig::default())
+  }
+
+  pub fn build_with(scanners: &[Scanner], config: &AlignConfig) -> Option<Self> {
+    let fingerprints = scanners.iter().map(Scanner::fingerprints).collect_vec();
+    let mut placements = vec![None; scanners.len()];
+    *placements.first_mut()? = Some(Transform::IDENTITY);
+    let mut edges = Vec::new();
+    let mut queue = VecDeque::from([0]);
+    while let Some(i) = queue.pop_front() {
+      for j in 0..scanners.len() {
+        if placements[j].is_some() {
+          continue;
+        }
+        // Each fingerprint can pair up at most as many times as the scarcer side has it.
+        let shared = fingerprints[i]
+          .iter()
+          .filter_map(|(f, mine)| Some(mine.len().min(fingerprints[j].get(f)?.len())))
+          .sum::<usize>();
+        if shared < config.shared_pairs() {
+          continue;
+        }
+        let local = scanners[i].align_by(&fingerprints[i], &scanners[j], &fingerprints[j], config);
+        if let Some(local) = local {
+          placements[j] = Some(placements[i].unwrap() * local);
+          edges.push((i, j));
+          queue.push_back(j);
+        }
+      }
+    }
+    Some(Self {
+      placements: placements.into_iter().collect::<Option<_>>()?,
+      edges,
+    })
+  }
+
+  /// Where scanner `j` is in scanner `i`'s frame, whether or not they overlap.
+  pub fn relative(&self, i: usize, j: usize) -> Transform {
+    self.placements[i].inverse() * self.placements[j]
+  }
+
+  /// Every beacon, in scanner 0's frame.
+  pub fn beacons(&self, scanners: &[Scanner]) -> HashSet<V3> {
+    scanners
+      .iter()
+      .zip(&self.placements)
+      .flat_map(|(s, p)| s.readings.iter().map(|&r| p.apply(r)))
+      .collect()
   }
 }
 
@@ -54,33 +123,85 @@ pub struct Scanner {
 }
 
 impl Scanner {
-  pub fn rotate_and_translate(&mut self, rotation: &RotMatrix, translation: V3) {
-    self.readings = self
+  pub fn transform(&mut self, transform: &Transform) {
+    self.readings = self.readings.iter().map(|&p| transform.apply(p)).collect()
+  }
+
+  pub fn fingerprints(&self) -> Fingerprints {
+    let mut fingerprints = Fingerprints::new();
+    for ((i, &a), (j, &b)) in self.readings.iter().enumerate().tuple_combinations() {
+      fingerprints
+        .entry(fingerprint(a, b))
+        .or_default()
+        .push((i, j));
+    }
+    fingerprints
+  }
+
+  /// Where `other` is in `self`'s frame, if they see enough of the same beacons.
+  pub fn align(&self, other: &Self) -> Option<Transform> {
+    self.align_with(other, &AlignConfig::default())
+  }
+
+  pub fn align_with(&self, other: &Self, config: &AlignConfig) -> Option<Transform> {
+    self.align_by(&self.fingerprints(), other, &other.fingerprints(), config)
+  }
+
+  /// Whether `other`, placed so, agrees with `self`: enough beacons line up, and neither reports a beacon within
+  /// the other's range that the other doesn't.
+  fn verify(
+    &self,
+    readings: &HashSet<V3>,
+    other: &Self,
+    placement: Transform,
+    config: &AlignConfig,
+  ) -> bool {
+    let origin = V3(0, 0, 0);
+    let placed = other
       .readings
       .iter()
-      .map(|p| rotation.apply(*p) + translation)
-      .collect()
+      .map(|&r| placement.apply(r))
+      .collect::<HashSet<_>>();
+    let matches = placed.iter().filter(|r| readings.contains(r)).count();
+    matches >= config.shared_beacons
+      && placed
+        .iter()
+        .all(|&r| readings.contains(&r) || !config.sees(origin, r))
+      && readings
+        .iter()
+        .all(|&r| placed.contains(&r) || !config.sees(placement.translation, r))
   }
-  /// If successful, returns the displacement from self to other and the rotation
-  /// needed to get other to orient other with self.
-  pub fn overlaps(&self, other: &Self) -> Option<(V3, RotMatrix)> {
-    for (other_pts, rot) in other.rotations().into_iter() {
-      for p in &self.readings {
-        for other_p in &other_pts {
-          let transform = *other_p - *p;
-          let transformed = other_pts
-            .iter()
-            .map(|other_p| *other_p - transform)
-            .collect::<HashSet<_>>();
-          if self
-            .readings
-            .iter()
-            .filter(|p| transformed.contains(p))
-            .count()
-            >= 12
-          {
-            // Probably can get away without checking that no other coordinates are in the box of matches; TODO: should check that though.
-            return Some((transform, rot));
+
+  /// Each pair of readings that `self` and `other` fingerprint alike could be the same two beacons.  Such a pair
+  /// only lines up under a rotation or two, and each of those gives a placement to test.
+  fn align_by(
+    &self,
+    mine: &Fingerprints,
+    other: &Self,
+    theirs: &Fingerprints,
+    config: &AlignConfig,
+  ) -> Option<Transform> {
+    let readings = self.readings.iter().copied().collect::<HashSet<_>>();
+    let rotations = Transform::rotations();
+    for (f, pairs) in mine {
+      let Some(their_pairs) = theirs.get(f) else {
+        continue;
+      };
+      for (&(a, b), &(c, d)) in pairs.iter().cartesian_product(their_pairs) {
+        let (a, b) = (self.readings[a], self.readings[b]);
+        for (c, d) in [(c, d), (d, c)] {
+          let (c, d) = (other.readings[c], other.readings[d]);
+          for &rotation in &rotations {
+            if rotation.apply(d - c) != b - a {
+              continue;
+            }
+            let placement = Transform {
+              translation: a - rotation.apply(c),
+              ..rotation
+            };
+            if self.verify(&readings, other, placement, config) {
+              return Some(placement);
+            }
           }
         }
       }
@@ -89,8 +210,8 @@ impl Scanner {
   }
 
   /// Rotational symmetries of a cube/octahedron
-  pub fn rotations(&self) -> Vec<(Vec<V3>, RotMatrix)> {
-    RotMatrix::all()
+  pub fn rotations(&self) -> Vec<(Vec<V3>, Transform)> {
+    Transform::rotations()
       .into_iter()
       .map(|m| {
         let vs = self.readings.iter().map(|r| m.apply(*r)).collect_vec();
@@ -121,81 +242,48 @@ pub fn parse_scanner(input: &str) -> IResult<&str, Scanner> {
   })(input)
 }
 
-pub fn parse(input: &str) -> IResult<&str, Input> {
-  lines_of(parse_scanner)(input)
+pub fn parse(input: &str) -> Result<Input> {
+  parse_all(lines_of(parse_scanner))(input)
 }
 
-// TODO: I'm brute forcing all of this; do it for real with speed next time.
-
-pub fn p1(input: Input) -> usize {
-  // Start with our first one as the reference frame.
-  let mut assigned = input.iter().take(1).cloned().collect_vec();
-  let mut unassigned = input.into_iter().skip(1).collect_vec();
-
-  // This is gross.
-  let mut assigned_i = 0; // once this reaches the end, we have made it.
-  while !unassigned.is_empty() {
-    let mut unassigned_i = 0;
-    while unassigned_i < unassigned.len() {
-      let unassigned_scanner = &unassigned[unassigned_i];
-      let assigned_scanner = &assigned[assigned_i];
-      println!("{} {}", assigned_scanner.id, unassigned_scanner.id);
-      if let Some((trans, rot)) = assigned_scanner.overlaps(&unassigned_scanner) {
-        let mut new_assigned = unassigned_scanner.clone();
-        drop(unassigned_scanner);
-        unassigned.remove(unassigned_i);
-        // because we persist the scanners wrt 0 reference frame, we can just do the transformation
-        new_assigned.rotate_and_translate(&rot, V3(0, 0, 0) - trans);
-        assigned.push(new_assigned);
-      } else {
-        unassigned_i = unassigned_i + 1;
-      }
-    }
-    assigned_i = assigned_i + 1;
-  }
+pub fn p1(input: Input) -> Result<usize> {
+  let graph = ScannerGraph::build(&input).ok_or(Error::NoSolution)?;
+  Ok(graph.beacons(&input).len())
+}
 
-  assigned
-    .into_iter()
-    .flat_map(|s| s.readings)
-    .unique()
-    .count()
+pub fn p2(input: Input) -> Result<usize> {
+  let graph = ScannerGraph::build(&input).ok_or(Error::NoSolution)?;
+  Ok(
+    graph
+      .placements
+      .iter()
+      .tuple_combinations()
+      .map(|(x, y)| x.translation.manhattan(y.translation))
+      .max()
+      .unwrap_or(0) as usize,
+  )
 }
 
-pub fn p2(input: Input) -> usize {
-  // Start with our first one as the reference frame.
-  let mut assigned = input.iter().take(1).cloned().collect_vec();
-  let mut unassigned = input.into_iter().skip(1).collect_vec();
-
-  let mut translations = vec![V3(0, 0, 0)];
-  // This is gross.
-  let mut assigned_i = 0; // once this reaches the end, we have made it.
-  while !unassigned.is_empty() {
-    let mut unassigned_i = 0;
-    while unassigned_i < unassigned.len() {
-      let unassigned_scanner = &unassigned[unassigned_i];
-      let assigned_scanner = &assigned[assigned_i];
-      println!("{} {}", assigned_scanner.id, unassigned_scanner.id);
-      if let Some((trans, rot)) = assigned_scanner.overlaps(&unassigned_scanner) {
-        translations.push(trans);
-        let mut new_assigned = unassigned_scanner.clone();
-        drop(unassigned_scanner);
-        unassigned.remove(unassigned_i);
-        // because we persist the scanners wrt 0 reference frame, we can just do the transformation
-        new_assigned.rotate_and_translate(&rot, V3(0, 0, 0) - trans);
-        assigned.push(new_assigned);
-      } else {
-        unassigned_i = unassigned_i + 1;
-      }
-    }
-    assigned_i = assigned_i + 1;
+pub struct Day;
+
+impl Solution for Day {
+  const YEAR: u32 = 2021;
+  const DAY: u32 = 19;
+  type Input<'a> = Input;
+  type Output1 = usize;
+  type Output2 = usize;
+
+  fn parse(input: &str) -> Result<Self::Input<'_>> {
+    parse(input)
+  }
+
+  fn part1(input: Self::Input<'_>) -> Result<usize> {
+    p1(input)
   }
 
-  translations
-    .into_iter()
-    .tuple_combinations()
-    .map(|(x, y)| x.manhattan(y))
-    .max()
-    .unwrap() as usize
+  fn part2(input: Self::Input<'_>) -> Result<usize> {
+    p2(input)
+  }
 }
 
 #[cfg(test)]
@@ -215,7 +303,7 @@ mod test {
 4,5,6
 ";
     assert_eq!(
-      parse(input).unwrap().1,
+      parse(input).unwrap(),
       vec![
         Scanner {
           id: 0,
@@ -260,25 +348,131 @@ mod test {
       .for_each(|(_, c)| assert_eq!(c, 3));
   }
 
+  #[test]
+  fn test_fingerprints() {
+    let input = std::fs::read_to_string("./inputs/d19-test.txt").unwrap();
+    let scanners = parse(&input).unwrap();
+    let fingerprints = scanners[0].fingerprints();
+    for (readings, _) in scanners[0].rotations() {
+      let rotated = Scanner { id: 0, readings };
+      assert_eq!(rotated.fingerprints(), fingerprints);
+    }
+  }
+
+  #[test]
+  fn test_align() {
+    let input = std::fs::read_to_string("./inputs/d19-test.txt").unwrap();
+    let scanners = parse(&input).unwrap();
+    let placement = scanners[0].align(&scanners[1]).unwrap();
+    assert_eq!(placement.translation, V3(68, -1246, -43));
+    // One of the beacons both can see.
+    assert_eq!(placement.apply(V3(686, 422, 578)), V3(-618, -824, -621));
+    assert_eq!(scanners[0].align(&scanners[2]), None);
+
+    let graph = ScannerGraph::build(&scanners).unwrap();
+    let positions = graph.placements.iter().map(|p| p.translation).collect_vec();
+    assert_eq!(
+      positions,
+      vec![
+        V3(0, 0, 0),
+        V3(68, -1246, -43),
+        V3(1105, -1205, 1229),
+        V3(-92, -2380, -20),
+        V3(-20, -1133, 1061)
+      ]
+    );
+    assert_eq!(graph.edges.len(), 4);
+    assert_eq!(graph.edges[0], (0, 1));
+    for &(i, j) in &graph.edges {
+      assert_eq!(scanners[i].align(&scanners[j]), Some(graph.relative(i, j)));
+      assert_eq!(scanners[j].align(&scanners[i]), Some(graph.relative(j, i)));
+    }
+    let mut moved = scanners[4].clone();
+    moved.transform(&graph.relative(2, 4));
+    assert!(moved
+      .readings
+      .iter()
+      .any(|r| scanners[2].readings.contains(r)));
+
+    let lost = Scanner {
+      id: 5,
+      readings: vec![V3(0, 0, 0)],
+    };
+    assert_eq!(ScannerGraph::build(&[scanners[0].clone(), lost]), None);
+    assert_eq!(ScannerGraph::build(&[]), None);
+
+    // Evenly spaced in a row, so only 11 distinct fingerprints among the 66 pairs.
+    let row = (0..12).map(|i| V3(40 * i, 7, -3)).collect_vec();
+    let position = V3(500, 0, 0);
+    let scanner = |readings: Vec<V3>| Scanner { id: 0, readings };
+    let mine = scanner(row.clone());
+    let theirs = scanner(row.iter().map(|&b| b - position).collect());
+    assert_eq!(mine.fingerprints().len(), 11);
+    assert!(ScannerGraph::build(&[mine, theirs]).is_some());
+  }
+
+  #[test]
+  fn test_align_config() {
+    let beacons = (0..12)
+      .map(|i| V3(17 * i % 23, i * i, 5 - 3 * i))
+      .collect_vec();
+    let position = V3(500, 0, 0);
+    let scanner = |readings: Vec<V3>| Scanner { id: 0, readings };
+    let mine = scanner(beacons.clone());
+    let theirs = scanner(beacons.iter().map(|&b| b - position).collect());
+    let expected = Some(Transform::translation(position));
+    assert_eq!(mine.align(&theirs), expected);
+
+    // A beacon they see where we should have seen it too.
+    let mut extra = theirs.clone();
+    extra.readings.push(V3(900, 900, 900) - position);
+    assert_eq!(mine.align(&extra), None);
+    let short_sighted = AlignConfig {
+      range: 800,
+      ..AlignConfig::default()
+    };
+    assert_eq!(mine.align_with(&extra, &short_sighted), expected);
+
+    // And one we see where they should have.
+    let mut extra = mine.clone();
+    extra.readings.push(position + V3(-900, 0, 0));
+    assert_eq!(extra.align(&theirs), None);
+    assert_eq!(extra.align_with(&theirs, &short_sighted), expected);
+
+    let few = scanner(beacons[..6].to_vec());
+    assert_eq!(few.align(&theirs), None);
+    let lenient = AlignConfig {
+      shared_beacons: 6,
+      ..AlignConfig::default()
+    };
+    assert_eq!(few.align_with(&theirs, &lenient), None);
+    let lenient = AlignConfig {
+      shared_beacons: 6,
+      range: 30,
+    };
+    assert_eq!(few.align_with(&theirs, &lenient), expected);
+    assert!(ScannerGraph::build_with(&[few, theirs], &lenient).is_some());
+  }
+
   #[test]
   fn test_p1() {
     let input = std::fs::read_to_string("./inputs/d19-test.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
-    assert_eq!(p1(parsed), 79);
+    let parsed = parse(&input).unwrap();
+    assert_eq!(p1(parsed), Ok(79));
 
     let input = std::fs::read_to_string("./inputs/d19.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
-    assert_eq!(p1(parsed), 459);
+    let parsed = parse(&input).unwrap();
+    assert_eq!(p1(parsed), Ok(459));
   }
 
   #[test]
   fn test_p2() {
     let input = std::fs::read_to_string("./inputs/d19-test.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
-    assert_eq!(p2(parsed), 3621);
+    let parsed = parse(&input).unwrap();
+    assert_eq!(p2(parsed), Ok(3621));
 
     let input = std::fs::read_to_string("./inputs/d19.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
-    assert_eq!(p2(parsed), 19130);
+    let parsed = parse(&input).unwrap();
+    assert_eq!(p2(parsed), Ok(19130));
   }
 }
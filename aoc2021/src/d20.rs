@@ -1,6 +1,8 @@
 //! https://adventofcode.com/2021/day/20
 
-use crate::nom_prelude::*;
+use aoc_common::error::Result;
+use aoc_common::nom_prelude::*;
+use aoc_common::solution::Solution;
 use itertools::Itertools;
 use std::collections::HashSet;
 
@@ -20,13 +22,13 @@ impl ImageEnhance {
       let mut idx = 0;
       for y_prime in ((y - 1)..=(y + 1)).rev() {
         for x_prime in ((x - 1)..=(x + 1)).rev() {
-          if x_prime < xmin || x_prime > xmax || y_prime < ymin || y_prime > ymax {
-            if self.infinite_pixel_val {
-              idx = idx | (1 << i);
-            }
+          if (x_prime < xmin || x_prime > xmax || y_prime < ymin || y_prime > ymax)
+            && self.infinite_pixel_val
+          {
+            idx |= 1 << i;
           }
           if self.image.contains(&(x_prime, y_prime)) {
-            idx = idx | (1 << i);
+            idx |= 1 << i;
           }
           i += 1;
         }
@@ -81,7 +83,7 @@ impl std::fmt::Display for ImageEnhance {
           write!(f, ".")?;
         }
       }
-      write!(f, "\n")?;
+      writeln!(f)?;
     }
     Ok(())
   }
@@ -93,7 +95,7 @@ pub fn parse_bit(input: &str) -> IResult<&str, bool> {
   alt((map(tag("."), |_| false), map(tag("#"), |_| true)))(input)
 }
 
-pub fn parse(input: &str) -> IResult<&str, Input> {
+fn parse_input(input: &str) -> IResult<&str, Input> {
   let alg = map_res(many1(parse_bit), |xs| xs.try_into());
   let image = map(lines_of(many1(parse_bit)), |xxs| {
     xxs
@@ -124,17 +126,43 @@ pub fn parse(input: &str) -> IResult<&str, Input> {
   )(input)
 }
 
-pub fn p1(mut input: Input) -> usize {
+pub fn parse(input: &str) -> Result<Input> {
+  parse_all(parse_input)(input)
+}
+
+pub fn p1(mut input: Input) -> Result<usize> {
   input.step();
   input.step();
-  input.image.len()
+  Ok(input.image.len())
 }
 
-pub fn p2(mut input: Input) -> usize {
+pub fn p2(mut input: Input) -> Result<usize> {
   for _ in 0..50 {
     input.step();
   }
-  input.image.len()
+  Ok(input.image.len())
+}
+
+pub struct Day;
+
+impl Solution for Day {
+  const YEAR: u32 = 2021;
+  const DAY: u32 = 20;
+  type Input<'a> = Input;
+  type Output1 = usize;
+  type Output2 = usize;
+
+  fn parse(input: &str) -> Result<Self::Input<'_>> {
+    parse(input)
+  }
+
+  fn part1(input: Self::Input<'_>) -> Result<usize> {
+    p1(input)
+  }
+
+  fn part2(input: Self::Input<'_>) -> Result<usize> {
+    p2(input)
+  }
 }
 
 #[cfg(test)]
@@ -144,7 +172,7 @@ mod test {
   #[test]
   fn test_parse() {
     let input = std::fs::read_to_string("./inputs/d20-test.txt").unwrap();
-    let img_enh = parse(&input).unwrap().1;
+    let img_enh = parse(&input).unwrap();
     assert!(!img_enh.alg[0]);
     assert!(img_enh.alg[2]);
     assert!(img_enh.alg[511]);
@@ -159,22 +187,22 @@ mod test {
   #[test]
   fn test_p1() {
     let input = std::fs::read_to_string("./inputs/d20-test.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
-    assert_eq!(p1(parsed), 35);
+    let parsed = parse(&input).unwrap();
+    assert_eq!(p1(parsed), Ok(35));
 
     let input = std::fs::read_to_string("./inputs/d20.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
-    assert_eq!(p1(parsed), 5479);
+    let parsed = parse(&input).unwrap();
+    assert_eq!(p1(parsed), Ok(5479));
   }
 
   #[test]
   fn test_p2() {
     let input = std::fs::read_to_string("./inputs/d20-test.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
-    assert_eq!(p2(parsed), 3351);
+    let parsed = parse(&input).unwrap();
+    assert_eq!(p2(parsed), Ok(3351));
 
     let input = std::fs::read_to_string("./inputs/d20.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
-    assert_eq!(p2(parsed), 19012);
+    let parsed = parse(&input).unwrap();
+    assert_eq!(p2(parsed), Ok(19012));
   }
 }
@@ -1,6 +1,8 @@
 //! https://adventofcode.com/2021/day/21
 
-use crate::nom_prelude::*;
+use aoc_common::error::Result;
+use aoc_common::nom_prelude::*;
+use aoc_common::solution::Solution;
 
 #[derive(Debug, Eq, PartialEq, Clone)]
 pub struct DiracStart {
@@ -100,8 +102,8 @@ impl RealDiracPlayer {
 
 type Input = DiracStart;
 
-pub fn parse(input: &str) -> IResult<&str, Input> {
-  map(
+pub fn parse(input: &str) -> Result<Input> {
+  parse_all(map(
     tuple((
       tag("Player 1 starting position: "),
       parse_u32,
@@ -109,23 +111,23 @@ pub fn parse(input: &str) -> IResult<&str, Input> {
       parse_u32,
     )),
     |(_, pos1, _, pos2)| DiracStart { pos1, pos2 },
-  )(input)
+  ))(input)
 }
 
-pub fn p1(input: Input) -> u32 {
+pub fn p1(input: Input) -> Result<u32> {
   let mut game = Dirac::from(input);
   let mut nums = (1..=100_u32).cycle();
-  loop {
+  Ok(loop {
     let r1 = nums.next().unwrap();
     let r2 = nums.next().unwrap();
     let r3 = nums.next().unwrap();
     if game.play(r1, r2, r3) {
-      return game.score1.min(game.score2) * game.rolls;
+      break game.score1.min(game.score2) * game.rolls;
     }
-  }
+  })
 }
 
-pub fn p2(input: Input) -> u128 {
+pub fn p2(input: Input) -> Result<u128> {
   let mut d1 = RealDiracPlayer::from_starting_pos(input.pos1 as u128);
   let mut d2 = RealDiracPlayer::from_starting_pos(input.pos2 as u128);
   // Because we are dealing with each player separately, we need to account for how many universes get created between plays
@@ -136,41 +138,63 @@ pub fn p2(input: Input) -> u128 {
     p2_wins += d2.roll() * d1.num_games();
   }
 
-  p1_wins.max(p2_wins)
+  Ok(p1_wins.max(p2_wins))
+}
+
+pub struct Day;
+
+impl Solution for Day {
+  const YEAR: u32 = 2021;
+  const DAY: u32 = 21;
+  type Input<'a> = Input;
+  type Output1 = u32;
+  type Output2 = u128;
+
+  fn parse(input: &str) -> Result<Self::Input<'_>> {
+    parse(input)
+  }
+
+  fn part1(input: Self::Input<'_>) -> Result<u32> {
+    p1(input)
+  }
+
+  fn part2(input: Self::Input<'_>) -> Result<u128> {
+    p2(input)
+  }
 }
 
 #[cfg(test)]
 mod test {
   use super::*;
 
-  const TEST_INPUT: &'static str = "Player 1 starting position: 4
+  const TEST_INPUT: &str = "Player 1 starting position: 4
 Player 2 starting position: 8";
 
   #[test]
   fn test_parse() {
     let input = TEST_INPUT;
-    assert_eq!(parse(input).unwrap(), ("", DiracStart { pos1: 4, pos2: 8 }))
+    assert_eq!(parse(input).unwrap(), DiracStart { pos1: 4, pos2: 8 })
   }
 
   #[test]
   fn test_p1() {
     let input = TEST_INPUT;
-    let parsed = parse(input).unwrap().1;
-    assert_eq!(p1(parsed), 739785);
+    let parsed = parse(input).unwrap();
+    assert_eq!(p1(parsed), Ok(739785));
 
     let input = std::fs::read_to_string("./inputs/d21.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
-    assert_eq!(p1(parsed), 551901);
+    let parsed = parse(&input).unwrap();
+    assert_eq!(p1(parsed), Ok(551901));
   }
 
   #[test]
   fn test_p2() {
     let input = TEST_INPUT;
-    let parsed = parse(input).unwrap().1;
-    assert_eq!(p2(parsed), 444356092776315);
+    let parsed = parse(input).unwrap();
+    assert_eq!(p2(parsed), Ok(444356092776315));
 
     let input = std::fs::read_to_string("./inputs/d21.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
-    assert_eq!(p2(parsed), 272847859601291);
+    let parsed = parse(&input).unwrap();
+    assert_eq!(p2(parsed), Ok(272847859601291));
   }
 }
@@ -2,9 +2,10 @@
 
 use itertools::Itertools;
 
-use crate::nom_prelude::*;
-use crate::vector::*;
-use std::collections::{HashMap, HashSet};
+use aoc_common::aabox::AABox;
+use aoc_common::error::Result;
+use aoc_common::nom_prelude::*;
+use aoc_common::solution::Solution;
 
 #[derive(Debug, PartialEq, Eq, Copy, Clone)]
 pub enum CubeState {
@@ -12,54 +13,7 @@ pub enum CubeState {
   Off,
 }
 
-// Small enough that it's worth making it copy for ease of use.
-#[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
-pub struct Cuboid {
-  /// all three of these coordinates should be smaller than the `maxes`
-  min: V3,
-  /// This coordinate is just outside of the cuboid
-  max: V3,
-}
-
-impl Cuboid {
-  fn from_v3s(v1: V3, v2: V3) -> Self {
-    Self {
-      min: V3(v1.0.min(v2.0), v1.1.min(v2.1), v1.2.min(v2.2)),
-      max: V3(v1.0.max(v2.0) + 1, v1.1.max(v2.1) + 1, v1.2.max(v2.2) + 1),
-    }
-  }
-
-  fn intersection(self, rhs: Self) -> Option<Self> {
-    let xmin = self.min.0.max(rhs.min.0);
-    let xmax = self.max.0.min(rhs.max.0);
-    let ymin = self.min.1.max(rhs.min.1);
-    let ymax = self.max.1.min(rhs.max.1);
-    let zmin = self.min.2.max(rhs.min.2);
-    let zmax = self.max.2.min(rhs.max.2);
-    if xmin >= xmax || ymin >= ymax || zmin >= zmax {
-      None
-    } else {
-      Some(Self {
-        min: V3(xmin, ymin, zmin),
-        max: V3(xmax, ymax, zmax),
-      })
-    }
-  }
-
-  fn points(&self) -> Vec<V3> {
-    (self.min.0..self.max.0)
-      .cartesian_product(self.min.1..self.max.1)
-      .cartesian_product(self.min.2..self.max.2)
-      .map(|((x, y), z)| V3(x, y, z))
-      .collect()
-  }
-
-  fn volume(&self) -> u64 {
-    (self.max.0 - self.min.0) as u64
-      * (self.max.1 - self.min.1) as u64
-      * (self.max.2 - self.min.2) as u64
-  }
-}
+pub type Cuboid = AABox<3>;
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct Instruction {
@@ -69,55 +23,68 @@ pub struct Instruction {
 
 type Input = Vec<Instruction>;
 
-pub struct LightCube(HashSet<V3>);
+/// The lit cubes, by inclusion–exclusion: a list of cuboids, each counted once with its sign.  Switching a cuboid
+/// on or off cancels its overlap with every cuboid already listed, so the list grows with the number of overlaps
+/// rather than with the size of the cuboids.
+#[derive(Debug, Default)]
+pub struct Reactor(Vec<(Cuboid, i64)>);
 
-impl LightCube {
-  fn new() -> Self {
-    Self(HashSet::new())
+impl Reactor {
+  pub fn new() -> Self {
+    Self::default()
   }
 
-  fn apply(&mut self, state: CubeState, cuboid: Cuboid) {
-    match state {
-      CubeState::On => cuboid.points().into_iter().for_each(|p| {
-        self.0.insert(p);
-      }),
-      CubeState::Off => cuboid.points().into_iter().for_each(|p| {
-        self.0.remove(&p);
-      }),
+  pub fn apply(&mut self, state: CubeState, cuboid: Cuboid) {
+    let overlaps = self
+      .0
+      .iter()
+      .filter_map(|&(c, sign)| Some((c.intersection(cuboid)?, -sign)))
+      .collect_vec();
+    self.0.extend(overlaps);
+    if state == CubeState::On {
+      self.0.push((cuboid, 1));
     }
   }
 
-  fn count_lights(&self) -> usize {
+  pub fn volume(&self) -> u64 {
+    self
+      .0
+      .iter()
+      .map(|(c, sign)| c.volume() as i64 * sign)
+      .sum::<i64>() as u64
+  }
+
+  /// How many signed cuboids it takes to describe the lit cubes.
+  pub fn len(&self) -> usize {
     self.0.len()
   }
 
-  fn lights<'a>(&'a self) -> impl Iterator<Item = V3> + 'a {
-    self.0.iter().copied()
+  pub fn is_empty(&self) -> bool {
+    self.0.is_empty()
   }
 }
 
-pub fn parse(input: &str) -> IResult<&str, Input> {
-  let cube_state = map(alt((tag("on"), tag("off"))), |s| match s {
-    "on" => CubeState::On,
-    "off" => CubeState::Off,
-    _ => panic!("unknown tag"),
-  });
+fn parse_input(input: &str) -> IResult<&str, Input> {
+  let cube_state = alt((
+    value(CubeState::On, tag("on")),
+    value(CubeState::Off, tag("off")),
+  ));
   let cuboid = map(
     tuple((
       tag("x="),
-      parse_i32,
+      parse_i64,
       tag(".."),
-      parse_i32,
+      parse_i64,
       tag(",y="),
-      parse_i32,
+      parse_i64,
       tag(".."),
-      parse_i32,
+      parse_i64,
       tag(",z="),
-      parse_i32,
+      parse_i64,
       tag(".."),
-      parse_i32,
+      parse_i64,
     )),
-    |(_, x1, _, x2, _, y1, _, y2, _, z1, _, z2)| Cuboid::from_v3s(V3(x1, y1, z1), V3(x2, y2, z2)),
+    |(_, x1, _, x2, _, y1, _, y2, _, z1, _, z2)| Cuboid::from_corners([x1, y1, z1], [x2, y2, z2]),
   );
   let instruction = map(
     tuple((cube_state, tag(" "), cuboid)),
@@ -126,202 +93,58 @@ pub fn parse(input: &str) -> IResult<&str, Input> {
   lines_of(instruction)(input)
 }
 
-pub fn p1(input: Input) -> usize {
-  let window = Cuboid::from_v3s(V3(-50, -50, -50), V3(50, 50, 50));
-  let mut grid = LightCube::new();
+pub fn parse(input: &str) -> Result<Input> {
+  parse_all(parse_input)(input)
+}
+
+pub fn p1(input: Input) -> Result<u64> {
+  let window = Cuboid::from_corners([-50, -50, -50], [50, 50, 50]);
+  let mut reactor = Reactor::new();
   for (state, cuboid) in input
     .into_iter()
     .filter_map(|i| Some((i.state, i.cuboid.intersection(window)?)))
   {
-    grid.apply(state, cuboid);
+    reactor.apply(state, cuboid);
   }
-  grid.count_lights()
+  Ok(reactor.volume())
 }
 
-pub fn p2(input: Input) -> u64 {
-  // lol, still pretty much brute force, though brought into the realm of feasibility.
-  // TODO: think about an actually efficient way to do this.
-  let xs = input
-    .iter()
-    .flat_map(|i| [i.cuboid.min.0, i.cuboid.max.0])
-    .sorted()
-    .unique()
-    .collect_vec();
-  let ys = input
-    .iter()
-    .flat_map(|i| [i.cuboid.min.1, i.cuboid.max.1])
-    .sorted()
-    .unique()
-    .collect_vec();
-  let zs = input
-    .iter()
-    .flat_map(|i| [i.cuboid.min.2, i.cuboid.max.2])
-    .sorted()
-    .unique()
-    .collect_vec();
-  println!("xs {:?}", xs);
-  let possible_entries = xs
-    .iter()
-    .tuple_windows()
-    .cartesian_product(ys.iter().tuple_windows())
-    .cartesian_product(zs.iter().tuple_windows())
-    .map(|(((x1, x2), (y1, y2)), (z1, z2))| {
-      Cuboid::from_v3s(V3(*x1, *y1, *z1), V3(*x2 - 1, *y2 - 1, *z2 - 1))
-    })
-    .collect_vec();
-
-  let mut actual_entries = HashSet::new();
-
-  for instruction in input {
-    match instruction.state {
-      CubeState::On => possible_entries
-        .iter()
-        .filter(|p| p.intersection(instruction.cuboid).is_some())
-        .for_each(|e| {
-          actual_entries.insert(*e);
-        }),
-      CubeState::Off => possible_entries
-        .iter()
-        .filter(|p| p.intersection(instruction.cuboid).is_some())
-        .for_each(|e| {
-          actual_entries.remove(e);
-        }),
-    }
+pub fn p2(input: Input) -> Result<u64> {
+  let mut reactor = Reactor::new();
+  for i in input {
+    reactor.apply(i.state, i.cuboid);
   }
-  actual_entries.iter().map(|e| e.volume()).sum::<u64>()
+  Ok(reactor.volume())
 }
 
-pub fn better_p2(input: Input) -> u64 {
-  // lol, still pretty much brute force, though brought into the realm of feasibility.
-  // TODO: think about an actually efficient way to do this.
-  let xs = input
-    .iter()
-    .flat_map(|i| [i.cuboid.min.0, i.cuboid.max.0])
-    .sorted()
-    .unique()
-    .collect_vec();
-  let ys = input
-    .iter()
-    .flat_map(|i| [i.cuboid.min.1, i.cuboid.max.1])
-    .sorted()
-    .unique()
-    .collect_vec();
-  let zs = input
-    .iter()
-    .flat_map(|i| [i.cuboid.min.2, i.cuboid.max.2])
-    .sorted()
-    .unique()
-    .collect_vec();
-  println!("xs {:?}", xs);
-  println!("lens {} {} {}", xs.len(), ys.len(), zs.len());
-  // let possible_entries = xs
-  //   .iter()
-  //   .tuple_windows()
-  //   .cartesian_product(ys.iter().tuple_windows())
-  //   .cartesian_product(zs.iter().tuple_windows())
-  //   .map(|(((x1, x2), (y1, y2)), (z1, z2))| {
-  //     Cuboid::from_v3s(V3(*x1, *y1, *z1), V3(*x2 - 1, *y2 - 1, *z2 - 1))
-  //   })
-  //   .collect_vec();
-
-  let x_indices = xs
-    .iter()
-    .enumerate()
-    .map(|(i, x)| (*x, i))
-    .collect::<HashMap<_, _>>();
-  let y_indices = ys
-    .iter()
-    .enumerate()
-    .map(|(i, y)| (*y, i))
-    .collect::<HashMap<_, _>>();
-  let z_indices = zs
-    .iter()
-    .enumerate()
-    .map(|(i, z)| (*z, i))
-    .collect::<HashMap<_, _>>();
-
-  // TODO: should have done these as vecs
-  let x_indices_rev = xs
-    .iter()
-    .enumerate()
-    .map(|(i, x)| (i, *x))
-    .collect::<HashMap<_, _>>();
-  let y_indices_rev = ys
-    .iter()
-    .enumerate()
-    .map(|(i, y)| (i, *y))
-    .collect::<HashMap<_, _>>();
-  let z_indices_rev = zs
-    .iter()
-    .enumerate()
-    .map(|(i, z)| (i, *z))
-    .collect::<HashMap<_, _>>();
-
-  // translate instructions to this new grid, perform
-  let new_instructions = input
-    .iter()
-    .map(|i| {
-      let Instruction {
-        state,
-        cuboid: Cuboid { min, max },
-      } = i;
-      let xmin = *x_indices.get(&min.0).unwrap() as i32;
-      let ymin = *y_indices.get(&min.1).unwrap() as i32;
-      let zmin = *z_indices.get(&min.2).unwrap() as i32;
-      let xmax = (x_indices.get(&max.0).unwrap() - 1) as i32;
-      let ymax = (y_indices.get(&max.1).unwrap() - 1) as i32;
-      let zmax = (z_indices.get(&max.2).unwrap() - 1) as i32;
-      Instruction {
-        state: *state,
-        cuboid: Cuboid::from_v3s(V3(xmin, ymin, zmin), V3(xmax, ymax, zmax)),
-      }
-    })
-    .collect_vec();
-
-  let mut grid = LightCube::new();
-  for (i, inst) in new_instructions.iter().enumerate() {
-    println!("new inst {:?}", i);
-    grid.apply(inst.state, inst.cuboid);
+pub struct Day;
+
+impl Solution for Day {
+  const YEAR: u32 = 2021;
+  const DAY: u32 = 22;
+  type Input<'a> = Input;
+  type Output1 = u64;
+  type Output2 = u64;
+
+  fn parse(input: &str) -> Result<Self::Input<'_>> {
+    parse(input)
+  }
+
+  fn part1(input: Self::Input<'_>) -> Result<u64> {
+    p1(input)
   }
 
-  grid
-    .lights()
-    .map(|v| {
-      let x = v.0 as usize;
-      let y = v.1 as usize;
-      let z = v.2 as usize;
-      (x_indices_rev.get(&(x + 1)).unwrap() - x_indices_rev.get(&x).unwrap()) as u64
-        * (y_indices_rev.get(&(y + 1)).unwrap() - y_indices_rev.get(&y).unwrap()) as u64
-        * (z_indices_rev.get(&(z + 1)).unwrap() - z_indices_rev.get(&z).unwrap()) as u64
-    })
-    .sum()
-
-  // let mut actual_entries = HashSet::new();
-
-  // for instruction in input {
-  //   match instruction.state {
-  //     CubeState::On => possible_entries
-  //       .iter()
-  //       .filter(|p| p.intersection(instruction.cuboid).is_some())
-  //       .for_each(|e| {
-  //         actual_entries.insert(*e);
-  //       }),
-  //     CubeState::Off => possible_entries
-  //       .iter()
-  //       .filter(|p| p.intersection(instruction.cuboid).is_some())
-  //       .for_each(|e| {
-  //         actual_entries.remove(e);
-  //       }),
-  //   }
-  // }
-  // actual_entries.iter().map(|e| e.volume()).sum::<u64>()
+  fn part2(input: Self::Input<'_>) -> Result<u64> {
+    p2(input)
+  }
 }
 
 #[cfg(test)]
 mod test {
   use super::*;
+  use aoc_common::error::Error;
 
-  const TEST_INPUT: &'static str = "on x=-20..26,y=-36..17,z=-47..7
+  const TEST_INPUT: &str = "on x=-20..26,y=-36..17,z=-47..7
   on x=-20..33,y=-21..23,z=-26..28
   on x=-22..28,y=-29..23,z=-38..16
   on x=-46..7,y=-6..46,z=-50..-1
@@ -344,7 +167,7 @@ mod test {
   on x=-54112..-39298,y=-85059..-49293,z=-27449..7877
   on x=967..23432,y=45373..81175,z=27513..53682";
 
-  const BIGGER_TEST_INPUT: &'static str = "on x=-5..47,y=-31..22,z=-19..33
+  const BIGGER_TEST_INPUT: &str = "on x=-5..47,y=-31..22,z=-19..33
   on x=-44..5,y=-27..21,z=-14..35
   on x=-49..-1,y=-11..42,z=-10..38
   on x=-20..34,y=-40..6,z=-44..1
@@ -411,41 +234,89 @@ mod test {
     off x=-39..-20,y=-32..-18,z=36..47";
     assert_eq!(
       parse(input).unwrap(),
-      (
-        "",
-        vec![
-          Instruction {
-            state: CubeState::On,
-            cuboid: Cuboid::from_v3s(V3(-29, -27, -32), V3(18, 17, 22)),
-          },
-          Instruction {
-            state: CubeState::Off,
-            cuboid: Cuboid::from_v3s(V3(-39, -32, 36), V3(-20, -18, 47)),
-          }
-        ]
-      )
-    )
+      vec![
+        Instruction {
+          state: CubeState::On,
+          cuboid: Cuboid::from_corners([-29, -27, -32], [18, 17, 22]),
+        },
+        Instruction {
+          state: CubeState::Off,
+          cuboid: Cuboid::from_corners([-39, -32, 36], [-20, -18, 47]),
+        }
+      ]
+    );
+
+    let input = "on x=-29..18,y=-27..17,z=-32..22
+flip x=-39..-20,y=-32..-18,z=36..47";
+    assert_eq!(
+      parse(input),
+      Err(Error::TrailingInput {
+        line: 2,
+        column: 1,
+        snippet: "flip x=-39..-20,y=-32..-18,z=36..47".to_owned()
+      })
+    );
+  }
+
+  #[test]
+  fn test_reactor() {
+    let input = "on x=10..12,y=10..12,z=10..12
+    on x=11..13,y=11..13,z=11..13
+    off x=9..11,y=9..11,z=9..11
+    on x=10..10,y=10..10,z=10..10";
+    let mut reactor = Reactor::new();
+    let volumes = parse(input)
+      .unwrap()
+      .into_iter()
+      .map(|i| {
+        reactor.apply(i.state, i.cuboid);
+        reactor.volume()
+      })
+      .collect_vec();
+    assert_eq!(volumes, vec![27, 46, 38, 39]);
+
+    // Switching everything off leaves nothing lit, however many cuboids it took to get there.
+    let everything = Cuboid::from_corners([0, 0, 0], [20, 20, 20]);
+    reactor.apply(CubeState::Off, everything);
+    assert_eq!(reactor.volume(), 0);
+    assert!(!reactor.is_empty());
+    reactor.apply(CubeState::On, everything);
+    assert_eq!(reactor.volume(), 21 * 21 * 21);
+
+    let ons = parse(BIGGER_TEST_INPUT)
+      .unwrap()
+      .into_iter()
+      .filter(|i| i.state == CubeState::On)
+      .collect_vec();
+    let mut reactor = Reactor::new();
+    for i in &ons {
+      reactor.apply(i.state, i.cuboid);
+    }
+    assert_eq!(
+      reactor.volume(),
+      aoc_common::aabox::union_volume(ons.iter().map(|i| i.cuboid))
+    );
   }
 
   #[test]
   fn test_p1() {
     let input = TEST_INPUT;
-    let parsed = parse(input).unwrap().1;
-    assert_eq!(p1(parsed), 590784);
+    let parsed = parse(input).unwrap();
+    assert_eq!(p1(parsed), Ok(590784));
 
     let input = std::fs::read_to_string("./inputs/d22.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
-    assert_eq!(p1(parsed), 580012);
+    let parsed = parse(&input).unwrap();
+    assert_eq!(p1(parsed), Ok(580012));
   }
 
   #[test]
   fn test_p2() {
     let input = BIGGER_TEST_INPUT;
-    let parsed = parse(input).unwrap().1;
-    assert_eq!(better_p2(parsed), 2758514936282235);
+    let parsed = parse(input).unwrap();
+    assert_eq!(p2(parsed), Ok(2758514936282235));
 
     let input = std::fs::read_to_string("./inputs/d22.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
-    assert_eq!(better_p2(parsed), 1334238660555542);
+    let parsed = parse(&input).unwrap();
+    assert_eq!(p2(parsed), Ok(1334238660555542));
   }
 }
@@ -1,619 +1,462 @@
 //! https://adventofcode.com/2021/day/23
 
-use crate::nom_prelude::*;
-use std::cmp::Reverse;
-use std::collections::{BinaryHeap, HashMap, HashSet};
-
+use aoc_common::error::{Error, Result};
+use aoc_common::nom_prelude::*;
+use aoc_common::search::{dijkstra, Path};
+use aoc_common::solution::Solution;
+use std::fmt::Display;
+use std::io::{self, Write};
+use std::rc::Rc;
+use std::time::Duration;
+
+/// An amphipod.  The `n`th kind (`A` is 0) lives in the `n`th room from the left.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
-pub enum Pawn {
-  A,
-  B,
-  C,
-  D,
-}
+pub struct Pawn(u8);
 
 impl Pawn {
-  pub fn move_cost(self) -> u32 {
-    match self {
-      Self::A => 1,
-      Self::B => 10,
-      Self::C => 100,
-      Self::D => 1000,
-    }
-  }
-
-  pub fn room_entrance_index(self) -> usize {
-    match self {
-      Self::A => 2,
-      Self::B => 4,
-      Self::C => 6,
-      Self::D => 8,
-    }
+  pub const A: Pawn = Pawn(0);
+  pub const B: Pawn = Pawn(1);
+  pub const C: Pawn = Pawn(2);
+  pub const D: Pawn = Pawn(3);
+
+  /// `None` if a single step is too dear to count in a `u32`.
+  pub fn move_cost(self) -> Option<u32> {
+    10u32.checked_pow(self.0 as u32)
   }
 
-  pub fn room_exit_index(self) -> usize {
-    match self {
-      Self::A => 12,
-      Self::B => 14,
-      Self::C => 16,
-      Self::D => 18,
-    }
-  }
-
-  pub fn expanded_room_exit_index(self) -> usize {
-    match self {
-      Self::A => 14,
-      Self::B => 18,
-      Self::C => 22,
-      Self::D => 26,
-    }
+  pub fn room(self) -> usize {
+    self.0 as usize
   }
+}
 
-  // lol this is terrible.
-  pub fn corresponding_expanded_entrance(u: usize) -> usize {
-    match u {
-      14 => 2,
-      18 => 4,
-      22 => 6,
-      26 => 8,
-      _ => panic!("asdf"),
-    }
+impl std::fmt::Display for Pawn {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}", (b'A' + self.0) as char)
   }
 }
 
 type Dist = u32;
 
-/// positions 0 - 10 are the hall
-/// positions 11 - 18 are the rooms; the evens are closest to the entrance.
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash, Ord, PartialOrd)]
-pub struct Cavern([Option<Pawn>; 19]);
+/// The shape of a burrow, which every state in a search shares.
+#[derive(Debug, Eq, PartialEq, Hash)]
+struct Layout {
+  hall: usize,
+  /// The hall position just outside each room.
+  doors: Vec<usize>,
+  depth: usize,
+}
 
-impl Cavern {
-  pub fn goal() -> Self {
-    parse(
-      "#############
-#...........#
-###A#B#C#D###
-  #A#B#C#D#
-  #########",
-    )
-    .unwrap()
-    .1
-  }
+/// The hall comes first in `cells`, then each room in turn, from the space nearest the hall to the back.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct Burrow {
+  layout: Rc<Layout>,
+  cells: Vec<Option<Pawn>>,
+}
 
-  pub fn mv(self, i1: usize, i2: usize) -> Self {
-    let mut new = self.0;
-    new[i1] = self.0[i2];
-    new[i2] = self.0[i1];
-    Self(new)
+impl Burrow {
+  /// `rows[d][r]` is the space `d` deep in room `r`.
+  fn from_parts(hall: Vec<Option<Pawn>>, doors: Vec<usize>, rows: Vec<Vec<Option<Pawn>>>) -> Self {
+    let layout = Layout {
+      hall: hall.len(),
+      doors,
+      depth: rows.len(),
+    };
+    let mut cells = hall;
+    for r in 0..layout.doors.len() {
+      cells.extend(rows.iter().map(|row| row[r]));
+    }
+    Self {
+      layout: Rc::new(layout),
+      cells,
+    }
   }
 
-  pub fn possible_moves(&self) -> Vec<(Cavern, Dist)> {
-    let mut moves = Vec::new();
-    for (i, v) in self.0.iter().enumerate() {
-      if let Some(p) = v {
-        // println!("i {:?} {:?}", i, p);
-        let cost = p.move_cost();
-        let in_hall = i <= 10;
-        // room enter
-        if in_hall {
-          let (open_path, spaces) = self.open_hall_path(i, p.room_entrance_index());
-          if open_path {
-            match self.0[p.room_exit_index() - 1..=p.room_exit_index()] {
-              [Some(pawn), None] if *p == pawn => {
-                // println!("first");
-                moves.push((self.mv(i, p.room_exit_index()), cost * (spaces + 1)));
-              }
-              [None, None] => {
-                // println!("second");
-                moves.push((self.mv(i, p.room_exit_index() - 1), cost * (spaces + 2)));
-              }
-              _ => (),
-            }
-          }
-        } else {
-          // exit near door
-          if i % 2 == 0 {
-            for j in [0, 1, 3, 5, 7, 9, 10] {
-              let (open_path, spaces) = self.open_hall_path_include_start(j, i - 10);
-              if open_path {
-                moves.push((self.mv(i, j), cost * (spaces + 1)));
-              }
-            }
-          }
-          // exit from back
-          if i % 2 == 1 && self.0[i + 1].is_none() {
-            for j in [0, 1, 3, 5, 7, 9, 10] {
-              let (open_path, spaces) = self.open_hall_path_include_start(j, i - 9);
-              if open_path {
-                moves.push((self.mv(i, j), cost * (spaces + 2)));
-              }
-            }
-          }
-        }
-      }
+  /// Takes the rows of a diagram below the hall, where `None` is wall and `Some` a space.  `None` unless every
+  /// row has its spaces in the same columns, each below the hall, and every pawn has a room.
+  fn from_diagram(hall: Vec<Option<Pawn>>, rows: Vec<Vec<Option<Option<Pawn>>>>) -> Option<Self> {
+    let columns = |row: &Vec<Option<Option<Pawn>>>| {
+      row
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| c.is_some())
+        .map(|(i, _)| i)
+        .collect::<Vec<_>>()
+    };
+    let doors = columns(rows.first()?);
+    if rows.iter().any(|row| columns(row) != doors)
+      || doors.iter().any(|&c| c == 0 || c > hall.len())
+      || hall
+        .iter()
+        .chain(rows.iter().flatten().flatten())
+        .flatten()
+        .any(|p| p.room() >= doors.len())
+    {
+      return None;
     }
-    moves
+    let rows = rows
+      .iter()
+      .map(|row| doors.iter().map(|&c| row[c].flatten()).collect())
+      .collect();
+    let doors = doors.iter().map(|c| c - 1).collect();
+    Some(Self::from_parts(hall, doors, rows))
   }
 
-  pub fn open_hall_path(&self, start: usize, end: usize) -> (bool, u32) {
-    if start < end {
-      (
-        self.0[start + 1..=end].iter().all(|e| e.is_none()),
-        (end - start) as u32,
-      )
-    } else {
-      (
-        self.0[end..=start - 1].iter().all(|e| e.is_none()),
-        (start - end) as u32,
-      )
-    }
+  fn rows(&self) -> Vec<Vec<Option<Pawn>>> {
+    (0..self.layout.depth)
+      .map(|d| {
+        (0..self.rooms())
+          .map(|r| self.cells[self.space(r, d)])
+          .collect()
+      })
+      .collect()
   }
 
-  pub fn open_hall_path_include_start(&self, start: usize, end: usize) -> (bool, u32) {
-    if start < end {
-      (
-        self.0[start..=end].iter().all(|e| e.is_none()),
-        (end - start) as u32,
-      )
-    } else {
-      (
-        self.0[end..=start].iter().all(|e| e.is_none()),
-        (start - end) as u32,
-      )
+  /// A deeper burrow, with `rows` (one pawn per room) slotted in `at` spaces down.
+  pub fn with_rows(&self, at: usize, rows: &[&[Pawn]]) -> Option<Self> {
+    if at > self.layout.depth
+      || rows
+        .iter()
+        .any(|row| row.len() != self.rooms() || row.iter().any(|p| p.room() >= self.rooms()))
+    {
+      return None;
     }
+    let mut all = self.rows();
+    all.splice(
+      at..at,
+      rows
+        .iter()
+        .map(|row| row.iter().map(|&p| Some(p)).collect()),
+    );
+    Some(Self::from_parts(
+      self.cells[..self.layout.hall].to_vec(),
+      self.layout.doors.clone(),
+      all,
+    ))
   }
 
-  // TODO: refactor out of today and d15.
-  pub fn dijkstra(self) -> Dijkstra {
-    let goal = Cavern::goal();
-    // Start empty instead of with `unvisited` full so that we don't need to create unreachable states, if any
-    let mut visited = HashSet::new();
-    let mut entries = HashMap::new();
-    let mut distances = BinaryHeap::new();
-    entries.insert(self, (0, None));
-    let mut current = self;
-    loop {
-      if current.0[11..19].iter().filter(|i| i.is_some()).count() >= 7 {
-        // println!("current {:?} {}", visited.len(), current);
-      }
-      if visited.len() % 10000 == 0 {
-        // println!("visited {:?} {:?}", visited.len(), current);
-      }
-      visited.insert(current);
-      let current_dist = entries.get(&current).unwrap().0;
-      if current == goal {
-        return Dijkstra(entries);
-      }
-      for (neighbor, delta) in current.possible_moves() {
-        if !visited.contains(&neighbor) {
-          let e = entries.entry(neighbor).or_insert((u32::MAX, None));
-          let proposed_dist = current_dist + delta;
-          if proposed_dist < e.0 {
-            *e = (proposed_dist, Some(current));
-            distances.push(Reverse((proposed_dist, neighbor)));
-          }
-        }
-      }
-
-      current = loop {
-        if let Some(Reverse((_, new_current))) = distances.pop() {
-          if !visited.contains(&new_current) {
-            break new_current;
-          }
-        } else {
-          return Dijkstra(entries);
-        }
-      };
-    }
+  pub fn rooms(&self) -> usize {
+    self.layout.doors.len()
   }
-}
 
-impl std::fmt::Display for Cavern {
-  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-    let item = |x| match x {
-      Some(c) => format!("{:?}", c),
-      _ => ".".to_owned(),
-    };
-    write!(
-      f,
-      "#############
-#{}{}{}{}{}{}{}{}{}{}{}#
-###{}#{}#{}#{}###
-  #{}#{}#{}#{}#
-  #########",
-      item(self.0[0]),
-      item(self.0[1]),
-      item(self.0[2]),
-      item(self.0[3]),
-      item(self.0[4]),
-      item(self.0[5]),
-      item(self.0[6]),
-      item(self.0[7]),
-      item(self.0[8]),
-      item(self.0[9]),
-      item(self.0[10]),
-      item(self.0[12]),
-      item(self.0[14]),
-      item(self.0[16]),
-      item(self.0[18]),
-      item(self.0[11]),
-      item(self.0[13]),
-      item(self.0[15]),
-      item(self.0[17]),
-    )
+  fn space(&self, room: usize, depth: usize) -> usize {
+    self.layout.hall + room * self.layout.depth + depth
   }
-}
 
-#[derive(Debug, PartialEq, Eq, Ord, PartialOrd, Hash, Copy, Clone)]
-pub struct ExpandedCavern([Option<Pawn>; 19 + 8]);
-
-impl ExpandedCavern {
-  pub fn goal() -> Self {
-    use Pawn::*;
-    Self([
-      None,
-      None,
-      None,
-      None,
-      None,
-      None,
-      None,
-      None,
-      None,
-      None,
-      None,
-      Some(A),
-      Some(A),
-      Some(A),
-      Some(A),
-      Some(B),
-      Some(B),
-      Some(B),
-      Some(B),
-      Some(C),
-      Some(C),
-      Some(C),
-      Some(C),
-      Some(D),
-      Some(D),
-      Some(D),
-      Some(D),
-    ])
+  fn room(&self, room: usize) -> &[Option<Pawn>] {
+    let start = self.space(room, 0);
+    &self.cells[start..start + self.layout.depth]
   }
 
-  pub fn mv(self, i1: usize, i2: usize) -> Self {
-    let mut new = self.0;
-    new[i1] = self.0[i2];
-    new[i2] = self.0[i1];
-    Self(new)
+  /// Whether the room holds only its own kind, so that nobody needs to leave and its own kind may enter.
+  fn room_is_ready(&self, room: usize) -> bool {
+    self.room(room).iter().flatten().all(|p| p.room() == room)
   }
 
-  pub fn possible_moves(&self) -> Vec<(ExpandedCavern, Dist)> {
-    let mut moves = Vec::new();
-    for (i, v) in self.0.iter().enumerate() {
-      if let Some(p) = v {
-        // println!("i {:?} {:?}", i, p);
-        let cost = p.move_cost();
-        let in_hall = i <= 10;
-        // room enter
-        if in_hall {
-          let (open_path, spaces) = self.open_hall_path(i, p.room_entrance_index());
-          if open_path {
-            match self.0[p.expanded_room_exit_index() - 3..=p.expanded_room_exit_index()] {
-              [Some(p1), Some(p2), Some(p3), None] if *p == p1 && *p == p2 && *p == p3 => {
-                moves.push((
-                  self.mv(i, p.expanded_room_exit_index()),
-                  cost * (spaces + 1),
-                ));
-              }
-              [Some(p1), Some(p2), None, None] if *p == p1 && *p == p2 => {
-                moves.push((
-                  self.mv(i, p.expanded_room_exit_index() - 1),
-                  cost * (spaces + 2),
-                ));
-              }
-              [Some(p1), None, None, None] if *p == p1 => {
-                moves.push((
-                  self.mv(i, p.expanded_room_exit_index() - 2),
-                  cost * (spaces + 3),
-                ));
-              }
-              [None, None, None, None] => {
-                moves.push((
-                  self.mv(i, p.expanded_room_exit_index() - 3),
-                  cost * (spaces + 4),
-                ));
-              }
-              _ => (),
-            }
-          }
-        } else {
-          // exit near door
-          if i % 4 == 2 {
-            let entrance = Pawn::corresponding_expanded_entrance(i);
-            for j in [0, 1, 3, 5, 7, 9, 10] {
-              let (open_path, spaces) = self.open_hall_path_include_start(j, entrance);
-              if open_path {
-                moves.push((self.mv(i, j), cost * (spaces + 1)));
-              }
-            }
-          }
-          // exit from next back
-          if i % 4 == 1 && self.0[i + 1].is_none() {
-            let entrance = Pawn::corresponding_expanded_entrance(i + 1);
-            for j in [0, 1, 3, 5, 7, 9, 10] {
-              let (open_path, spaces) = self.open_hall_path_include_start(j, entrance);
-              if open_path {
-                moves.push((self.mv(i, j), cost * (spaces + 2)));
-              }
-            }
-          }
-
-          // exit from third
-          if i % 4 == 0 && self.0[i + 1].is_none() && self.0[i + 2].is_none() {
-            let entrance = Pawn::corresponding_expanded_entrance(i + 2);
-            for j in [0, 1, 3, 5, 7, 9, 10] {
-              let (open_path, spaces) = self.open_hall_path_include_start(j, entrance);
-              if open_path {
-                moves.push((self.mv(i, j), cost * (spaces + 3)));
-              }
-            }
-          }
-
-          // exit from fourth
-          if i % 4 == 3
-            && self.0[i + 1].is_none()
-            && self.0[i + 2].is_none()
-            && self.0[i + 3].is_none()
-          {
-            let entrance = Pawn::corresponding_expanded_entrance(i + 3);
-            for j in [0, 1, 3, 5, 7, 9, 10] {
-              let (open_path, spaces) = self.open_hall_path_include_start(j, entrance);
-              if open_path {
-                moves.push((self.mv(i, j), cost * (spaces + 4)));
-              }
-            }
-          }
-        }
-      }
-    }
-    moves
+  pub fn is_goal(&self) -> bool {
+    (0..self.rooms()).all(|r| self.room(r).iter().all(|p| p.map(Pawn::room) == Some(r)))
   }
 
-  pub fn open_hall_path(&self, start: usize, end: usize) -> (bool, u32) {
-    if start < end {
-      (
-        self.0[start + 1..=end].iter().all(|e| e.is_none()),
-        (end - start) as u32,
-      )
-    } else {
-      (
-        self.0[end..=start - 1].iter().all(|e| e.is_none()),
-        (start - end) as u32,
-      )
-    }
+  pub fn mv(&self, i1: usize, i2: usize) -> Self {
+    let mut new = self.clone();
+    new.cells.swap(i1, i2);
+    new
   }
 
-  pub fn open_hall_path_include_start(&self, start: usize, end: usize) -> (bool, u32) {
-    if start < end {
-      (
-        self.0[start..=end].iter().all(|e| e.is_none()),
-        (end - start) as u32,
-      )
+  /// Whether the hall is empty from `start` to `end`, not counting `start` itself, and how far that is.
+  pub fn open_hall_path(&self, start: usize, end: usize) -> (bool, u32) {
+    let (lo, hi) = if start < end {
+      (start, end)
     } else {
-      (
-        self.0[end..=start].iter().all(|e| e.is_none()),
-        (start - end) as u32,
-      )
-    }
+      (end, start)
+    };
+    (
+      (lo..=hi).all(|i| i == start || self.cells[i].is_none()),
+      (hi - lo) as u32,
+    )
   }
 
-  // TODO: refactor out of today and d15.
-  pub fn dijkstra(self) -> HashMap<ExpandedCavern, (u32, Option<ExpandedCavern>)> {
-    let goal = Self::goal();
-    // Start empty instead of with `unvisited` full so that we don't need to create unreachable states, if any
-    let mut visited = HashSet::new();
-    let mut entries = HashMap::new();
-    let mut distances = BinaryHeap::new();
-    entries.insert(self, (0, None));
-    let mut current = self;
-    loop {
-      if current.0[11..19].iter().filter(|i| i.is_some()).count() >= 7 {
-        // println!("current {:?} {}", visited.len(), current);
-      }
-      if visited.len() % 10000 == 0 {
-        // println!("visited {:?} {:?}", visited.len(), current);
+  /// Moves too dear to count in a `Dist` are left out.
+  pub fn possible_moves(&self) -> Vec<(Burrow, Dist)> {
+    let mut moves = Vec::new();
+    // room enter: a pawn only goes home, as deep as it can, once no strangers are left there.
+    for (i, p) in self.cells[..self.layout.hall].iter().enumerate() {
+      if let Some(p) = *p {
+        let room = p.room();
+        if !self.room_is_ready(room) {
+          continue;
+        }
+        let (open_path, spaces) = self.open_hall_path(i, self.layout.doors[room]);
+        let depth = self.room(room).iter().take_while(|s| s.is_none()).count();
+        let cost = p
+          .move_cost()
+          .and_then(|c| c.checked_mul(spaces + depth as u32));
+        if let (true, true, Some(cost)) = (open_path, depth > 0, cost) {
+          let to = self.space(room, depth - 1);
+          moves.push((self.mv(i, to), cost));
+        }
       }
-      visited.insert(current);
-      let current_dist = entries.get(&current).unwrap().0;
-      if current == goal {
-        return entries;
+    }
+    // room exit: the nearest pawn to the hall, to anywhere in the hall not outside a room.
+    for room in 0..self.rooms() {
+      if self.room_is_ready(room) {
+        continue;
       }
-      for (neighbor, delta) in current.possible_moves() {
-        if !visited.contains(&neighbor) {
-          let e = entries.entry(neighbor).or_insert((u32::MAX, None));
-          let proposed_dist = current_dist + delta;
-          if proposed_dist < e.0 {
-            *e = (proposed_dist, Some(current));
-            distances.push(Reverse((proposed_dist, neighbor)));
-          }
+      let depth = self.room(room).iter().take_while(|s| s.is_none()).count();
+      let from = self.space(room, depth);
+      let Some(cost) = self.cells[from].and_then(Pawn::move_cost) else {
+        continue;
+      };
+      for j in (0..self.layout.hall).filter(|j| !self.layout.doors.contains(j)) {
+        let (open_path, spaces) = self.open_hall_path(self.layout.doors[room], j);
+        if let (true, Some(cost)) = (open_path, cost.checked_mul(spaces + depth as u32 + 1)) {
+          moves.push((self.mv(from, j), cost));
         }
       }
-
-      current = loop {
-        if let Some(Reverse((_, new_current))) = distances.pop() {
-          if !visited.contains(&new_current) {
-            break new_current;
-          }
-        } else {
-          return entries;
-        }
-      };
     }
+    moves
   }
-}
 
-impl From<Cavern> for ExpandedCavern {
-  fn from(c: Cavern) -> ExpandedCavern {
-    let mut new = [None; 19 + 8];
-    for i in 0..11 {
-      new[i] = c.0[i];
-    }
-    new[11] = c.0[11];
-    new[12] = Some(Pawn::D);
-    new[13] = Some(Pawn::D);
-    new[14] = c.0[12];
-    new[15] = c.0[13];
-    new[16] = Some(Pawn::B);
-    new[17] = Some(Pawn::C);
-    new[18] = c.0[14];
-    new[19] = c.0[15];
-    new[20] = Some(Pawn::A);
-    new[21] = Some(Pawn::B);
-    new[22] = c.0[16];
-    new[23] = c.0[17];
-    new[24] = Some(Pawn::C);
-    new[25] = Some(Pawn::A);
-    new[26] = c.0[18];
-    Self(new)
+  /// The cheapest way to sort the pawns into their rooms.
+  pub fn solve(&self) -> Option<Path<Burrow, Dist>> {
+    dijkstra(self.clone(), Self::possible_moves, Self::is_goal)
+  }
+
+  pub fn plan(&self) -> Option<Vec<Step<Burrow>>> {
+    self.solve().map(|p| steps(&p))
   }
 }
 
-impl std::fmt::Display for ExpandedCavern {
+impl std::fmt::Display for Burrow {
   fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-    let item = |x| match x {
-      Some(c) => format!("{:?}", c),
-      _ => ".".to_owned(),
-    };
-    write!(
+    let item = |x: Option<Pawn>| x.map_or('.', |p| (b'A' + p.0) as char);
+    let width = self.layout.hall + 2;
+    let doors = &self.layout.doors;
+    let (left, right) = (doors[0], doors[doors.len() - 1] + 3);
+    writeln!(f, "{}", "#".repeat(width))?;
+    writeln!(
       f,
-      "#############
-#{}{}{}{}{}{}{}{}{}{}{}#
-###{}#{}#{}#{}###
-  #{}#{}#{}#{}#
-  #{}#{}#{}#{}#
-  #{}#{}#{}#{}#
-  #########",
-      item(self.0[0]),
-      item(self.0[1]),
-      item(self.0[2]),
-      item(self.0[3]),
-      item(self.0[4]),
-      item(self.0[5]),
-      item(self.0[6]),
-      item(self.0[7]),
-      item(self.0[8]),
-      item(self.0[9]),
-      item(self.0[10]),
-      item(self.0[14]),
-      item(self.0[18]),
-      item(self.0[22]),
-      item(self.0[26]),
-      item(self.0[13]),
-      item(self.0[17]),
-      item(self.0[21]),
-      item(self.0[25]),
-      item(self.0[12]),
-      item(self.0[16]),
-      item(self.0[20]),
-      item(self.0[24]),
-      item(self.0[11]),
-      item(self.0[15]),
-      item(self.0[19]),
-      item(self.0[23]),
-    )
+      "#{}#",
+      self.cells[..self.layout.hall]
+        .iter()
+        .map(|&c| item(c))
+        .collect::<String>()
+    )?;
+    for (d, row) in self.rows().into_iter().enumerate() {
+      let (start, end) = if d == 0 { (0, width) } else { (left, right) };
+      let line = (0..end)
+        .map(|col| match doors.iter().position(|&door| door + 1 == col) {
+          _ if col < start => ' ',
+          Some(r) => item(row[r]),
+          None => '#',
+        })
+        .collect::<String>();
+      writeln!(f, "{}", line)?;
+    }
+    write!(f, "{}{}", " ".repeat(left), "#".repeat(right - left))
   }
 }
 
-type Input = Cavern;
+/// A state along a plan, with the cost of the move into it and the running total.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Step<S> {
+  pub state: S,
+  pub cost: Dist,
+  pub total: Dist,
+}
 
-pub struct Dijkstra(HashMap<Cavern, (u32, Option<Cavern>)>);
+/// Splits a path into its moves; the first step is the start, at no cost.
+pub fn steps<S: Clone>(path: &Path<S, Dist>) -> Vec<Step<S>> {
+  let mut prev = 0;
+  path
+    .states
+    .iter()
+    .zip(&path.costs)
+    .map(|(state, &total)| {
+      let cost = total - prev;
+      prev = total;
+      Step {
+        state: state.clone(),
+        cost,
+        total,
+      }
+    })
+    .collect()
+}
+
+/// One frame per step, each captioned with the move number and its cost.
+pub fn replay<S: Display>(plan: &[Step<S>]) -> Vec<String> {
+  plan
+    .iter()
+    .enumerate()
+    .map(|(i, step)| {
+      format!(
+        "move {}/{}: cost {}, total {}\n{}\n",
+        i,
+        plan.len() - 1,
+        step.cost,
+        step.total,
+        step.state
+      )
+    })
+    .collect()
+}
+
+/// Plays the replay on a terminal, clearing the screen between frames.
+pub fn animate<S: Display>(
+  plan: &[Step<S>],
+  out: &mut impl Write,
+  delay: Duration,
+) -> io::Result<()> {
+  for frame in replay(plan) {
+    write!(out, "\x1b[2J\x1b[H{}", frame)?;
+    out.flush()?;
+    std::thread::sleep(delay);
+  }
+  Ok(())
+}
+
+type Input = Burrow;
 
 pub fn parse_pawn(input: &str) -> IResult<&str, Option<Pawn>> {
-  map(
-    alt((tag("A"), tag("B"), tag("C"), tag("D"), tag("."))),
-    |p| match p {
-      "A" => Some(Pawn::A),
-      "B" => Some(Pawn::B),
-      "C" => Some(Pawn::C),
-      "D" => Some(Pawn::D),
-      "." => None,
-      _ => panic!("non matching tag"),
-    },
+  alt((
+    value(None, char('.')),
+    map(satisfy(|c| c.is_ascii_uppercase()), |c| {
+      Some(Pawn(c as u8 - b'A'))
+    }),
+  ))(input)
+}
+
+/// A line below the hall: spaces (`Some`) between walls (`None`).  Fails on a line that is all wall.
+fn parse_room_row(input: &str) -> IResult<&str, Vec<Option<Option<Pawn>>>> {
+  map_opt(
+    many1(alt((map(parse_pawn, Some), value(None, one_of("# "))))),
+    |row| row.iter().any(Option::is_some).then_some(row),
   )(input)
 }
 
-pub fn parse(input: &str) -> IResult<&str, Input> {
-  map(
+fn parse_input(input: &str) -> IResult<&str, Input> {
+  map_opt(
     tuple((
-      tag("#############\n#"),
-      many_m_n(11, 11, parse_pawn),
-      tag("#\n###"),
-      parse_pawn,
-      tag("#"),
-      parse_pawn,
-      tag("#"),
-      parse_pawn,
-      tag("#"),
-      parse_pawn,
-      tag("###\n  #"),
-      parse_pawn,
-      tag("#"),
-      parse_pawn,
-      tag("#"),
-      parse_pawn,
-      tag("#"),
-      parse_pawn,
-      tag("#\n  #########"),
+      pair(many1(char('#')), line_ending),
+      delimited(char('#'), many1(parse_pawn), pair(char('#'), line_ending)),
+      many1(terminated(parse_room_row, line_ending)),
+      pair(space0, many1(char('#'))),
     )),
-    |(_, mut xs, _, a1, _, b1, _, c1, _, d1, _, a2, _, b2, _, c2, _, d2, _)| {
-      xs.append(&mut vec![a2, a1, b2, b1, c2, c1, d2, d1]);
-      let xs = xs.try_into().unwrap(); // some type pain around the error return time for `map_res`
-      Cavern(xs)
-    },
+    |(_, hall, rows, _)| Burrow::from_diagram(hall, rows),
   )(input)
 }
 
-pub fn p1(input: Input) -> u32 {
-  input.dijkstra().0.get(&Cavern::goal()).unwrap().0
+pub fn parse(input: &str) -> Result<Input> {
+  parse_all(parse_input)(input)
+}
+
+pub fn p1(input: Input) -> Result<u32> {
+  input.solve().map(|p| p.cost).ok_or(Error::NoSolution)
 }
 
-pub fn p2(input: Input) -> u32 {
-  ExpandedCavern::from(input)
-    .dijkstra()
-    .get(&ExpandedCavern::goal())
-    .unwrap()
-    .0
+pub fn p2(input: Input) -> Result<u32> {
+  use Pawn as P;
+  input
+    .with_rows(1, &[&[P::D, P::C, P::B, P::A], &[P::D, P::B, P::A, P::C]])
+    .and_then(|b| b.solve())
+    .map(|p| p.cost)
+    .ok_or(Error::NoSolution)
+}
+
+pub struct Day;
+
+impl Solution for Day {
+  const YEAR: u32 = 2021;
+  const DAY: u32 = 23;
+  type Input<'a> = Input;
+  type Output1 = u32;
+  type Output2 = u32;
+
+  fn parse(input: &str) -> Result<Self::Input<'_>> {
+    parse(input)
+  }
+
+  fn part1(input: Self::Input<'_>) -> Result<u32> {
+    p1(input)
+  }
+
+  fn part2(input: Self::Input<'_>) -> Result<u32> {
+    p2(input)
+  }
 }
 
 #[cfg(test)]
 mod test {
   use super::*;
 
-  const TEST_INPUT: &'static str = "#############
+  const TEST_INPUT: &str = "#############
 #...........#
 ###B#C#B#D###
   #A#D#C#A#
   #########";
 
+  // Two rooms off a five-space hall.
+  const SMALL_INPUT: &str = "#######
+#.....#
+###B#A#
+  #A#B#
+  #####";
+
   #[test]
   fn test_parse() {
-    use super::Pawn::*;
-    let input = TEST_INPUT;
-    assert_eq!(
-      parse(input).unwrap().1 .0[11..19],
-      [
-        Some(A),
-        Some(B),
-        Some(D),
-        Some(C),
-        Some(C),
-        Some(B),
-        Some(A),
-        Some(D)
-      ]
+    let parsed = parse(TEST_INPUT).unwrap();
+    assert_eq!(parsed.rooms(), 4);
+    assert_eq!(parsed.room(0), [Some(Pawn::B), Some(Pawn::A)]);
+    assert_eq!(parsed.room(3), [Some(Pawn::D), Some(Pawn::A)]);
+    assert_eq!(parsed.to_string(), TEST_INPUT);
+
+    let small = parse(SMALL_INPUT).unwrap();
+    assert_eq!(small.layout.doors, vec![2, 4]);
+    assert_eq!(small.to_string(), SMALL_INPUT);
+
+    assert!(parse(
+      "#############
+#...........#
+###B#C#B#D###
+  #A#D#C#A.#
+  #########"
     )
+    .is_err());
+    // Only four rooms, so there is no home for an `E`, let alone a `Z`.
+    for pawn in ['E', 'Z'] {
+      assert!(parse(&TEST_INPUT.replacen('C', &pawn.to_string(), 1)).is_err());
+    }
+  }
+
+  #[test]
+  fn test_move_cost() {
+    assert_eq!(Pawn::D.move_cost(), Some(1000));
+    assert_eq!(Pawn(9).move_cost(), Some(1_000_000_000));
+    assert_eq!(Pawn(10).move_cost(), None);
+  }
+
+  #[test]
+  fn test_with_rows() {
+    let deeper = parse(TEST_INPUT)
+      .unwrap()
+      .with_rows(1, &[&[Pawn::D, Pawn::C, Pawn::B, Pawn::A]])
+      .unwrap();
+    assert_eq!(
+      deeper.to_string(),
+      "#############
+#...........#
+###B#C#B#D###
+  #D#C#B#A#
+  #A#D#C#A#
+  #########"
+    );
+    assert_eq!(parse(TEST_INPUT).unwrap().with_rows(1, &[&[Pawn::D]]), None);
+    assert_eq!(
+      parse(SMALL_INPUT)
+        .unwrap()
+        .with_rows(1, &[&[Pawn::A, Pawn::C]]),
+      None
+    );
   }
 
   #[test]
@@ -625,31 +468,75 @@ mod test {
   #A#.#.#.#
   #########",
     )
-    .unwrap()
-    .1;
-    println!("{:?}", parsed.possible_moves());
-    panic!("asdf");
+    .unwrap();
+    let moved = parse(
+      "#############
+#...........#
+###A#.#.#.###
+  #A#.#.#.#
+  #########",
+    )
+    .unwrap();
+    assert_eq!(parsed.possible_moves(), vec![(moved.clone(), 3)]);
+    assert_eq!(moved.possible_moves(), vec![]);
+  }
+
+  #[test]
+  fn test_small() {
+    assert_eq!(p1(parse(SMALL_INPUT).unwrap()), Ok(46));
+  }
+
+  #[test]
+  fn test_plan() {
+    let start = parse(TEST_INPUT).unwrap();
+    let plan = start.plan().unwrap();
+    assert_eq!(plan[0].state, start);
+    assert_eq!(plan[0].cost, 0);
+    assert!(plan.last().unwrap().state.is_goal());
+    assert_eq!(plan.last().unwrap().total, 12521);
+    assert_eq!(plan.iter().map(|s| s.cost).sum::<Dist>(), 12521);
+    for w in plan.windows(2) {
+      assert!(w[0]
+        .state
+        .possible_moves()
+        .contains(&(w[1].state.clone(), w[1].cost)));
+    }
+
+    let frames = replay(&plan);
+    assert_eq!(frames.len(), plan.len());
+    assert_eq!(
+      frames[0],
+      format!("move 0/{}: cost 0, total 0\n{}\n", plan.len() - 1, start)
+    );
+
+    let mut out = Vec::new();
+    animate(&plan[..2], &mut out, Duration::ZERO).unwrap();
+    let short = replay(&plan[..2]);
+    assert_eq!(
+      String::from_utf8(out).unwrap(),
+      format!("\x1b[2J\x1b[H{}\x1b[2J\x1b[H{}", short[0], short[1])
+    );
   }
 
   #[test]
   fn test_p1() {
     let input = TEST_INPUT;
-    let parsed = parse(input).unwrap().1;
-    assert_eq!(p1(parsed), 12521);
+    let parsed = parse(input).unwrap();
+    assert_eq!(p1(parsed), Ok(12521));
 
     let input = std::fs::read_to_string("./inputs/d23.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
-    assert_eq!(p1(parsed), 11120);
+    let parsed = parse(&input).unwrap();
+    assert_eq!(p1(parsed), Ok(11120));
   }
 
   #[test]
   fn test_p2() {
     let input = TEST_INPUT;
-    let parsed = parse(input).unwrap().1;
-    assert_eq!(p2(parsed), 44169);
+    let parsed = parse(input).unwrap();
+    assert_eq!(p2(parsed), Ok(44169));
 
     let input = std::fs::read_to_string("./inputs/d23.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
-    assert_eq!(p2(parsed), 0);
+    let parsed = parse(&input).unwrap();
+    assert_eq!(p2(parsed), Ok(49232));
   }
 }
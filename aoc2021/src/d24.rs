@@ -1,6 +1,8 @@
 //! https://adventofcode.com/2021/day/24
 
-use crate::nom_prelude::*;
+use aoc_common::error::{Error, Result};
+use aoc_common::nom_prelude::*;
+use aoc_common::solution::Solution;
 
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
 pub enum Register {
@@ -10,6 +12,14 @@ pub enum Register {
   Z,
 }
 
+impl Register {
+  pub const ALL: [Register; 4] = [Register::W, Register::X, Register::Y, Register::Z];
+
+  fn index(self) -> usize {
+    self as usize
+  }
+}
+
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
 pub enum Value {
   Literal(i64),
@@ -26,14 +36,125 @@ pub enum Inst {
   Eql(Register, Value),
 }
 
+/// Why a program couldn't run to the end.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum AluErrorKind {
+  DivByZero,
+  /// `mod a b` with `a < 0` or `b < 0`.
+  NegativeMod,
+  InputExhausted,
+}
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct AluError {
+  /// The instruction that failed.
+  pub index: usize,
+  pub kind: AluErrorKind,
+}
+
+impl std::fmt::Display for AluError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let kind = match self.kind {
+      AluErrorKind::DivByZero => "division by zero",
+      AluErrorKind::NegativeMod => "modulo of a negative number",
+      AluErrorKind::InputExhausted => "ran out of input",
+    };
+    write!(f, "{} at instruction {}", kind, self.index)
+  }
+}
+
+impl std::error::Error for AluError {}
+
+type Op = fn(i64, i64) -> std::result::Result<i64, AluErrorKind>;
+
+impl Inst {
+  /// The register written and the value read alongside it, for everything but `inp`.
+  fn operands(self) -> Option<(Register, Value)> {
+    match self {
+      Inst::Inp(_) => None,
+      Inst::Add(r, v) | Inst::Mul(r, v) | Inst::Div(r, v) | Inst::Mod(r, v) | Inst::Eql(r, v) => {
+        Some((r, v))
+      }
+    }
+  }
+
+  /// What the instruction computes from its register and value, or `None` for `inp`.
+  fn op(self) -> Option<Op> {
+    match self {
+      Inst::Inp(_) => None,
+      Inst::Add(..) => Some(|a, b| Ok(a.wrapping_add(b))),
+      Inst::Mul(..) => Some(|a, b| Ok(a.wrapping_mul(b))),
+      Inst::Div(..) => Some(|a, b| match b {
+        0 => Err(AluErrorKind::DivByZero),
+        _ => Ok(a.wrapping_div(b)),
+      }),
+      Inst::Mod(..) => Some(|a, b| match b {
+        0 => Err(AluErrorKind::DivByZero),
+        _ if a < 0 || b < 0 => Err(AluErrorKind::NegativeMod),
+        _ => Ok(a % b),
+      }),
+      Inst::Eql(..) => Some(|a, b| Ok((a == b) as i64)),
+    }
+  }
+
+  fn with_value(self, v: Value) -> Self {
+    match self {
+      Inst::Inp(_) => self,
+      Inst::Add(r, _) => Inst::Add(r, v),
+      Inst::Mul(r, _) => Inst::Mul(r, v),
+      Inst::Div(r, _) => Inst::Div(r, v),
+      Inst::Mod(r, _) => Inst::Mod(r, v),
+      Inst::Eql(r, _) => Inst::Eql(r, v),
+    }
+  }
+}
+
+/// The optimiser's instructions: the puzzle's, plus a plain move that it writes where an `add` or `mul` would
+/// only copy a value.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum OptInst {
+  Inst(Inst),
+  Set(Register, Value),
+}
+
+impl From<Inst> for OptInst {
+  fn from(inst: Inst) -> Self {
+    OptInst::Inst(inst)
+  }
+}
+
+impl OptInst {
+  fn operands(self) -> Option<(Register, Value)> {
+    match self {
+      OptInst::Inst(inst) => inst.operands(),
+      OptInst::Set(r, v) => Some((r, v)),
+    }
+  }
+
+  fn op(self) -> Option<Op> {
+    match self {
+      OptInst::Inst(inst) => inst.op(),
+      OptInst::Set(..) => Some(|_, b| Ok(b)),
+    }
+  }
+
+  fn with_value(self, v: Value) -> Self {
+    match self {
+      OptInst::Inst(inst) => OptInst::Inst(inst.with_value(v)),
+      OptInst::Set(r, _) => OptInst::Set(r, v),
+    }
+  }
+}
+
 type Input = Vec<Inst>;
 
-#[derive(Debug, Eq, PartialEq)]
-pub struct Alu {
-  w: i64,
-  x: i64,
-  y: i64,
-  z: i64,
+/// Registers hold plain numbers when running a program, or `Interval`s when analysing one.
+#[derive(Debug, Eq, PartialEq, Default, Copy, Clone)]
+pub struct Alu<T = i64> {
+  w: T,
+  x: T,
+  y: T,
+  z: T,
 }
 
 impl Alu {
@@ -46,31 +167,194 @@ impl Alu {
     }
   }
 
-  pub fn run_program(&mut self, insts: &[Inst], mut inputs: Vec<i64>) {
-    inputs.reverse();
-    for inst in insts {
-      match *inst {
-        Inst::Inp(r) => self.write_register(r, inputs.pop().unwrap()),
-        Inst::Add(r, v) => self.write_register(r, self.read_register(r) + self.get_value(v)),
-        Inst::Mul(r, v) => self.write_register(r, self.read_register(r) * self.get_value(v)),
-        Inst::Div(r, v) => self.write_register(r, self.read_register(r) / self.get_value(v)),
-        Inst::Mod(r, v) => self.write_register(r, self.read_register(r) % self.get_value(v)),
-        Inst::Eql(r, v) => {
-          let val = if self.read_register(r) == self.get_value(v) {
-            1
-          } else {
-            0
-          };
-          self.write_register(r, val)
-        }
-      }
-      if matches!(inst, Inst::Inp(_)) {
-        // println!("inst {:?} alu {:?}", inst, self);
+  pub fn run_program(
+    &mut self,
+    insts: &[Inst],
+    inputs: Vec<i64>,
+  ) -> std::result::Result<(), AluError> {
+    let mut inputs = inputs.into_iter();
+    for (index, &inst) in insts.iter().enumerate() {
+      self
+        .execute(inst, &mut inputs)
+        .map_err(|kind| AluError { index, kind })?;
+    }
+    Ok(())
+  }
+
+  /// Leaves the ALU as it was if the instruction fails.  Callers know where they are in the program, so they
+  /// attach the index to the error.
+  pub fn execute(
+    &mut self,
+    inst: Inst,
+    inputs: &mut impl Iterator<Item = i64>,
+  ) -> std::result::Result<(), AluErrorKind> {
+    let (r, value) = match (inst.op(), inst.operands()) {
+      (Some(op), Some((r, v))) => (r, op(self.read_register(r), self.get_value(v))?),
+      _ => match inst {
+        Inst::Inp(r) => (r, inputs.next().ok_or(AluErrorKind::InputExhausted)?),
+        _ => unreachable!("only inp has no op"),
+      },
+    };
+    self.write_register(r, value);
+    Ok(())
+  }
+}
+
+/// The ALU just after running the instruction at `index`.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct TraceRow {
+  pub index: usize,
+  pub inst: Inst,
+  pub alu: Alu,
+}
+
+pub enum Breakpoint {
+  /// Pause before any `inp`, i.e. between MONAD's blocks.
+  Inp,
+  /// Pause after any instruction that leaves the ALU in this state.
+  When(Box<dyn Fn(&Alu) -> bool>),
+}
+
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub enum Stop {
+  /// Paused with the instruction at this index up next.
+  Breakpoint(usize),
+  Finished,
+}
+
+/// Runs a program an instruction at a time, recording every step.
+pub struct Debugger<'a> {
+  insts: &'a [Inst],
+  inputs: std::vec::IntoIter<i64>,
+  alu: Alu,
+  next: usize,
+  trace: Vec<TraceRow>,
+  breakpoints: Vec<Breakpoint>,
+}
+
+impl<'a> Debugger<'a> {
+  pub fn new(insts: &'a [Inst], inputs: Vec<i64>) -> Self {
+    Self {
+      insts,
+      inputs: inputs.into_iter(),
+      alu: Alu::new(),
+      next: 0,
+      trace: Vec::new(),
+      breakpoints: Vec::new(),
+    }
+  }
+
+  pub fn break_on(&mut self, breakpoint: Breakpoint) -> &mut Self {
+    self.breakpoints.push(breakpoint);
+    self
+  }
+
+  pub fn alu(&self) -> &Alu {
+    &self.alu
+  }
+
+  pub fn trace(&self) -> &[TraceRow] {
+    &self.trace
+  }
+
+  /// Runs one instruction, or returns `None` if there are none left.  A failed instruction can't be stepped
+  /// past.
+  pub fn step(&mut self) -> std::result::Result<Option<TraceRow>, AluError> {
+    let inst = match self.insts.get(self.next) {
+      Some(&inst) => inst,
+      None => return Ok(None),
+    };
+    self
+      .alu
+      .execute(inst, &mut self.inputs)
+      .map_err(|kind| AluError {
+        index: self.next,
+        kind,
+      })?;
+    let row = TraceRow {
+      index: self.next,
+      inst,
+      alu: self.alu,
+    };
+    self.trace.push(row);
+    self.next += 1;
+    Ok(Some(row))
+  }
+
+  /// Runs at least one instruction, then carries on until a breakpoint, the end, or an error.
+  pub fn resume(&mut self) -> std::result::Result<Stop, AluError> {
+    while self.step()?.is_some() {
+      let before_inp = matches!(self.insts.get(self.next), Some(Inst::Inp(_)));
+      let hit = self.breakpoints.iter().any(|b| match b {
+        Breakpoint::Inp => before_inp,
+        Breakpoint::When(condition) => condition(&self.alu),
+      });
+      if hit {
+        return Ok(Stop::Breakpoint(self.next));
       }
     }
+    Ok(Stop::Finished)
   }
 
-  fn read_register(&self, r: Register) -> i64 {
+  /// The trace so far, one row per instruction.
+  pub fn table(&self) -> String {
+    let mut table = format!(
+      "{:>5}  {:<10} {:>6} {:>6} {:>6} {:>12}\n",
+      "#", "inst", "w", "x", "y", "z"
+    );
+    for row in &self.trace {
+      table += &format!(
+        "{:>5}  {:<10} {:>6} {:>6} {:>6} {:>12}\n",
+        row.index,
+        row.inst.to_string(),
+        row.alu.w,
+        row.alu.x,
+        row.alu.y,
+        row.alu.z
+      );
+    }
+    table
+  }
+}
+
+impl std::fmt::Display for Register {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let name = match self {
+      Register::W => "w",
+      Register::X => "x",
+      Register::Y => "y",
+      Register::Z => "z",
+    };
+    write!(f, "{}", name)
+  }
+}
+
+impl std::fmt::Display for Value {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Value::Literal(n) => write!(f, "{}", n),
+      Value::Register(r) => write!(f, "{}", r),
+    }
+  }
+}
+
+impl std::fmt::Display for Inst {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let name = match self {
+      Inst::Inp(r) => return write!(f, "inp {}", r),
+      Inst::Add(..) => "add",
+      Inst::Mul(..) => "mul",
+      Inst::Div(..) => "div",
+      Inst::Mod(..) => "mod",
+      Inst::Eql(..) => "eql",
+    };
+    let (r, v) = self.operands().unwrap();
+    write!(f, "{} {} {}", name, r, v)
+  }
+}
+
+impl<T: Copy + From<i64>> Alu<T> {
+  pub fn read_register(&self, r: Register) -> T {
     match r {
       Register::W => self.w,
       Register::X => self.x,
@@ -79,7 +363,7 @@ impl Alu {
     }
   }
 
-  fn write_register(&mut self, r: Register, v: i64) {
+  fn write_register(&mut self, r: Register, v: T) {
     match r {
       Register::W => self.w = v,
       Register::X => self.x = v,
@@ -88,239 +372,502 @@ impl Alu {
     }
   }
 
-  fn get_value(&self, value: Value) -> i64 {
+  fn get_value(&self, value: Value) -> T {
     match value {
-      Value::Literal(i) => i,
+      Value::Literal(i) => T::from(i),
       Value::Register(r) => self.read_register(r),
     }
   }
 }
 
-pub fn parse_register(input: &str) -> IResult<&str, Register> {
-  map(one_of("wxyz"), |r| match r {
-    'w' => Register::W,
-    'x' => Register::X,
-    'y' => Register::Y,
-    'z' => Register::Z,
-    _ => panic!("non-matching character"),
-  })(input)
+/// Every value a register might hold, from `lo` to `hi` inclusive.  Arithmetic saturates rather than
+/// overflowing, so the bounds stay safe if loose.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct Interval {
+  pub lo: i64,
+  pub hi: i64,
 }
 
-pub fn parse_value(input: &str) -> IResult<&str, Value> {
-  let literal = map(parse_i64, Value::Literal);
-  let register = map(parse_register, Value::Register);
-  alt((literal, register))(input)
+impl Interval {
+  pub const FULL: Interval = Interval {
+    lo: i64::MIN,
+    hi: i64::MAX,
+  };
+  pub const DIGIT: Interval = Interval { lo: 1, hi: 9 };
+
+  pub fn new(lo: i64, hi: i64) -> Self {
+    Self { lo, hi }
+  }
+
+  /// The value, if there is only one.
+  pub fn constant(self) -> Option<i64> {
+    (self.lo == self.hi).then_some(self.lo)
+  }
+
+  pub fn contains(self, n: i64) -> bool {
+    self.lo <= n && n <= self.hi
+  }
+
+  fn is_empty(self) -> bool {
+    self.lo > self.hi
+  }
+
+  fn union(self, other: Self) -> Self {
+    Self::new(self.lo.min(other.lo), self.hi.max(other.hi))
+  }
+
+  fn spanning(corners: [i64; 4]) -> Self {
+    let lo = *corners.iter().min().unwrap();
+    let hi = *corners.iter().max().unwrap();
+    Self::new(lo, hi)
+  }
+
+  pub fn eql(self, other: Self) -> Self {
+    match (self.constant(), other.constant()) {
+      (Some(a), Some(b)) if a == b => Self::from(1),
+      _ if self.hi < other.lo || other.hi < self.lo => Self::from(0),
+      _ => Self::new(0, 1),
+    }
+  }
 }
 
-pub fn parse(input: &str) -> IResult<&str, Input> {
-  let unary = map(preceded(tag("inp "), parse_register), Inst::Inp);
-  let binary_tag = alt((tag("add"), tag("mul"), tag("div"), tag("mod"), tag("eql")));
-  let binary = map(
-    tuple((binary_tag, space1, parse_register, space1, parse_value)),
-    |(t, _, r, _, v)| match t {
-      "add" => Inst::Add(r, v),
-      "mul" => Inst::Mul(r, v),
-      "div" => Inst::Div(r, v),
-      "mod" => Inst::Mod(r, v),
-      "eql" => Inst::Eql(r, v),
-      _ => panic!("non-matching tag"),
-    },
-  );
-  let inst = alt((unary, binary));
-  lines_of(inst)(input)
+impl From<i64> for Interval {
+  fn from(n: i64) -> Self {
+    Self::new(n, n)
+  }
 }
 
-fn digits(mut n: i64) -> [i64; 7] {
-  let mut to_return = [0; 7];
-  for i in 0..7 {
-    let d = n % 10;
-    n /= 10;
-    to_return[6 - i] = d;
+impl std::ops::Add for Interval {
+  type Output = Self;
+  fn add(self, rhs: Self) -> Self {
+    Self::new(
+      self.lo.saturating_add(rhs.lo),
+      self.hi.saturating_add(rhs.hi),
+    )
   }
-  to_return
 }
 
-// Ok, the naïve approach isn't going to cut it here. Maybe I should get a way to prove particular instructions are unneeded.  Or maybe I express z in terms of the original inputs
-pub fn p1(insts: Input) -> i64 {
-  // for my input, positions 3, 5, and 9 through 13 were div by 26 positions.
+impl std::ops::Mul for Interval {
+  type Output = Self;
+  fn mul(self, rhs: Self) -> Self {
+    Self::spanning([
+      self.lo.saturating_mul(rhs.lo),
+      self.lo.saturating_mul(rhs.hi),
+      self.hi.saturating_mul(rhs.lo),
+      self.hi.saturating_mul(rhs.hi),
+    ])
+  }
+}
 
-  // No need to left pad, as that would add a zero digit.
-  // the "bigger" steps can never fail to increase 26-fold, so they might as well be 9s.  Brute-forcing is now manageable.
-  // let mut n = 9999999_i64;
-  let mut n = 9999999_i64;
+impl std::ops::Div for Interval {
+  type Output = Self;
+  /// Dividing by 0 is invalid, so only the nonzero divisors count.
+  fn div(self, rhs: Self) -> Self {
+    let div = |a: i64, b: i64| a.checked_div(b).unwrap_or(i64::MAX);
+    [
+      Self::new(rhs.lo, rhs.hi.min(-1)),
+      Self::new(rhs.lo.max(1), rhs.hi),
+    ]
+    .into_iter()
+    .filter(|d| !d.is_empty())
+    .map(|d| {
+      Self::spanning([
+        div(self.lo, d.lo),
+        div(self.lo, d.hi),
+        div(self.hi, d.lo),
+        div(self.hi, d.hi),
+      ])
+    })
+    .reduce(Self::union)
+    .unwrap_or(Self::FULL)
+  }
+}
 
-  // let start = std::time::Instant::now();
-  loop {
-    let mut inputs = [9; 14];
-    let other_inputs = digits(n);
-    // only need to try different inputs where we call the "smaller" variant.
-    inputs[3] = other_inputs[0];
-    inputs[5] = other_inputs[1];
-    for i in 0..5 {
-      inputs[9 + i] = other_inputs[2 + i];
+impl std::ops::Rem for Interval {
+  type Output = Self;
+  /// Only a nonnegative value modulo a positive one is valid.
+  fn rem(self, rhs: Self) -> Self {
+    let a = Self::new(self.lo.max(0), self.hi);
+    let b = Self::new(rhs.lo.max(1), rhs.hi);
+    if a.is_empty() || b.is_empty() {
+      Self::FULL
+    } else if a.hi < b.lo {
+      a
+    } else if b.lo == b.hi && a.lo / b.lo == a.hi / b.lo {
+      Self::new(a.lo % b.lo, a.hi % b.lo)
+    } else {
+      Self::new(0, a.hi.min(b.hi - 1))
     }
-    n -= 1;
-    // println!("inputs {:?}", inputs);
-    if n < 0 {
-      panic!("tried them all");
-    }
-    if inputs.iter().any(|v| *v == 0) {
-      continue;
+  }
+}
+
+impl Alu<Interval> {
+  /// An ALU whose registers all start at exactly 0.
+  pub fn intervals() -> Self {
+    Self {
+      w: Interval::from(0),
+      x: Interval::from(0),
+      y: Interval::from(0),
+      z: Interval::from(0),
     }
-    let inputs: [i64; 14] = inputs.clone().try_into().unwrap();
-    let out = my_program(inputs.clone());
-    if out == 0 {
-      return inputs
-        .into_iter()
-        .rev()
-        .enumerate()
-        .map(|(i, digit)| digit * (10_i64.pow(i as u32)))
-        .sum();
+  }
+
+  /// Runs every possible execution at once, with each `inp` reading the matching interval.  Reads past the end
+  /// of `inputs` could be anything.
+  pub fn run_program(&mut self, insts: &[Inst], inputs: &[Interval]) {
+    let mut inputs = inputs.iter();
+    for inst in insts {
+      match *inst {
+        Inst::Inp(r) => self.write_register(r, *inputs.next().unwrap_or(&Interval::FULL)),
+        Inst::Add(r, v) => self.write_register(r, self.read_register(r) + self.get_value(v)),
+        Inst::Mul(r, v) => self.write_register(r, self.read_register(r) * self.get_value(v)),
+        Inst::Div(r, v) => self.write_register(r, self.read_register(r) / self.get_value(v)),
+        Inst::Mod(r, v) => self.write_register(r, self.read_register(r) % self.get_value(v)),
+        Inst::Eql(r, v) => self.write_register(r, self.read_register(r).eql(self.get_value(v))),
+      }
     }
   }
 }
 
-pub fn p2(_input: Input) -> usize {
-  0
-}
-
-pub fn bigger(w: i64, v1: i64, v2: i64, mut z: i64) -> i64 {
-  // inp w
-  // mul x 0
-  // add x z
-  // mod x 26
-  // div z 1
-  // add x 15
-  // eql x w
-  // eql x 0
-  // mul y 0
-  // add y 25
-  // mul y x
-  // add y 1
-  // mul z y
-  // mul y 0
-  // add y w
-  // add y 15
-  // mul y x
-  // add z y
-  if w != (z % 26) + v1 {
-    z *= 26;
-  } else {
-    // println!("hit this");
-  }
-  z += v2 + w;
-  z
-}
-
-pub fn smaller(w: i64, v1: i64, v2: i64, mut z: i64) -> i64 {
-  // inp w
-  // mul x 0
-  // add x z
-  // mod x 26
-  // div z 26
-  // add x -10
-  // eql x w
-  // eql x 0
-  // mul y 0
-  // add y 25
-  // mul y x
-  // add y 1
-  // mul z y
-  // mul y 0
-  // add y w
-  // add y 1
-  // mul y x
-  // add z y
-  println!("smaller {}", w);
-  let x = z % 26;
-  z /= 26;
-  if w != x + v1 {
-    z *= 26;
-    z += v2 + w + 1;
-  } else {
-    println!("and hit this");
-    panic!("I want to hit this");
-  }
-  z
-}
-
-pub fn my_program(xs: [i64; 14]) -> i64 {
-  // Think of encoding a base 26 number.  Each time we go "bigger", we multiply by 26 and add our input and the number from our input.
-  // Because there are seven bigger steps and seven smaller steps, we need all the smaller steps to not have the mult by 26 step; our life is easier.
-  let z = bigger(xs[0], 15, 15, 0); // z = xs[0] + 15
-  let z = bigger(xs[1], 12, 5, z); // z = xs[0] + 15 | xs[1] + 5
-  let z = bigger(xs[2], 13, 6, z); // z = xs[0] + 15 | xs[1] + 5 | xs[2] + 6
-  let z = smaller(xs[3], -14, 7, z); // choose x[3] = xs[2] + 6 - 14, then z = xs[0] + 15 | xs[1] + 5
-  let z = bigger(xs[4], 15, 9, z); // z = xs[0] + 15 | xs[1] + 5 | xs[4] + 9
-  let z = smaller(xs[5], -7, 6, z); // choose x[5] = xs[4] + 9 - 7, then z = xs[0] + 15 | xs[1] + 5
-  let z = bigger(xs[6], 14, 14, z); // z = xs[0] + 15 | xs[1] + 5 | xs[6] + 14
-  let z = bigger(xs[7], 15, 3, z); // z = xs[0] + 15 | xs[1] + 5 | xs[6] + 14 | xs[7] + 3
-  let z = bigger(xs[8], 15, 1, z); // z = xs[0] + 15 | xs[1] + 5 | xs[6] + 14 | xs[7] + 3 | xs[8] + 1
-  let z = smaller(xs[9], -7, 3, z); // choose xs[9] = xs[8] + 1 - 7
-  let z = smaller(xs[10], -8, 4, z); // choose xs[10] = xs[7] + 3 - 8
-  let z = smaller(xs[11], -7, 6, z); // choose xs[11] = xs[6] + 14 - 7
-  let z = smaller(xs[12], -5, 7, z); // choose xs[12] = xs[1] + 5 - 5
-  let z = smaller(xs[13], -10, 1, z); // choose xs[13] = xs[0] + 15 - 10
-
-  // constraints
-  // x[3] + 8 = xs[2]  => 1, 9
-  // x[5] = xs[4] + 2
-  // x[9] + 6 =x[8]
-  // xs[10] + 5 = xs[7]
-  // xs[11] = xs[6] + 7
-  // xs[12] = xs[1]
-  // xs[13] = xs[0] + 5
-  // 49917929934999
-  // 11911316711816
-  z
-}
-
-pub fn my_mini_program(first_input: i64, third_input: i64) -> i64 {
-  // let xs = [9, 9, 9];
-  let z = 0;
-  let z = bigger(first_input, 12, 5, z); // z = xs[0] + 5
-  let z = bigger(9, 13, 6, z); // z = xs[1] + 5 | xs[2] + 6
-  let z = smaller(third_input, -14, 7, z); // choose xs[3] == xs[1] + 5 - 14
-  z
-}
-
-pub fn my_mini_program2(x1: i64, x2: i64, x3: i64, x4: i64) -> i64 {
-  let z = bigger(x1, 15, 15, 0); // z = xs[0] + 15
-  let z = bigger(x2, 12, 5, z); // z = xs[0] + 15 | xs[1] + 5
-  let z = bigger(x3, 13, 6, z); // z = xs[0] + 15 | xs[1] + 5 | xs[2] + 6
-  let z = smaller(x4, -14, 7, z);
-  z
+/// Whether some model number starting with `prefix` might leave z at 0.  `false` is a proof that none does;
+/// `true` may be a false alarm unless the whole number is given.
+pub fn z_can_be_zero(insts: &[Inst], prefix: &[i64]) -> bool {
+  let digits = insts.iter().filter(|i| matches!(i, Inst::Inp(_))).count();
+  let inputs = (0..digits)
+    .map(|i| {
+      prefix
+        .get(i)
+        .map_or(Interval::DIGIT, |&d| Interval::from(d))
+    })
+    .collect::<Vec<_>>();
+  let mut alu = Alu::intervals();
+  alu.run_program(insts, &inputs);
+  alu.z.contains(0)
 }
 
-#[cfg(test)]
-mod test {
-  use super::*;
+/// Simplifies a program without changing what it leaves in `outputs`.  It assumes the program is valid, so it
+/// may drop a division whose result goes unused even if that division would have failed.
+pub fn optimise(insts: &[Inst], outputs: &[Register]) -> Vec<OptInst> {
+  let mut insts = insts.iter().map(|&inst| inst.into()).collect::<Vec<_>>();
+  loop {
+    let next = drop_dead_writes(&fold_constants(&insts), outputs);
+    if next == insts {
+      return insts;
+    }
+    insts = next;
+  }
+}
 
-  #[test]
-  fn test_mini2() {
-    for i in 1..10 {
-      for j in 1..10 {
-        for k in 1..10 {
-          for l in 1..10 {
-            my_mini_program2(i, j, k, l);
-          }
+/// Tracks which registers hold known values, replacing reads of them with literals, folding what can be
+/// worked out ahead of time and dropping what does nothing.
+fn fold_constants(insts: &[OptInst]) -> Vec<OptInst> {
+  use Inst::{Add, Div, Mul};
+  use OptInst::Set;
+  use Value::Literal;
+  let mut known = [Some(0); 4];
+  let mut folded = Vec::new();
+  for &inst in insts {
+    let (r, v) = match inst.operands() {
+      Some((r, v)) => (r, v),
+      None => {
+        if let OptInst::Inst(Inst::Inp(r)) = inst {
+          known[r.index()] = None;
         }
+        folded.push(inst);
+        continue;
+      }
+    };
+    let v = match v {
+      Value::Register(s) => known[s.index()].map_or(v, Literal),
+      _ => v,
+    };
+    let current = known[r.index()];
+    let simplified = match (inst, current, v) {
+      (_, Some(a), Literal(b)) => match inst.op().map(|op| op(a, b)) {
+        Some(Ok(n)) if n == a => None,
+        Some(Ok(n)) => Some(Set(r, Literal(n))),
+        _ => Some(inst.with_value(v)),
+      },
+      (Set(_, Value::Register(s)), _, _) if s == r => None,
+      (OptInst::Inst(Add(..)), _, Literal(0))
+      | (OptInst::Inst(Mul(..) | Div(..)), _, Literal(1)) => None,
+      (OptInst::Inst(Add(..)), Some(0), _) | (OptInst::Inst(Mul(..)), Some(1), _) => {
+        Some(Set(r, v))
+      }
+      (OptInst::Inst(Mul(..)), Some(0), _) | (OptInst::Inst(Mul(..)), _, Literal(0)) => {
+        Some(Set(r, Literal(0)))
       }
+      _ => Some(inst.with_value(v)),
+    };
+    if let Some(inst) = simplified {
+      known[r.index()] = match inst {
+        Set(_, Literal(n)) => Some(n),
+        _ => None,
+      };
+      folded.push(inst);
     }
   }
+  folded
+}
 
-  #[test]
-  fn test_mini() {
-    for i in 1..10 {
-      for j in 1..10 {
-        println!("my mini_program {} {} {}", i, j, my_mini_program(i, j));
+/// Works backwards, dropping writes to registers that are overwritten or never read again.
+fn drop_dead_writes(insts: &[OptInst], outputs: &[Register]) -> Vec<OptInst> {
+  let mut live = [false; 4];
+  for r in outputs {
+    live[r.index()] = true;
+  }
+  let mut kept = Vec::new();
+  for &inst in insts.iter().rev() {
+    match inst.operands() {
+      // `inp` always stays, so that later reads get the right input.
+      None => {
+        if let OptInst::Inst(Inst::Inp(r)) = inst {
+          live[r.index()] = false;
+        }
+      }
+      Some((r, _)) if !live[r.index()] => continue,
+      Some((r, v)) => {
+        live[r.index()] = !matches!(inst, OptInst::Set(..));
+        if let Value::Register(s) = v {
+          live[s.index()] = true;
+        }
+      }
+    }
+    kept.push(inst);
+  }
+  kept.reverse();
+  kept
+}
+
+type Step =
+  Box<dyn Fn(&mut [i64; 4], &mut std::slice::Iter<i64>) -> std::result::Result<(), AluErrorKind>>;
+
+/// A program turned into a chain of closures, each specialised to its registers and literal.
+pub struct Compiled(Vec<Step>);
+
+/// Takes a plain program or an optimised one.
+pub fn compile<I: Copy + std::convert::Into<OptInst>>(insts: &[I]) -> Compiled {
+  let step = |inst: OptInst| -> Step {
+    match (inst.op(), inst.operands()) {
+      (Some(op), Some((r, Value::Literal(b)))) => {
+        let r = r.index();
+        Box::new(move |regs, _| {
+          regs[r] = op(regs[r], b)?;
+          Ok(())
+        })
+      }
+      (Some(op), Some((r, Value::Register(s)))) => {
+        let (r, s) = (r.index(), s.index());
+        Box::new(move |regs, _| {
+          regs[r] = op(regs[r], regs[s])?;
+          Ok(())
+        })
+      }
+      _ => {
+        let r = match inst {
+          OptInst::Inst(Inst::Inp(r)) => r.index(),
+          _ => unreachable!("only inp has no op"),
+        };
+        Box::new(move |regs, inputs| {
+          regs[r] = *inputs.next().ok_or(AluErrorKind::InputExhausted)?;
+          Ok(())
+        })
+      }
+    }
+  };
+  Compiled(insts.iter().map(|&inst| step(inst.into())).collect())
+}
+
+impl Compiled {
+  /// Errors point into the program as compiled, which may be the optimised one.
+  pub fn run(&self, inputs: &[i64]) -> std::result::Result<Alu, AluError> {
+    let mut regs = [0; 4];
+    let mut inputs = inputs.iter();
+    for (index, step) in self.0.iter().enumerate() {
+      step(&mut regs, &mut inputs).map_err(|kind| AluError { index, kind })?;
+    }
+    let [w, x, y, z] = regs;
+    Ok(Alu { w, x, y, z })
+  }
+}
+
+pub fn parse_register(input: &str) -> IResult<&str, Register> {
+  alt((
+    value(Register::W, char('w')),
+    value(Register::X, char('x')),
+    value(Register::Y, char('y')),
+    value(Register::Z, char('z')),
+  ))(input)
+}
+
+pub fn parse_value(input: &str) -> IResult<&str, Value> {
+  let literal = map(parse_i64, Value::Literal);
+  let register = map(parse_register, Value::Register);
+  alt((literal, register))(input)
+}
+
+fn parse_binary<'a>(
+  name: &'static str,
+  inst: fn(Register, Value) -> Inst,
+) -> impl FnMut(&'a str) -> IResult<&'a str, Inst> {
+  map(
+    tuple((tag(name), space1, parse_register, space1, parse_value)),
+    move |(_, _, r, _, v)| inst(r, v),
+  )
+}
+
+fn parse_input(input: &str) -> IResult<&str, Input> {
+  let unary = map(preceded(tag("inp "), parse_register), Inst::Inp);
+  let inst = alt((
+    unary,
+    parse_binary("add", Inst::Add),
+    parse_binary("mul", Inst::Mul),
+    parse_binary("div", Inst::Div),
+    parse_binary("mod", Inst::Mod),
+    parse_binary("eql", Inst::Eql),
+  ));
+  lines_of(inst)(input)
+}
+
+pub fn parse(input: &str) -> Result<Input> {
+  parse_all(parse_input)(input)
+}
+
+/// The three numbers that differ between MONAD's blocks, one block per input digit.
+///
+/// Each block treats z as a stack of base-26 digits.  A block that divides z by 1 pushes `w + add_y`.  A block
+/// that divides by 26 pops the top, and pushes again unless `w` equals the popped value plus `add_x`.
+#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+pub struct Block {
+  pub div_z: i64,
+  pub add_x: i64,
+  pub add_y: i64,
+}
+
+impl Block {
+  fn insts(self) -> [Inst; 18] {
+    use Register::*;
+    let lit = Value::Literal;
+    let reg = Value::Register;
+    [
+      Inst::Inp(W),
+      Inst::Mul(X, lit(0)),
+      Inst::Add(X, reg(Z)),
+      Inst::Mod(X, lit(26)),
+      Inst::Div(Z, lit(self.div_z)),
+      Inst::Add(X, lit(self.add_x)),
+      Inst::Eql(X, reg(W)),
+      Inst::Eql(X, lit(0)),
+      Inst::Mul(Y, lit(0)),
+      Inst::Add(Y, lit(25)),
+      Inst::Mul(Y, reg(X)),
+      Inst::Add(Y, lit(1)),
+      Inst::Mul(Z, reg(Y)),
+      Inst::Mul(Y, lit(0)),
+      Inst::Add(Y, reg(W)),
+      Inst::Add(Y, lit(self.add_y)),
+      Inst::Mul(Y, reg(X)),
+      Inst::Add(Z, reg(Y)),
+    ]
+  }
+
+  fn from_insts(insts: &[Inst]) -> Option<Self> {
+    let literal = |i: usize| match insts.get(i)? {
+      Inst::Div(_, Value::Literal(n)) | Inst::Add(_, Value::Literal(n)) => Some(*n),
+      _ => None,
+    };
+    let block = Self {
+      div_z: literal(4)?,
+      add_x: literal(5)?,
+      add_y: literal(15)?,
+    };
+    (insts == block.insts()).then_some(block)
+  }
+}
+
+/// Splits a MONAD program into its blocks, or `None` if it isn't shaped like one.
+pub fn blocks(insts: &[Inst]) -> Option<Vec<Block>> {
+  if insts.is_empty() || !insts.len().is_multiple_of(18) {
+    return None;
+  }
+  insts.chunks(18).map(Block::from_insts).collect()
+}
+
+/// The largest and smallest model numbers that leave z at 0.
+///
+/// z only ends at 0 if every popping block matches, so each pairs up with the block whose push it pops, and
+/// that fixes the difference between their two digits.
+pub fn model_numbers(blocks: &[Block]) -> Option<(i64, i64)> {
+  let mut largest = vec![0; blocks.len()];
+  let mut smallest = vec![0; blocks.len()];
+  let mut stack = Vec::new();
+  for (i, b) in blocks.iter().enumerate() {
+    match b.div_z {
+      // A digit can't be 10 or more, so these always push.
+      1 if b.add_x > 9 => stack.push((i, b.add_y)),
+      26 => {
+        let (j, add_y) = stack.pop()?;
+        // digits[i] == digits[j] + diff
+        let diff = add_y + b.add_x;
+        if diff.abs() > 8 {
+          return None;
+        }
+        largest[j] = 9.min(9 - diff);
+        largest[i] = largest[j] + diff;
+        smallest[j] = 1.max(1 - diff);
+        smallest[i] = smallest[j] + diff;
       }
+      _ => return None,
     }
+  }
+  let number = |digits: Vec<i64>| digits.into_iter().fold(0, |n, d| n * 10 + d);
+  stack
+    .is_empty()
+    .then(|| (number(largest), number(smallest)))
+}
+
+pub fn p1(insts: Input) -> Result<i64> {
+  blocks(&insts)
+    .and_then(|b| model_numbers(&b))
+    .map(|(largest, _)| largest)
+    .ok_or(Error::NoSolution)
+}
+
+pub fn p2(insts: Input) -> Result<i64> {
+  blocks(&insts)
+    .and_then(|b| model_numbers(&b))
+    .map(|(_, smallest)| smallest)
+    .ok_or(Error::NoSolution)
+}
+
+pub struct Day;
+
+impl Solution for Day {
+  const YEAR: u32 = 2021;
+  const DAY: u32 = 24;
+  type Input<'a> = Input;
+  type Output1 = i64;
+  type Output2 = i64;
 
-    panic!("asf")
+  fn parse(input: &str) -> Result<Self::Input<'_>> {
+    parse(input)
   }
 
-  const TEST_INPUT: &'static str = "inp w
+  fn part1(input: Self::Input<'_>) -> Result<i64> {
+    p1(input)
+  }
+
+  fn part2(input: Self::Input<'_>) -> Result<i64> {
+    p2(input)
+  }
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use aoc_common::test_util::XorShift;
+
+  const TEST_INPUT: &str = "inp w
   add z w
   mod z 2
   div w 2
@@ -332,17 +879,6 @@ mod test {
   div w 2
   mod w 2";
 
-  #[test]
-  fn test_bigger() {
-    for i in 1..=9 {
-      for j in 1..=9 {
-        let b = bigger(i, 15, 15, 0);
-        println!("{} {} {} {}", i, j, b, bigger(j, 12, 5, b));
-      }
-    }
-    panic!("asf");
-  }
-
   #[test]
   fn test_parse() {
     let input = "inp w
@@ -351,15 +887,12 @@ mod test {
     div w -2";
     assert_eq!(
       parse(input).unwrap(),
-      (
-        "",
-        vec![
-          Inst::Inp(Register::W),
-          Inst::Add(Register::Z, Value::Register(Register::W)),
-          Inst::Mod(Register::Z, Value::Literal(2)),
-          Inst::Div(Register::W, Value::Literal(-2)),
-        ]
-      )
+      vec![
+        Inst::Inp(Register::W),
+        Inst::Add(Register::Z, Value::Register(Register::W)),
+        Inst::Mod(Register::Z, Value::Literal(2)),
+        Inst::Div(Register::W, Value::Literal(-2)),
+      ]
     )
   }
 
@@ -367,22 +900,22 @@ mod test {
   fn test_alu() {
     let input = "inp x
     mul x -1";
-    let insts = parse(input).unwrap().1;
+    let insts = parse(input).unwrap();
     let mut alu = Alu::new();
-    alu.run_program(&insts, vec![8]);
+    alu.run_program(&insts, vec![8]).unwrap();
     assert_eq!(alu.x, -8);
 
     let input = "inp z
     inp x
     mul z 3
     eql z x";
-    let insts = parse(input).unwrap().1;
+    let insts = parse(input).unwrap();
     let mut alu = Alu::new();
-    alu.run_program(&insts, vec![3, 9]);
+    alu.run_program(&insts, vec![3, 9]).unwrap();
     assert_eq!(alu.z, 1);
 
     let mut alu = Alu::new();
-    alu.run_program(&insts, vec![3, 8]);
+    alu.run_program(&insts, vec![3, 8]).unwrap();
     assert_eq!(alu.z, 0);
 
     let input = "inp w
@@ -397,9 +930,9 @@ mod test {
     div w 2
     mod w 2";
 
-    let insts = parse(input).unwrap().1;
+    let insts = parse(input).unwrap();
     let mut alu = Alu::new();
-    alu.run_program(&insts, vec![9]);
+    alu.run_program(&insts, vec![9]).unwrap();
     assert_eq!(
       alu,
       Alu {
@@ -411,7 +944,7 @@ mod test {
     );
 
     let mut alu = Alu::new();
-    alu.run_program(&insts, vec![7]);
+    alu.run_program(&insts, vec![7]).unwrap();
     assert_eq!(
       alu,
       Alu {
@@ -423,7 +956,7 @@ mod test {
     );
 
     let mut alu = Alu::new();
-    alu.run_program(&insts, vec![5]);
+    alu.run_program(&insts, vec![5]).unwrap();
     assert_eq!(
       alu,
       Alu {
@@ -435,21 +968,359 @@ mod test {
     );
   }
 
+  #[test]
+  fn test_blocks() {
+    let input = std::fs::read_to_string("./inputs/d24.txt").unwrap();
+    let found = blocks(&parse(&input).unwrap()).unwrap();
+    assert_eq!(found.len(), 14);
+    assert_eq!(
+      found[0],
+      Block {
+        div_z: 1,
+        add_x: 15,
+        add_y: 15
+      }
+    );
+    assert_eq!(found.iter().filter(|b| b.div_z == 26).count(), 7);
+
+    assert_eq!(blocks(&parse(TEST_INPUT).unwrap()), None);
+  }
+
+  #[test]
+  fn test_model_numbers() {
+    // Push the first digit plus 3, then pop it wanting the second digit to be 1 less: 98 and 21 are the extremes.
+    let blocks = [
+      Block {
+        div_z: 1,
+        add_x: 11,
+        add_y: 3,
+      },
+      Block {
+        div_z: 26,
+        add_x: -4,
+        add_y: 5,
+      },
+    ];
+    assert_eq!(model_numbers(&blocks), Some((98, 21)));
+    let program = blocks.iter().flat_map(|b| b.insts()).collect::<Vec<_>>();
+    for digits in [vec![9, 8], vec![2, 1]] {
+      let mut alu = Alu::new();
+      alu.run_program(&program, digits).unwrap();
+      assert_eq!(alu.z, 0);
+    }
+
+    assert_eq!(model_numbers(&blocks[..1]), None);
+  }
+
+  #[test]
+  fn test_interval_ops() {
+    let i = Interval::new;
+    assert_eq!(i(-2, 3) * i(-4, 5), i(-12, 15));
+    assert_eq!(i(-7, 20) / i(2, 3), i(-3, 10));
+    assert_eq!(i(5, 9) / i(-1, 0), i(-9, -5));
+    assert_eq!(i(27, 30) % Interval::from(26), i(1, 4));
+    assert_eq!(i(20, 30) % Interval::from(26), i(0, 25));
+    assert_eq!(i(3, 4) % i(5, 9), i(3, 4));
+    assert_eq!(i(1, 9).eql(Interval::from(10)), Interval::from(0));
+    assert_eq!(i(1, 9).eql(Interval::from(4)), i(0, 1));
+    assert_eq!(
+      Interval::from(i64::MAX) + Interval::from(1),
+      Interval::from(i64::MAX)
+    );
+  }
+
+  #[test]
+  fn test_intervals() {
+    let insts = parse(
+      "inp w
+      mul x 0
+      add x 3
+      add y w
+      mul y 2
+      eql w 10",
+    )
+    .unwrap();
+    let mut alu = Alu::intervals();
+    alu.run_program(&insts, &[Interval::DIGIT]);
+    assert_eq!(alu.x.constant(), Some(3));
+    assert_eq!(alu.w.constant(), Some(0));
+    assert_eq!(alu.y, Interval::new(2, 18));
+
+    // Agrees with the concrete ALU wherever the inputs are known.
+    let mut concrete = Alu::new();
+    concrete.run_program(&insts, vec![7]).unwrap();
+    let mut alu = Alu::intervals();
+    alu.run_program(&insts, &[Interval::from(7)]);
+    assert_eq!(alu.y.constant(), Some(concrete.y));
+  }
+
+  #[test]
+  fn test_z_can_be_zero() {
+    let input = std::fs::read_to_string("./inputs/d24.txt").unwrap();
+    let insts = parse(&input).unwrap();
+    let digits = |n: i64| {
+      n.to_string()
+        .bytes()
+        .map(|b| (b - b'0') as i64)
+        .collect::<Vec<_>>()
+    };
+    assert!(z_can_be_zero(&insts, &digits(49917929934999)));
+    assert!(!z_can_be_zero(&insts, &digits(49917929934998)));
+    assert!(z_can_be_zero(&insts, &digits(4991792993499)));
+    // The last digit can't fix a wrong thirteenth.
+    assert!(!z_can_be_zero(&insts, &digits(4991792993498)));
+  }
+
+  fn register(rng: &mut XorShift) -> Register {
+    Register::ALL[rng.range(0, 3) as usize]
+  }
+
+  fn run(insts: &[Inst], inputs: &[i64]) -> std::result::Result<Alu, AluError> {
+    let mut alu = Alu::new();
+    alu.run_program(insts, inputs.to_vec())?;
+    Ok(alu)
+  }
+
+  /// Interprets an optimised program, as `run` does a plain one.
+  fn run_optimised(insts: &[OptInst], inputs: &[i64]) -> std::result::Result<Alu, AluError> {
+    let mut alu = Alu::new();
+    let mut inputs = inputs.iter().copied();
+    for (index, &inst) in insts.iter().enumerate() {
+      match inst {
+        OptInst::Inst(inst) => alu
+          .execute(inst, &mut inputs)
+          .map_err(|kind| AluError { index, kind })?,
+        OptInst::Set(r, v) => alu.write_register(r, alu.get_value(v)),
+      }
+    }
+    Ok(alu)
+  }
+
+  #[test]
+  fn test_optimise() {
+    use OptInst::Set;
+    use Register::*;
+    let inp = |r| OptInst::Inst(Inst::Inp(r));
+    let insts = parse(
+      "inp w
+      mul x 0
+      add x z
+      add y 0
+      mul y 1
+      add x w
+      inp z
+      mul z 0
+      add z 2
+      mul z 13",
+    )
+    .unwrap();
+    assert_eq!(
+      optimise(&insts, &[X]),
+      vec![inp(W), Set(X, Value::Register(W)), inp(Z)]
+    );
+    assert_eq!(
+      optimise(&insts, &Register::ALL),
+      vec![
+        inp(W),
+        Set(X, Value::Register(W)),
+        inp(Z),
+        Set(Z, Value::Literal(26)),
+      ]
+    );
+
+    let input = std::fs::read_to_string("./inputs/d24.txt").unwrap();
+    let insts = parse(&input).unwrap();
+    assert!(optimise(&insts, &[Z]).len() < insts.len());
+  }
+
+  #[test]
+  fn test_equivalence() {
+    let mut rng = XorShift(0x2545_f491_4f6c_dd1d);
+    let input = std::fs::read_to_string("./inputs/d24.txt").unwrap();
+    let monad = parse(&input).unwrap();
+    let optimised = optimise(&monad, &[Register::Z]);
+    let compiled = compile(&optimised);
+    for _ in 0..1000 {
+      let digits = (0..14).map(|_| rng.range(1, 9)).collect::<Vec<_>>();
+      let expected = run(&monad, &digits).map(|alu| alu.z);
+      assert_eq!(
+        run_optimised(&optimised, &digits).map(|alu| alu.z),
+        expected
+      );
+      assert_eq!(compiled.run(&digits).map(|alu| alu.z), expected);
+    }
+
+    // Short random programs, keeping every register.  The optimiser only promises to agree on valid runs.
+    let mut valid = 0;
+    for _ in 0..1000 {
+      let insts = (0..20)
+        .map(|_| {
+          let r = register(&mut rng);
+          let v = if rng.range(0, 1) == 0 {
+            Value::Literal(rng.range(-5, 5))
+          } else {
+            Value::Register(register(&mut rng))
+          };
+          let divisor = Value::Literal(rng.range(0, 5));
+          match rng.range(0, 5) {
+            0 => Inst::Inp(r),
+            1 => Inst::Add(r, v),
+            2 => Inst::Mul(r, v),
+            3 => Inst::Div(r, divisor),
+            4 => Inst::Mod(r, divisor),
+            _ => Inst::Eql(r, v),
+          }
+        })
+        .collect::<Vec<_>>();
+      let inputs = (0..20).map(|_| rng.range(-9, 9)).collect::<Vec<_>>();
+      let expected = run(&insts, &inputs);
+      assert_eq!(compile(&insts).run(&inputs), expected);
+      if let Ok(expected) = expected {
+        valid += 1;
+        let optimised = optimise(&insts, &Register::ALL);
+        assert_eq!(
+          run_optimised(&optimised, &inputs),
+          Ok(expected),
+          "{:?}",
+          insts
+        );
+        assert_eq!(compile(&optimised).run(&inputs), Ok(expected));
+      }
+    }
+    assert!(valid > 100);
+  }
+
+  #[test]
+  fn test_display() {
+    let input = "inp w
+add z w
+mod z 2
+div w -2";
+    let insts = parse(input).unwrap();
+    assert_eq!(
+      insts.iter().map(|i| i.to_string()).collect::<Vec<_>>(),
+      input.lines().collect::<Vec<_>>()
+    );
+  }
+
+  #[test]
+  fn test_debugger() {
+    let insts = parse(TEST_INPUT).unwrap();
+    let mut debugger = Debugger::new(&insts, vec![5]);
+    assert_eq!(
+      debugger.step(),
+      Ok(Some(TraceRow {
+        index: 0,
+        inst: Inst::Inp(Register::W),
+        alu: Alu {
+          w: 5,
+          x: 0,
+          y: 0,
+          z: 0
+        }
+      }))
+    );
+    debugger.break_on(Breakpoint::When(Box::new(|alu| alu.y == 2)));
+    assert_eq!(debugger.resume(), Ok(Stop::Breakpoint(5)));
+    assert_eq!(debugger.alu().z, 1);
+    assert_eq!(debugger.resume(), Ok(Stop::Finished));
+    assert_eq!(debugger.trace().len(), insts.len());
+    assert_eq!(debugger.step(), Ok(None));
+
+    let mut debugger = Debugger::new(&insts[..3], vec![5]);
+    debugger.resume().unwrap();
+    assert_eq!(
+      debugger.table(),
+      "    #  inst            w      x      y            z
+    0  inp w           5      0      0            0
+    1  add z w         5      0      0            5
+    2  mod z 2         5      0      0            1
+"
+    );
+  }
+
+  #[test]
+  fn test_debugger_inp() {
+    let input = std::fs::read_to_string("./inputs/d24.txt").unwrap();
+    let insts = parse(&input).unwrap();
+    let digits = vec![4, 9, 9, 1, 7, 9, 2, 9, 9, 3, 4, 9, 9, 9];
+    let mut debugger = Debugger::new(&insts, digits.clone());
+    debugger.break_on(Breakpoint::Inp);
+    let mut stops = Vec::new();
+    while let Ok(Stop::Breakpoint(i)) = debugger.resume() {
+      stops.push((i, debugger.alu().z));
+    }
+    assert_eq!(stops.len(), 13);
+    assert_eq!(stops[0], (18, 4 + 15));
+    assert_eq!(debugger.alu().z, 0);
+    assert_eq!(Ok(*debugger.alu()), run(&insts, &digits));
+  }
+
+  #[test]
+  fn test_alu_errors() {
+    let fails = |program: &str, inputs: Vec<i64>| {
+      let insts = parse(program).unwrap();
+      let mut alu = Alu::new();
+      let err = alu.run_program(&insts, inputs.clone()).unwrap_err();
+      assert_eq!(compile(&insts).run(&inputs), Err(err));
+      let mut debugger = Debugger::new(&insts, inputs);
+      assert_eq!(debugger.resume(), Err(err));
+      assert_eq!(debugger.trace().len(), err.index);
+      err
+    };
+    assert_eq!(
+      fails("inp x\ndiv w x", vec![0]),
+      AluError {
+        index: 1,
+        kind: AluErrorKind::DivByZero
+      }
+    );
+    assert_eq!(
+      fails("inp x\nadd y -3\nmod y x", vec![2]).kind,
+      AluErrorKind::NegativeMod
+    );
+    assert_eq!(fails("mod y -3", vec![]).kind, AluErrorKind::NegativeMod);
+    assert_eq!(
+      fails("inp x\ninp y", vec![1]),
+      AluError {
+        index: 1,
+        kind: AluErrorKind::InputExhausted
+      }
+    );
+    assert_eq!(
+      fails("inp x", vec![]).to_string(),
+      "ran out of input at instruction 0"
+    );
+  }
+
   #[test]
   fn test_p1() {
     let input = std::fs::read_to_string("./inputs/d24.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
-    assert_eq!(p1(parsed), 0);
+    let parsed = parse(&input).unwrap();
+    assert_eq!(p1(parsed), Ok(49917929934999));
   }
 
   #[test]
   fn test_p2() {
     let input = TEST_INPUT;
-    let parsed = parse(input).unwrap().1;
-    assert_eq!(p2(parsed), 0);
+    let parsed = parse(input).unwrap();
+    assert_eq!(p2(parsed), Err(Error::NoSolution));
 
     let input = std::fs::read_to_string("./inputs/d24.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
-    assert_eq!(p2(parsed), 0);
+    let parsed = parse(&input).unwrap();
+    assert_eq!(p2(parsed), Ok(11911316711816));
+  }
+
+  #[test]
+  fn test_model_numbers_on_alu() {
+    let input = std::fs::read_to_string("./inputs/d24.txt").unwrap();
+    let insts = parse(&input).unwrap();
+    let (largest, smallest) = model_numbers(&blocks(&insts).unwrap()).unwrap();
+    for n in [largest, smallest] {
+      let digits = n.to_string().bytes().map(|b| (b - b'0') as i64).collect();
+      let mut alu = Alu::new();
+      alu.run_program(&insts, digits).unwrap();
+      assert_eq!(alu.z, 0);
+    }
   }
 }
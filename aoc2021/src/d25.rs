@@ -1,6 +1,9 @@
 //! https://adventofcode.com/2021/day/25
 
-use crate::nom_prelude::*;
+use aoc_common::error::Result;
+use aoc_common::grid::{char_grid, Grid};
+use aoc_common::nom_prelude::*;
+use aoc_common::solution::Solution;
 
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
 pub enum Cucumber {
@@ -8,137 +11,115 @@ pub enum Cucumber {
   South,
 }
 
-#[derive(Debug, Eq, PartialEq, Clone)]
-pub struct Grid<T> {
-  width: usize,
-  height: usize,
-  v: Vec<T>,
-}
-
-impl<T: Default> Grid<T> {
-  pub fn new(width: usize, height: usize) -> Self {
-    let v = (0..(width * height)).map(|_| T::default()).collect();
-    Self { width, height, v }
-  }
-
-  pub fn from_vec(v: Vec<T>, width: usize, height: usize) -> Option<Self> {
-    if v.len() == width * height {
-      Some(Self { width, height, v })
-    } else {
-      None
-    }
-  }
-
-  pub fn at(&self, x: usize, y: usize) -> &T {
-    &self.v[(y * self.width) + x]
-  }
-
-  pub fn at_mut(&mut self, x: usize, y: usize) -> &mut T {
-    &mut self.v[(y * self.width) + x]
-  }
-}
+type Input = Grid<Option<Cucumber>>;
 
-impl Grid<Option<Cucumber>> {
-  /// Returns true if step produced a change.
-  fn step(&self) -> (Self, bool) {
-    let mut east_grid = Grid::new(self.width, self.height);
-    let mut changed = false;
-    for x in 0..self.width {
-      for y in 0..self.height {
-        match self.at(x, y) {
-          Some(Cucumber::East) => {
-            if self.at((x + 1) % self.width, y).is_none() {
-              *east_grid.at_mut((x + 1) % self.width, y) = Some(Cucumber::East);
-              changed = true;
-            } else {
-              *east_grid.at_mut(x, y) = Some(Cucumber::East);
-            }
-          }
-          Some(Cucumber::South) => {
-            *east_grid.at_mut(x, y) = Some(Cucumber::South);
-          }
-          _ => (),
+/// Returns true if step produced a change.
+fn step(grid: &Input) -> (Input, bool) {
+  let (width, height) = (grid.width(), grid.height());
+  let mut east_grid = Grid::new(width, height, None);
+  let mut changed = false;
+  for (x, y) in grid.coords() {
+    match grid[(x, y)] {
+      Some(Cucumber::East) => {
+        let next = ((x + 1) % width, y);
+        if grid[next].is_none() {
+          east_grid[next] = Some(Cucumber::East);
+          changed = true;
+        } else {
+          east_grid[(x, y)] = Some(Cucumber::East);
         }
       }
-    }
-    let mut new = Grid::new(east_grid.width, east_grid.height);
-    for x in 0..east_grid.width {
-      for y in 0..east_grid.height {
-        match east_grid.at(x, y) {
-          Some(Cucumber::South) => {
-            if east_grid.at(x, (y + 1) % east_grid.height).is_none() {
-              *new.at_mut(x, (y + 1) % east_grid.height) = Some(Cucumber::South);
-              changed = true;
-            } else {
-              *new.at_mut(x, y) = Some(Cucumber::South);
-            }
-          }
-          Some(Cucumber::East) => {
-            *new.at_mut(x, y) = Some(Cucumber::East);
-          }
-          _ => (),
-        }
+      Some(Cucumber::South) => {
+        east_grid[(x, y)] = Some(Cucumber::South);
       }
+      None => (),
     }
-    (new, changed)
   }
-}
-
-impl std::fmt::Display for Grid<Option<Cucumber>> {
-  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-    for (i, c) in self.v.iter().enumerate() {
-      let d = match c {
-        None => ".",
-        Some(Cucumber::East) => ">",
-        Some(Cucumber::South) => "v",
-      };
-      if i % self.width == 0 {
-        write!(f, "\n")?;
+  let mut new = Grid::new(width, height, None);
+  for (x, y) in east_grid.coords() {
+    match east_grid[(x, y)] {
+      Some(Cucumber::South) => {
+        let next = (x, (y + 1) % height);
+        if east_grid[next].is_none() {
+          new[next] = Some(Cucumber::South);
+          changed = true;
+        } else {
+          new[(x, y)] = Some(Cucumber::South);
+        }
+      }
+      Some(Cucumber::East) => {
+        new[(x, y)] = Some(Cucumber::East);
       }
-      write!(f, "{}", d)?;
+      None => (),
     }
-    Ok(())
   }
+  (new, changed)
 }
 
-type Input = Grid<Option<Cucumber>>;
+pub fn render(grid: &Input) -> String {
+  grid.render(|c| match c {
+    None => '.',
+    Some(Cucumber::East) => '>',
+    Some(Cucumber::South) => 'v',
+  })
+}
 
-pub fn parse(input: &str) -> IResult<&str, Input> {
-  let cucumber = map(one_of(".>v"), |c| match c {
-    '.' => None,
-    '>' => Some(Cucumber::East),
-    'v' => Some(Cucumber::South),
-    _ => panic!("non-matching char"),
-  });
-  map(lines_of(many1(cucumber)), |xss| {
-    let height = xss.len();
-    let width = xss[0].len();
-    let xss = xss.into_iter().flatten().collect();
-    Grid::from_vec(xss, width, height).unwrap()
+fn parse_input(input: &str) -> IResult<&str, Input> {
+  char_grid(|c| match c {
+    '.' => Some(None),
+    '>' => Some(Some(Cucumber::East)),
+    'v' => Some(Some(Cucumber::South)),
+    _ => None,
   })(input)
 }
 
-pub fn p1(mut input: Input) -> usize {
+pub fn parse(input: &str) -> Result<Input> {
+  parse_all(parse_input)(input)
+}
+
+pub fn p1(mut input: Input) -> Result<usize> {
   let mut stopped = false;
   let mut count = 0;
   while !stopped {
-    let (next_input, changed) = input.step();
+    let (next_input, changed) = step(&input);
     input = next_input;
     stopped = !changed;
     count += 1;
   }
-  count
+  Ok(count)
+}
+
+pub fn p2(_input: Input) -> Result<usize> {
+  Ok(0)
 }
 
-pub fn p2(_input: Input) -> usize {
-  0
+pub struct Day;
+
+impl Solution for Day {
+  const YEAR: u32 = 2021;
+  const DAY: u32 = 25;
+  type Input<'a> = Input;
+  type Output1 = usize;
+  type Output2 = usize;
+
+  fn parse(input: &str) -> Result<Self::Input<'_>> {
+    parse(input)
+  }
+
+  fn part1(input: Self::Input<'_>) -> Result<usize> {
+    p1(input)
+  }
+
+  fn part2(input: Self::Input<'_>) -> Result<usize> {
+    p2(input)
+  }
 }
 
 #[cfg(test)]
 mod test {
   use super::*;
 
-  const TEST_INPUT: &'static str = "v...>>.vv>
+  const TEST_INPUT: &str = "v...>>.vv>
 .vv>>.vv..
 >>.>v>...v
 >>v>>.>.v.
@@ -155,37 +136,35 @@ v.v..>>v.v
 v.v";
     assert_eq!(
       parse(input).unwrap(),
-      (
-        "",
-        Grid::from_vec(
-          vec![Some(East), None, None, Some(South), None, Some(South)],
-          3,
-          2
-        )
-        .unwrap()
+      Grid::from_vec(
+        vec![Some(East), None, None, Some(South), None, Some(South)],
+        3,
+        2
       )
-    )
+      .unwrap()
+    );
+    assert_eq!(render(&parse(input).unwrap()), ">..\nv.v\n");
   }
 
   #[test]
   fn test_p1() {
     let input = TEST_INPUT;
-    let parsed = parse(input).unwrap().1;
-    assert_eq!(p1(parsed), 58);
+    let parsed = parse(input).unwrap();
+    assert_eq!(p1(parsed), Ok(58));
 
     let input = std::fs::read_to_string("./inputs/d25.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
-    assert_eq!(p1(parsed), 429);
+    let parsed = parse(&input).unwrap();
+    assert_eq!(p1(parsed), Ok(429));
   }
 
   #[test]
   fn test_p2() {
     let input = TEST_INPUT;
-    let parsed = parse(input).unwrap().1;
-    assert_eq!(p2(parsed), 0);
+    let parsed = parse(input).unwrap();
+    assert_eq!(p2(parsed), Ok(0));
 
     let input = std::fs::read_to_string("./inputs/d25.txt").unwrap();
-    let parsed = parse(&input).unwrap().1;
-    assert_eq!(p2(parsed), 0);
+    let parsed = parse(&input).unwrap();
+    assert_eq!(p2(parsed), Ok(0));
   }
 }
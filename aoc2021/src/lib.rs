@@ -24,6 +24,58 @@ pub mod d23;
 pub mod d24;
 pub mod d25;
 
-pub mod nom_prelude;
-pub mod util;
-pub mod vector;
+use aoc_common::solution::Registry;
+
+/// Registers every day of 2021.
+pub fn register(registry: &mut Registry) {
+  registry
+    .register::<d01::Day>()
+    .register::<d02::Day>()
+    .register::<d03::Day>()
+    .register::<d04::Day>()
+    .register::<d05::Day>()
+    .register::<d06::Day>()
+    .register::<d07::Day>()
+    .register::<d08::Day>()
+    .register::<d09::Day>()
+    .register::<d10::Day>()
+    .register::<d11::Day>()
+    .register::<d12::Day>()
+    .register::<d13::Day>()
+    .register::<d14::Day>()
+    .register::<d15::Day>()
+    .register::<d16::Day>()
+    .register::<d17::Day>()
+    .register::<d18::Day>()
+    .register::<d19::Day>()
+    .register::<d20::Day>()
+    .register::<d21::Day>()
+    .register::<d22::Day>()
+    .register::<d23::Day>()
+    .register::<d24::Day>()
+    .register::<d25::Day>();
+}
+
+#[cfg(test)]
+mod test {
+  use super::*;
+  use aoc_common::solution::Part;
+
+  #[test]
+  fn test_register() {
+    let mut registry = Registry::new();
+    register(&mut registry);
+    assert_eq!(
+      registry.days().collect::<Vec<_>>(),
+      (1..=25).map(|day| (2021, day)).collect::<Vec<_>>()
+    );
+
+    let input = std::fs::read_to_string("./inputs/d01.txt").unwrap();
+    assert_eq!(
+      registry
+        .solve(2021, 1, Part::Two, &input)
+        .map(|r| r.map(|a| a.answer)),
+      Some(Ok("1653".to_owned()))
+    );
+  }
+}